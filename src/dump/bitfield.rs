@@ -69,15 +69,19 @@ impl Bitfield {
                 normalized
             };
 
-            imp
+            let mut getter = imp
                 .line(format_args!("// get {}", field))?
-                .function_args_ret("pub ", format_args!("is_{}", normalized), args!("&self"), "bool")?
-                .line(format_args!("is_bit_set(self.{}, {})", name, bit))?;
+                .function_args_ret("pub ", format_args!("is_{}", normalized), args!("&self"), "bool")?;
 
-            imp
+            getter.line(format_args!("is_bit_set(self.{}, {})", name, bit))?;
+            getter.finish()?;
+
+            let mut setter = imp
                 .line(format_args!("// set {}", field))?
-                .function_args("pub ", format_args!("set_{}", normalized), args!("&mut self", [("value", "bool")].iter()))?
-                .line(format_args!("set_bit(&mut self.{}, {}, value);", name, bit))?;
+                .function_args("pub ", format_args!("set_{}", normalized), args!("&mut self", [("value", "bool")].iter()))?;
+
+            setter.line(format_args!("set_bit(&mut self.{}, {}, value);", name, bit))?;
+            setter.finish()?;
         }
 
         Ok(())
@@ -134,6 +138,8 @@ impl Bitfields {
             bitfield.emit(&mut imp, &name)?;
         }
 
+        imp.finish()?;
+
         Ok(())
     }
 }
@@ -1,10 +1,15 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, Write};
+use std::ops::Range;
+use std::rc::Rc;
 
 macro_rules! ind {
-    ($writer:expr, $fmt:expr, $($arg:tt),*) => (
-        write!($writer.writer, concat!("{:indent$}", $fmt), "", $($arg,)* indent=$writer.indent)
-    );
+    ($writer:expr, $fmt:expr, $($arg:tt),*) => ({
+        let indent = $writer.indent;
+        write!($writer, concat!("{:indent$}", $fmt), "", $($arg,)* indent=indent)
+    });
 
     ($writer:expr, $fmt:expr) => (ind!($writer, $fmt,));
 }
@@ -14,18 +19,83 @@ macro_rules! ind_ln {
     ($writer:expr, $fmt:expr) => (ind_ln!($writer, $fmt,));
 }
 
+/// The byte range of a top-level item, e.g., "struct Object" or
+/// "impl Deref for Actor".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub name: String,
+    pub range: Range<usize>,
+}
+
+/// Shared between a `Writer` and all of its nested writers so that bytes
+/// written at any depth advance the same position.
+#[derive(Default)]
+struct SpanTracker {
+    position: Cell<usize>,
+    open: RefCell<Option<(String, usize)>>,
+    spans: RefCell<Vec<Span>>,
+}
+
 pub struct Writer<W: Write> {
     writer: W,
     indent: usize,
+    depth: usize,
+    column_limit: Option<usize>,
+    spans: Option<Rc<SpanTracker>>,
 }
 
 impl<W: Write> Writer<W> {
     const INDENT: usize = 4;
 
+    /// Wrap function arguments one per line when a signature would be wider
+    /// than `limit` columns.
+    pub fn column_limit(mut self, limit: usize) -> Self {
+        self.column_limit = Some(limit);
+        self
+    }
+
+    /// Record a `Span` for every top-level item written from now on.
+    pub fn track_spans(mut self) -> Self {
+        self.spans = Some(Rc::default());
+        self
+    }
+
+    /// The spans of the top-level items closed so far. Positions count every
+    /// byte written through this writer, including bytes that were flushed
+    /// from an in-memory scope.
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans
+            .as_ref()
+            .map_or_else(Vec::new, |tracker| tracker.spans.borrow().clone())
+    }
+
+    fn begin_item(&mut self, name: impl Display) {
+        if self.depth == 0 {
+            if let Some(tracker) = &self.spans {
+                let start = tracker.position.get();
+                *tracker.open.borrow_mut() = Some((name.to_string(), start));
+            }
+        }
+    }
+
+    fn end_item(&mut self) {
+        if self.depth == 1 {
+            if let Some(tracker) = &self.spans {
+                if let Some((name, start)) = tracker.open.borrow_mut().take() {
+                    let range = start..tracker.position.get();
+                    tracker.spans.borrow_mut().push(Span { name, range });
+                }
+            }
+        }
+    }
+
     pub fn nest(&mut self) -> Writer<&mut W> {
         Writer {
             writer: &mut self.writer,
             indent: self.indent + Self::INDENT,
+            depth: self.depth + 1,
+            column_limit: self.column_limit,
+            spans: self.spans.clone(),
         }
     }
 
@@ -40,7 +110,29 @@ impl<W: Write> Writer<W> {
 
 impl<W: Write> From<W> for Writer<W> {
     fn from(writer: W) -> Self {
-        Self { writer, indent: 0 }
+        Self {
+            writer,
+            indent: 0,
+            depth: 0,
+            column_limit: None,
+            spans: None,
+        }
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        let written = self.writer.write(buf)?;
+
+        if let Some(tracker) = &self.spans {
+            tracker.position.set(tracker.position.get() + written);
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.writer.flush()
     }
 }
 
@@ -61,7 +153,34 @@ pub trait WriterWrapper<W: Write> {
 
     fn raw(&mut self, raw: impl Display) -> Result<&mut Self, io::Error> {
         let writer = self.writer();
-        write!(writer.writer, "{}", raw)?;
+        write!(writer, "{}", raw)?;
+        Ok(self)
+    }
+
+    /// Emit `comment` as `//` line comments, one per line of input.
+    fn comment(&mut self, comment: impl Display) -> Result<&mut Self, io::Error> {
+        let comment = comment.to_string();
+        let writer = self.writer();
+
+        if comment.is_empty() {
+            ind_ln!(writer, "//")?;
+        }
+
+        for line in comment.lines() {
+            if line.is_empty() {
+                ind_ln!(writer, "//")?;
+            } else {
+                ind_ln!(writer, "// {}", line)?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Emit a macro invocation statement, `name!(args);`.
+    fn mac(&mut self, name: impl Display, args: impl Display) -> Result<&mut Self, io::Error> {
+        let writer = self.writer();
+        ind_ln!(writer, "{}!({});", name, args)?;
         Ok(self)
     }
 
@@ -83,26 +202,59 @@ pub trait GenFunction<W: Write>: WriterWrapper<W> {
         self.function_args(qualifiers, name, None::<(Nil, Nil)>)
     }
 
+    /// Returns the width of the header so that the arguments know where they
+    /// start.
     fn write_function_header(
         &mut self,
         qualifiers: impl Display,
         name: impl Display,
-    ) -> Result<(), io::Error> {
+    ) -> Result<usize, io::Error> {
+        let header = format!("{}fn {}(", qualifiers, name);
         let writer = self.writer();
-        ind!(writer, "{}fn {}(", qualifiers, name)?;
-        Ok(())
+        writer.begin_item(format_args!("fn {}", name));
+        ind!(writer, "{}", header)?;
+        Ok(header.len())
     }
 
+    /// `header_width` and `trailer_width` are the widths of the signature
+    /// text on either side of the arguments.
     fn write_function_args<N: Display, T: Display>(
         &mut self,
+        header_width: usize,
+        trailer_width: usize,
         args: impl IntoIterator<Item = impl Into<Arg<N, T>>>,
     ) -> Result<(), io::Error> {
+        let args: Vec<String> = args
+            .into_iter()
+            .map(|arg| match arg.into() {
+                Arg::Receiver(receiver) => receiver.to_string(),
+                Arg::RefReceiver(receiver) => format!("{}self", receiver),
+                Arg::NameType(name, typ) => format!("{}: {}", name, typ),
+            })
+            .collect();
+
         let writer = self.writer();
 
-        for arg in args {
-            match arg.into() {
-                Arg::Receiver(receiver) => write!(writer.writer, "{}, ", receiver)?,
-                Arg::NameType(name, typ) => write!(writer.writer, "{}: {}, ", name, typ)?,
+        let width = writer.indent
+            + header_width
+            + args.iter().map(|arg| arg.len() + ", ".len()).sum::<usize>()
+            + trailer_width;
+
+        let wrap = !args.is_empty() && writer.column_limit.map_or(false, |limit| width > limit);
+
+        if wrap {
+            writeln!(writer)?;
+            writer.indent();
+
+            for arg in &args {
+                ind_ln!(writer, "{},", arg)?;
+            }
+
+            writer.undent();
+            ind!(writer, "")?;
+        } else {
+            for arg in &args {
+                write!(writer, "{}, ", arg)?;
             }
         }
 
@@ -115,14 +267,17 @@ pub trait GenFunction<W: Write>: WriterWrapper<W> {
         name: impl Display,
         args: impl IntoIterator<Item = impl Into<Arg<N, T>>>,
     ) -> Result<Function<&mut W>, io::Error> {
-        self.write_function_header(qualifiers, name)?;
-        self.write_function_args(args)?;
+        const TRAILER: &str = ") {";
+
+        let header_width = self.write_function_header(qualifiers, name)?;
+        self.write_function_args(header_width, TRAILER.len(), args)?;
 
         let writer = self.writer();
-        writeln!(writer.writer, ") {{")?;
+        writeln!(writer, "{}", TRAILER)?;
 
         Ok(Function {
             writer: writer.nest(),
+            closed: false,
         })
     }
 
@@ -133,14 +288,17 @@ pub trait GenFunction<W: Write>: WriterWrapper<W> {
         args: impl IntoIterator<Item = impl Into<Arg<N, T>>>,
         ret: impl Display,
     ) -> Result<Function<&mut W>, io::Error> {
-        self.write_function_header(qualifiers, name)?;
-        self.write_function_args(args)?;
+        let trailer = format!(") -> {} {{", ret);
+
+        let header_width = self.write_function_header(qualifiers, name)?;
+        self.write_function_args(header_width, trailer.len(), args)?;
 
         let writer = self.writer();
-        writeln!(writer.writer, ") -> {} {{", ret)?;
+        writeln!(writer, "{}", trailer)?;
 
         Ok(Function {
             writer: writer.nest(),
+            closed: false,
         })
     }
 }
@@ -152,10 +310,12 @@ pub trait Gen<W: Write>: WriterWrapper<W> {
         name: impl Display,
     ) -> Result<Structure<&mut W>, io::Error> {
         let writer = self.writer();
+        writer.begin_item(format_args!("struct {}", name));
         ind_ln!(writer, "{}struct {} {{", vis, name)?;
 
         Ok(Structure {
             writer: self.writer().nest(),
+            closed: false,
         })
     }
 
@@ -165,19 +325,23 @@ pub trait Gen<W: Write>: WriterWrapper<W> {
         name: impl Display,
     ) -> Result<Enumeration<&mut W>, io::Error> {
         let writer = self.writer();
+        writer.begin_item(format_args!("enum {}", name));
         ind_ln!(writer, "{}enum {} {{", vis, name)?;
 
         Ok(Enumeration {
             writer: self.writer().nest(),
+            closed: false,
         })
     }
 
     fn imp(&mut self, target: impl Display) -> Result<Impl<&mut W>, io::Error> {
         let writer = self.writer();
+        writer.begin_item(format_args!("impl {}", target));
         ind_ln!(writer, "impl {} {{", target)?;
 
         Ok(Impl {
             writer: self.writer().nest(),
+            closed: false,
         })
     }
 
@@ -187,10 +351,49 @@ pub trait Gen<W: Write>: WriterWrapper<W> {
         target: impl Display,
     ) -> Result<Impl<&mut W>, io::Error> {
         let writer = self.writer();
+        writer.begin_item(format_args!("impl {} for {}", r#trait, target));
         ind_ln!(writer, "impl {} for {} {{", r#trait, target)?;
 
         Ok(Impl {
             writer: self.writer().nest(),
+            closed: false,
+        })
+    }
+
+    /// `imp_generic("Wrapper", &["T: Copy"])` opens
+    /// `impl<T: Copy> Wrapper<T> {`.
+    fn imp_generic(
+        &mut self,
+        target: impl Display,
+        params: &[impl Display],
+    ) -> Result<Impl<&mut W>, io::Error> {
+        let (declarations, arguments) = generics(params);
+
+        let writer = self.writer();
+        writer.begin_item(format_args!("impl {}{}", target, arguments));
+        ind_ln!(writer, "impl{} {}{} {{", declarations, target, arguments)?;
+
+        Ok(Impl {
+            writer: self.writer().nest(),
+            closed: false,
+        })
+    }
+
+    fn imp_trait_generic(
+        &mut self,
+        r#trait: impl Display,
+        target: impl Display,
+        params: &[impl Display],
+    ) -> Result<Impl<&mut W>, io::Error> {
+        let (declarations, arguments) = generics(params);
+
+        let writer = self.writer();
+        writer.begin_item(format_args!("impl {} for {}{}", r#trait, target, arguments));
+        ind_ln!(writer, "impl{} {} for {}{} {{", declarations, r#trait, target, arguments)?;
+
+        Ok(Impl {
+            writer: self.writer().nest(),
+            closed: false,
         })
     }
 
@@ -198,17 +401,56 @@ pub trait Gen<W: Write>: WriterWrapper<W> {
         &mut self,
         prefix: impl Display,
         suffix: BlockSuffix,
+    ) -> Result<Block<&mut W>, io::Error> {
+        self.raw_block(prefix, '{', format_args!("}}{}", suffix))
+    }
+
+    /// A block delimited by `open` and `close` instead of braces, e.g.,
+    /// `raw_block("let x = ", '[', "];")` for an array literal.
+    fn raw_block(
+        &mut self,
+        header: impl Display,
+        open: impl Display,
+        close: impl Display,
     ) -> Result<Block<&mut W>, io::Error> {
         let writer = self.writer();
-        ind_ln!(writer, "{}{{", prefix)?;
+        writer.begin_item(&header);
+        ind_ln!(writer, "{}{}", header, open)?;
 
         Ok(Block {
             writer: self.writer().nest(),
-            suffix,
+            close: close.to_string(),
+            closed: false,
         })
     }
 }
 
+/// Split generic parameters like `["'a: 'b", "T: Copy", "const N: usize"]`
+/// into the declaration list `<'a: 'b, T: Copy, const N: usize>` and the
+/// argument list `<'a, T, N>`.
+fn generics(params: &[impl Display]) -> (String, String) {
+    if params.is_empty() {
+        return (String::new(), String::new());
+    }
+
+    let declarations: Vec<String> = params.iter().map(ToString::to_string).collect();
+
+    // The argument is the name alone, without `const`, bounds or a default.
+    let arguments: Vec<&str> = declarations
+        .iter()
+        .map(|d| {
+            let d = d.trim_start();
+            let d = d.strip_prefix("const ").unwrap_or(d);
+            d.split(|c| c == ':' || c == '=').next().unwrap_or(d).trim()
+        })
+        .collect();
+
+    (
+        format!("<{}>", declarations.join(", ")),
+        format!("<{}>", arguments.join(", ")),
+    )
+}
+
 macro_rules! impl_writer_wrapper {
     ($($structure:ident)+) => {
         $(
@@ -230,13 +472,33 @@ macro_rules! impl_gen {
     }
 }
 
-macro_rules! impl_closing_brace_drop {
+macro_rules! impl_closing_brace {
     ($($structure:ident)+) => {
         $(
+            impl<W: Write> $structure<W> {
+                fn close(&mut self) -> Result<(), io::Error> {
+                    if self.closed {
+                        return Ok(());
+                    }
+
+                    self.closed = true;
+                    self.writer.undent();
+                    ind_ln!(self.writer, "}}\n")?;
+                    self.writer.end_item();
+                    Ok(())
+                }
+
+                /// Write the closing brace now so that IO errors can be handled
+                /// instead of being swallowed on drop.
+                pub fn finish(mut self) -> Result<(), io::Error> {
+                    self.close()
+                }
+            }
+
             impl<W: Write> Drop for $structure<W> {
                 fn drop(&mut self) {
-                    self.writer.undent();
-                    ind_ln!(self.writer, "}}\n").unwrap();
+                    // Best effort. Use `finish()` to observe the error.
+                    let _ = self.close();
                 }
             }
         )+
@@ -245,13 +507,16 @@ macro_rules! impl_closing_brace_drop {
 
 impl_writer_wrapper! { Scope Structure Enumeration Impl Function IfBlock Block }
 impl_gen! { Scope Function IfBlock Block }
-impl_closing_brace_drop! { Structure Enumeration Impl Function IfBlock }
+impl_closing_brace! { Structure Enumeration Impl Function IfBlock }
 
 impl<W: Write> GenFunction<W> for Impl<W> {}
 
 pub enum Visibility {
     Private,
     Public,
+    PubCrate,
+    PubSuper,
+    PubIn(&'static str),
 }
 
 impl Default for Visibility {
@@ -265,17 +530,92 @@ impl Display for Visibility {
         match self {
             Self::Private => Ok(()),
             Self::Public => "pub ".fmt(f),
+            Self::PubCrate => "pub(crate) ".fmt(f),
+            Self::PubSuper => "pub(super) ".fmt(f),
+            Self::PubIn(path) => write!(f, "pub(in {}) ", path),
         }
     }
 }
 
 pub struct Scope<W: Write> {
     writer: Writer<W>,
+    imports: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl<W: Write> Scope<W> {
     pub fn new(writer: Writer<W>) -> Scope<W> {
-        Scope { writer }
+        Scope {
+            writer,
+            imports: BTreeMap::new(),
+        }
+    }
+
+    pub fn spans(&self) -> Vec<Span> {
+        self.writer.spans()
+    }
+
+    /// Queue `use path::{names};` for the next `write_imports()`. Paths and
+    /// names are deduplicated across calls.
+    pub fn import(&mut self, path: impl Display, names: &[impl Display]) -> &mut Self {
+        self.imports
+            .entry(path.to_string())
+            .or_default()
+            .extend(names.iter().map(ToString::to_string));
+
+        self
+    }
+
+    /// Write the queued imports sorted by path and name, then forget them.
+    pub fn write_imports(&mut self) -> Result<&mut Self, io::Error> {
+        if self.imports.is_empty() {
+            return Ok(self);
+        }
+
+        let imports = std::mem::take(&mut self.imports);
+
+        for (path, names) in imports {
+            // `self` goes first like rustfmt does, even though it sorts after
+            // the capitalized type names.
+            let names: Vec<String> = names
+                .iter()
+                .filter(|n| *n == "self")
+                .chain(names.iter().filter(|n| *n != "self"))
+                .cloned()
+                .collect();
+
+            match names.as_slice() {
+                [] => ind_ln!(self.writer, "use {};", path)?,
+                [name] => ind_ln!(self.writer, "use {}::{};", path, name)?,
+                names => {
+                    let names = names.join(", ");
+                    ind_ln!(self.writer, "use {}::{{{}}};", path, names)?;
+                }
+            }
+        }
+
+        writeln!(self.writer)?;
+
+        Ok(self)
+    }
+}
+
+impl Scope<Vec<u8>> {
+    /// Create a scope that buffers its output in memory until it's flushed.
+    pub fn in_memory() -> Scope<Vec<u8>> {
+        Scope::new(Writer::from(vec![]))
+    }
+
+    /// Write everything buffered so far to `writer` and clear the buffer.
+    pub fn flush_to(&mut self, mut writer: impl Write) -> Result<(), io::Error> {
+        writer.write_all(&self.writer.writer)?;
+        self.writer.writer.clear();
+        Ok(())
+    }
+}
+
+impl Display for Scope<Vec<u8>> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        String::from_utf8_lossy(&self.writer.writer).fmt(f)
     }
 }
 
@@ -294,6 +634,7 @@ macro_rules! args {
 
 pub enum Arg<N: Display, T: Display> {
     Receiver(&'static str),
+    RefReceiver(Ref<Nil>),
     NameType(N, T),
 }
 
@@ -315,8 +656,73 @@ impl<N: Display, T: Display, U: Into<Arg<N, T>> + Copy> From<&U> for Arg<N, T> {
     }
 }
 
+#[derive(Copy, Clone)]
+pub enum Lifetime {
+    Static,
+
+    /// The name without its leading apostrophe, e.g., `Named("a")` for `'a`.
+    Named(&'static str),
+}
+
+impl Display for Lifetime {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Static => "'static".fmt(f),
+            Self::Named(name) => write!(f, "'{}", name),
+        }
+    }
+}
+
+/// A reference type such as `&'a mut T`. Use `Ref<Nil>` with
+/// `Arg::RefReceiver` for a `&'a mut self` receiver.
+#[derive(Copy, Clone)]
+pub struct Ref<T: Display> {
+    lifetime: Option<Lifetime>,
+    mutable: bool,
+    referent: T,
+}
+
+impl<T: Display> Ref<T> {
+    pub fn shared(referent: T) -> Self {
+        Self {
+            lifetime: None,
+            mutable: false,
+            referent,
+        }
+    }
+
+    pub fn mutable(referent: T) -> Self {
+        Self {
+            mutable: true,
+            ..Self::shared(referent)
+        }
+    }
+
+    pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
+        self.lifetime = Some(lifetime);
+        self
+    }
+}
+
+impl<T: Display> Display for Ref<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        '&'.fmt(f)?;
+
+        if let Some(lifetime) = self.lifetime {
+            write!(f, "{} ", lifetime)?;
+        }
+
+        if self.mutable {
+            "mut ".fmt(f)?;
+        }
+
+        self.referent.fmt(f)
+    }
+}
+
 pub struct Structure<W: Write> {
     writer: Writer<W>,
+    closed: bool,
 }
 
 impl<W: Write> Structure<W> {
@@ -328,6 +734,7 @@ impl<W: Write> Structure<W> {
 
 pub struct Enumeration<W: Write> {
     writer: Writer<W>,
+    closed: bool,
 }
 
 impl<W: Write> Enumeration<W> {
@@ -339,12 +746,29 @@ impl<W: Write> Enumeration<W> {
 
 pub struct Impl<W: Write> {
     writer: Writer<W>,
+    closed: bool,
 }
 
-impl<W: Write> Impl<W> {}
+impl<W: Write> Impl<W> {
+    pub fn assoc_type(&mut self, name: impl Display, value: impl Display) -> Result<&mut Self, io::Error> {
+        ind_ln!(self.writer, "type {} = {};", name, value)?;
+        Ok(self)
+    }
+
+    pub fn assoc_const(
+        &mut self,
+        name: impl Display,
+        typ: impl Display,
+        value: impl Display,
+    ) -> Result<&mut Self, io::Error> {
+        ind_ln!(self.writer, "const {}: {} = {};", name, typ, value)?;
+        Ok(self)
+    }
+}
 
 pub struct Function<W: Write> {
     writer: Writer<W>,
+    closed: bool,
 }
 
 impl<W: Write> Function<W> {
@@ -353,12 +777,14 @@ impl<W: Write> Function<W> {
 
         Ok(IfBlock {
             writer: self.writer.nest(),
+            closed: false,
         })
     }
 }
 
 pub struct IfBlock<W: Write> {
     writer: Writer<W>,
+    closed: bool,
 }
 
 impl<W: Write> IfBlock<W> {
@@ -373,17 +799,35 @@ impl<W: Write> IfBlock<W> {
 
 pub struct Block<W: Write> {
     writer: Writer<W>,
-    suffix: BlockSuffix,
+    close: String,
+    closed: bool,
 }
 
 impl<W: Write> Block<W> {
+    fn close(&mut self) -> Result<(), io::Error> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.closed = true;
+        self.writer.undent();
+        let close = &self.close;
+        ind_ln!(self.writer, "{}\n", close)?;
+        self.writer.end_item();
+        Ok(())
+    }
+
+    /// Write the closing delimiter now so that IO errors can be handled
+    /// instead of being swallowed on drop.
+    pub fn finish(mut self) -> Result<(), io::Error> {
+        self.close()
+    }
 }
 
 impl<W: Write> Drop for Block<W> {
     fn drop(&mut self) {
-        self.writer.undent();
-        let suffix = self.suffix;
-        ind_ln!(self.writer, "}}{}\n", suffix).unwrap();
+        // Best effort. Use `finish()` to observe the error.
+        let _ = self.close();
     }
 }
 
@@ -402,6 +846,7 @@ impl Display for BlockSuffix {
     }
 }
 
+#[derive(Copy, Clone)]
 pub struct Nil;
 
 impl Display for Nil {
@@ -1,4 +1,7 @@
 use super::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::str;
 
 macro_rules! eq {
@@ -11,6 +14,46 @@ macro_rules! eq {
     };
 }
 
+/// Generate a test that runs `$body` against a fresh in-memory `Scope`, or the
+/// one `$new` makes, and compares the output with `$name.expected`.
+///
+/// ```ignore
+/// golden!(structure_empty, |scope| {
+///     scope.structure(Visibility::Private, "Test").unwrap();
+/// });
+/// ```
+macro_rules! golden {
+    ($name:ident, |$scope:ident| $body:block) => {
+        golden!($name, Scope::in_memory(), |$scope| $body);
+    };
+
+    ($name:ident, $new:expr, |$scope:ident| $body:block) => {
+        #[test]
+        fn $name() {
+            let mut $scope = $new;
+            $body
+            expect(stringify!($name), &$scope.to_string());
+        }
+    };
+}
+
+/// Compare `actual` with the contents of `name.expected`. Set `UPDATE_EXPECT`
+/// in the environment to rewrite the file with `actual` instead.
+fn expect(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), file!()].iter().collect();
+    let path = path.with_file_name(format!("{}.expected", name));
+
+    if env::var_os("UPDATE_EXPECT").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("unable to read {:?}: {}. Run with UPDATE_EXPECT=1 to create it.", path, e));
+
+    eq!(actual, expected, "Run with UPDATE_EXPECT=1 to accept the new output.");
+}
+
 #[test]
 fn scope_empty() {
     let mut buffer = vec![];
@@ -581,4 +624,366 @@ fn block_structure_and_init() {
     let buffer = str::from_utf8(&buffer).unwrap();
 
     eq!(buffer, include_str!("block_structure_and_init.expected"));
-}
\ No newline at end of file
+}
+#[test]
+fn scope_in_memory_empty() {
+    let scope = Scope::in_memory();
+
+    eq!(
+        scope.to_string(), "",
+        "A newly created in-memory Scope should not emit anything."
+    );
+}
+
+#[test]
+fn scope_in_memory_to_string() {
+    let mut scope = Scope::in_memory();
+
+    {
+        let mut structure = scope
+            .line("#[repr(C)]")
+            .unwrap()
+            .structure(Visibility::Private, "Test")
+            .unwrap();
+
+        structure.field("field1", "u32").unwrap();
+    }
+
+    eq!(scope.to_string(), "#[repr(C)]\nstruct Test {\n    field1: u32,\n}\n\n");
+}
+
+#[test]
+fn scope_in_memory_flush_to() {
+    let mut buffer = vec![];
+    let mut scope = Scope::in_memory();
+
+    scope.line("// First chunk.").unwrap();
+    scope.flush_to(&mut buffer).unwrap();
+
+    eq!(scope.to_string(), "", "Flushing should clear the in-memory buffer.");
+
+    scope.line("// Second chunk.").unwrap();
+    scope.flush_to(&mut buffer).unwrap();
+
+    let buffer = str::from_utf8(&buffer).unwrap();
+
+    eq!(buffer, "// First chunk.\n// Second chunk.\n");
+}
+
+#[test]
+fn scope_imports() {
+    let mut scope = Scope::in_memory();
+
+    scope
+        .import("std::ops", &["DerefMut", "Deref"])
+        .import("crate::game", &["FString", "self", "Array"])
+        .import("crate", &["GLOBAL_OBJECTS"])
+        .import("crate::game", &["Array", "NameIndex"])
+        .write_imports()
+        .unwrap()
+        .line("mod core;")
+        .unwrap();
+
+    eq!(scope.to_string(), include_str!("scope_imports.expected"));
+}
+
+#[test]
+fn scope_imports_none() {
+    let mut scope = Scope::in_memory();
+
+    scope.write_imports().unwrap();
+
+    eq!(
+        scope.to_string(), "",
+        "Writing imports without any queued should not emit anything."
+    );
+}
+
+#[test]
+fn structure_finish() {
+    let mut scope = Scope::in_memory();
+
+    {
+        let mut structure = scope.structure(Visibility::Private, "Test").unwrap();
+        structure.field("field1", "u32").unwrap();
+        structure.finish().unwrap();
+    }
+
+    eq!(
+        scope.to_string(),
+        include_str!("structure_single_field.expected"),
+        "Finishing then dropping should close the structure exactly once."
+    );
+}
+
+#[test]
+fn block_finish() {
+    let mut scope = Scope::in_memory();
+
+    {
+        let mut function = scope.function(Visibility::Private, "test").unwrap();
+        function.block(Nil, BlockSuffix::None).unwrap().finish().unwrap();
+        function.finish().unwrap();
+    }
+
+    eq!(scope.to_string(), include_str!("block_empty.expected"));
+}
+
+#[test]
+fn finish_propagates_error() {
+    const HEADER: &str = "struct Test {\n";
+
+    let mut buffer = [0; HEADER.len()];
+
+    let mut scope = Scope::new(Writer::from(io::Cursor::new(&mut buffer[..])));
+    let structure = scope.structure(Visibility::Private, "Test").unwrap();
+
+    assert!(
+        structure.finish().is_err(),
+        "The closing brace doesn't fit in the buffer, so finish() should fail."
+    );
+}
+
+#[test]
+fn scope_mac() {
+    let mut scope = Scope::in_memory();
+
+    scope
+        .mac("assert_size", "Object, 0x3c")
+        .unwrap()
+        .mac("assert_offset", format_args!("{}, {}, {:#x}", "Object", "outer", 0x28))
+        .unwrap();
+
+    eq!(
+        scope.to_string(),
+        "assert_size!(Object, 0x3c);\nassert_offset!(Object, outer, 0x28);\n"
+    );
+}
+
+golden!(fn_mac, |scope| {
+    scope
+        .function(Visibility::Private, "test")
+        .unwrap()
+        .mac("println", "\"{}\", 5")
+        .unwrap()
+        .mac("unreachable", Nil)
+        .unwrap();
+});
+
+golden!(impl_trait_assoc_type, |scope| {
+    let mut imp = scope.imp_trait("Deref", "Struct").unwrap();
+
+    imp.assoc_type("Target", "Base").unwrap();
+
+    imp.function_args_ret(Nil, "deref", args!("&self"), "&Self::Target")
+        .unwrap()
+        .line("&self.base")
+        .unwrap();
+});
+
+golden!(impl_trait_assoc_const, |scope| {
+    scope
+        .imp_trait("Trait", "Struct")
+        .unwrap()
+        .assoc_const("SIZE", "usize", 0x3c)
+        .unwrap()
+        .assoc_const("NAME", "&'static str", "\"Struct\"")
+        .unwrap();
+});
+
+golden!(structure_empty_pub_crate, |scope| {
+    scope.structure(Visibility::PubCrate, "Test").unwrap();
+});
+
+golden!(enum_empty_pub_super, |scope| {
+    scope.enumeration(Visibility::PubSuper, "TestEnum").unwrap();
+});
+
+golden!(fn_no_args_no_ret_pub_in, |scope| {
+    scope.function(Visibility::PubIn("crate::hook"), "test").unwrap();
+});
+
+golden!(
+    fn_args_column_limit_fits,
+    Scope::new(Writer::from(vec![]).column_limit(75)),
+    |scope| {
+        let args = [["arg1", "typ1"], ["arg2", "typ2"], ["arg3", "typ3"]];
+        let ret = "impl Iterator<Item = u8>";
+        scope.function_args_ret(Nil, "test", &args, ret).unwrap();
+    }
+);
+
+golden!(
+    fn_args_column_limit_wrap,
+    Scope::new(Writer::from(vec![]).column_limit(74)),
+    |scope| {
+        let args = [["arg1", "typ1"], ["arg2", "typ2"], ["arg3", "typ3"]];
+        let ret = "impl Iterator<Item = u8>";
+        scope.function_args_ret(Nil, "test", &args, ret).unwrap();
+    }
+);
+
+golden!(
+    impl_method_column_limit_wrap,
+    Scope::new(Writer::from(vec![]).column_limit(40)),
+    |scope| {
+        scope
+            .imp("Struct")
+            .unwrap()
+            .function_args(
+                "pub unsafe ",
+                "test",
+                args!("&mut self", [["arg1", "typ1"], ["arg2", "typ2"]].iter()),
+            )
+            .unwrap()
+            .line("// Function implementation.")
+            .unwrap();
+    }
+);
+
+golden!(raw_block_array_literal, |scope| {
+    let mut function = scope.function(Visibility::Private, "test").unwrap();
+    let mut array = function.raw_block("let primes = ", '[', "];").unwrap();
+    array.line("2,").unwrap().line("3,").unwrap().line("5,").unwrap();
+});
+
+golden!(raw_block_extern, |scope| {
+    scope
+        .raw_block("extern \"C\" ", '{', '}')
+        .unwrap()
+        .line("fn abs(input: i32) -> i32;")
+        .unwrap();
+});
+
+#[test]
+fn fn_args_ref_lifetimes() {
+    let mut scope = Scope::in_memory();
+
+    let args = [
+        ("name", Ref::shared("str").lifetime(Lifetime::Static)),
+        ("object", Ref::mutable("Object").lifetime(Lifetime::Named("a"))),
+        ("names", Ref::shared("Names")),
+    ];
+
+    scope.function_args(Nil, "test", &args).unwrap();
+
+    eq!(
+        scope.to_string(),
+        "fn test(name: &'static str, object: &'a mut Object, names: &Names, ) {\n}\n\n"
+    );
+}
+
+#[test]
+fn impl_method_ref_receiver() {
+    let mut scope = Scope::in_memory();
+
+    let receiver = Ref::mutable(Nil).lifetime(Lifetime::Named("a"));
+    let args = std::iter::once(Arg::RefReceiver(receiver))
+        .chain([["arg1", "typ1"]].iter().map(Arg::from));
+
+    scope
+        .imp("Struct")
+        .unwrap()
+        .function_args_ret(Nil, "test", args, "&'a typ1")
+        .unwrap();
+
+    eq!(
+        scope.to_string(),
+        "impl Struct {\n    fn test(&'a mut self, arg1: typ1, ) -> &'a typ1 {\n    }\n\n}\n\n"
+    );
+}
+
+#[test]
+fn spans_untracked() {
+    let mut scope = Scope::in_memory();
+    scope.structure(Visibility::Private, "Test").unwrap();
+    assert!(scope.spans().is_empty(), "Spans should only be recorded when tracking.");
+}
+
+#[test]
+fn spans_top_level_items() {
+    let mut scope = Scope::new(Writer::from(vec![]).track_spans());
+
+    scope.line("#[repr(C)]").unwrap();
+
+    scope
+        .structure(Visibility::Public, "Test")
+        .unwrap()
+        .field("field1", "u32")
+        .unwrap();
+
+    {
+        let mut imp = scope.imp_trait("Deref", "Test").unwrap();
+        imp.assoc_type("Target", "u32").unwrap();
+
+        imp.function_args_ret(Nil, "deref", args!("&self"), "&Self::Target")
+            .unwrap()
+            .if_block("if true")
+            .unwrap();
+    }
+
+    scope.function(Visibility::Private, "test").unwrap();
+
+    let output = scope.to_string();
+    let spans = scope.spans();
+
+    let names: Vec<&str> = spans.iter().map(|span| span.name.as_str()).collect();
+
+    assert_eq!(names, ["struct Test", "impl Deref for Test", "fn test"]);
+
+    eq!(&output[spans[0].range.clone()], "pub struct Test {\n    field1: u32,\n}\n\n");
+    assert!(output[spans[1].range.clone()].starts_with("impl Deref for Test {\n"));
+    assert!(output[spans[1].range.clone()].ends_with("    }\n\n}\n\n"));
+    eq!(&output[spans[2].range.clone()], "fn test() {\n}\n\n");
+    assert_eq!(spans[2].range.end, output.len());
+}
+
+golden!(impl_generic, |scope| {
+    scope
+        .imp_generic("ClassPtr", &["'a", "T: Copy"])
+        .unwrap()
+        .function_args_ret("pub ", "get", args!("&self"), "T")
+        .unwrap()
+        .line("self.0")
+        .unwrap();
+});
+
+golden!(impl_generic_const_and_bounds, |scope| {
+    scope
+        .imp_generic("Array", &["'a: 'b", "'b", "T: Copy + 'a", "const N: usize"])
+        .unwrap();
+});
+
+golden!(impl_trait_generic, |scope| {
+    scope
+        .imp_trait_generic("Deref", "TArrayField", &["T"])
+        .unwrap()
+        .assoc_type("Target", "[T]")
+        .unwrap();
+});
+
+golden!(impl_generic_no_params, |scope| {
+    scope.imp_generic("Struct", &[] as &[&str]).unwrap();
+});
+
+golden!(structure_comment_fields, |scope| {
+    let mut structure = scope
+        .comment("Class Core.Object, 0x3c")
+        .unwrap()
+        .structure(Visibility::Private, "Test")
+        .unwrap();
+
+    structure
+        .comment(format_args!("{:#x}({:#x})", 0, 4))
+        .unwrap()
+        .field("field1", "u32")
+        .unwrap()
+        .comment("Multi-\n\nLine\r\nComment\n")
+        .unwrap()
+        .field("field2", "u32")
+        .unwrap();
+});
+
+golden!(comment_empty, |scope| {
+    scope.comment("").unwrap();
+});
@@ -49,8 +49,21 @@ pub unsafe fn resolve_duplicate(object: *const Object) -> Result<Cow<'static, st
     }
 }
 
+/// Whether `object` is a struct that `game` defines by hand, with methods,
+/// which the SDK uses instead of generating its own.
+pub unsafe fn is_handwritten(object: *const Object) -> Result<bool, Error> {
+    const HANDWRITTEN: [&str; 4] = [
+        "ScriptStruct Core.Object.Color",
+        "ScriptStruct Core.Object.Vector",
+        "ScriptStruct Core.Object.Rotator",
+        "ScriptStruct Core.Object.Quat",
+    ];
+
+    Ok(HANDWRITTEN.contains(&get_full_name(object)?.as_str()))
+}
+
 pub unsafe fn get_name(object: *const Object) -> Result<&'static str, Error> {
-    Ok((*object).name().ok_or(Error::NullName(object))?)
+    Ok((*object).name.text().ok_or(Error::NullName(object))?)
 }
 
 pub unsafe fn get_full_name(object: *const Object) -> Result<String, Error> {
@@ -58,7 +71,8 @@ pub unsafe fn get_full_name(object: *const Object) -> Result<String, Error> {
 }
 
 pub unsafe fn find(class: &'static str) -> Result<*const Class, Error> {
-    Ok((*GLOBAL_OBJECTS)
+    Ok(GLOBAL_OBJECTS
+        .get()
         .find(class)
         .map(|o| o.cast())
         .ok_or(Error::StaticClassNotFound(class))?)
@@ -1,6 +1,6 @@
 use crate::args;
 
-use crate::game::{cast, BoolProperty, Class, Const, Enum, Function, Object, Property, Struct};
+use crate::game::{cast, BoolProperty, ByteProperty, Class, Const, Enum, Function, Object, Property, Struct};
 use crate::TimeIt;
 use crate::{GLOBAL_NAMES, GLOBAL_OBJECTS};
 
@@ -29,13 +29,14 @@ use genial::{Arg, BlockSuffix, Gen, GenFunction, Impl, Nil, Scope, Structure, Vi
 mod helper;
 
 mod property_info;
-use property_info::{PropertyInfo, BOOL_PROPERTY};
+use property_info::{PropertyInfo, BOOL_PROPERTY, BYTE_PROPERTY};
+
+const COLUMN_LIMIT: usize = 100;
 
 static mut CLASS: *const Class = ptr::null();
 static mut CONSTANT: *const Class = ptr::null();
 static mut ENUMERATION: *const Class = ptr::null();
 static mut STRUCTURE: *const Class = ptr::null();
-static mut FUNCTION: *const Class = ptr::null();
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -138,13 +139,13 @@ impl Generator {
     }
     
     fn add_imports(&mut self) -> Result<(), Error> {
-        self.root_mod_rs.line(
-            "use crate::GLOBAL_OBJECTS;\n\
-             use crate::game::{self, Array, FString, NameIndex, ScriptDelegate, ScriptInterface};\n\
-             use crate::hook::bitfield::{is_bit_set, set_bit};\n\
-             use std::mem::MaybeUninit;\n\
-             use std::ops::{Deref, DerefMut};\n",
-        )?;
+        self.root_mod_rs
+            .import("crate", &["GLOBAL_OBJECTS"])
+            .import("crate::game", &["self", "Array", "Color", "FString", "Map", "NameIndex", "Quat", "Rotator", "ScriptDelegate", "ScriptInterface", "Vector"])
+            .import("crate::hook::bitfield", &["is_bit_set", "set_bit"])
+            .import("std::mem", &["MaybeUninit"])
+            .import("std::ops", &["Deref", "DerefMut"])
+            .write_imports()?;
         Ok(())
     }
 
@@ -154,7 +155,9 @@ impl Generator {
         } else if (*object).is(ENUMERATION) {
             self.write_enumeration(object)?;
         } else if (*object).is(STRUCTURE) {
-            self.write_structure(object)?;
+            if !helper::is_handwritten(object)? {
+                self.write_structure(object)?;
+            }
         } else if (*object).is(CLASS) {
             self.write_class(object)?;
         }
@@ -190,26 +193,30 @@ impl Generator {
         let name = helper::get_name(object)?;
         let package = helper::get_package(object)?;
 
-        self.create_module(package)?.line(format_args!("// {}_{} = {}\n", outer, name, value))?;
+        self.create_module(package)?
+            .comment(format_args!("{}_{} = {}", outer, name, value))?
+            .line(Nil)?;
         Ok(())
     }
 
     unsafe fn write_enumeration(&mut self, object: *const Object) -> Result<(), Error> {
         impl Enum {
-            pub unsafe fn variants(&self) -> impl Iterator<Item = Option<&str>> {
+            pub unsafe fn variants(&self) -> impl Iterator<Item = Option<Cow<'static, str>>> + '_ {
                 self.variants.iter().map(|n| n.name())
             }
         }
     
         let object: *const Enum = object.cast();
     
+        let variant_names: Vec<_> = (*object).variants().collect();
+    
         let mut variant_name_counts: HashMap<&str, u8> = HashMap::new();
         let mut common_prefix: Option<Vec<&str>> = None;
     
-        let variants: Result<Vec<Cow<str>>, Error> = (*object)
-            .variants()
+        let variants: Result<Vec<Cow<str>>, Error> = variant_names
+            .iter()
             .map(|variant| {
-                let variant = variant.ok_or(Error::BadVariant(object))?;
+                let variant = variant.as_deref().ok_or(Error::BadVariant(object))?;
     
                 if let Some(common_prefix) = common_prefix.as_mut() {
                     // Shrink the common prefix to the number of components still matching.
@@ -276,6 +283,8 @@ impl Generator {
     
             enum_gen.variant(variant)?;
         }
+
+        enum_gen.finish()?;
     
         Ok(())
     }
@@ -294,14 +303,14 @@ impl Generator {
         let full_name = helper::get_full_name(object)?;
     
         let super_class = if super_class.is_null() || ptr::eq(super_class, structure) {
-            sdk.line(format_args!("// {}, {:#x}", full_name, structure_size))?;
+            sdk.comment(format_args!("{}, {:#x}", full_name, structure_size))?;
             None
         } else {
             offset = (*super_class).property_size.into();
             let relative_size = structure_size - offset;
             let super_name = helper::get_name(super_class.cast())?;
-            sdk.line(format_args!(
-                "// {}, {:#x} ({:#x} - {:#x})",
+            sdk.comment(format_args!(
+                "{}, {:#x} ({:#x} - {:#x})",
                 full_name, relative_size, structure_size, offset
             ))?;
     
@@ -310,7 +319,7 @@ impl Generator {
     
         let name = helper::resolve_duplicate(object)?;
 
-        let bitfields = {
+        let (bitfields, enum_fields) = {
 
             let mut struct_gen = sdk
                 .line("#[repr(C)]")?
@@ -321,16 +330,19 @@ impl Generator {
             }
     
             let properties = get_fields(structure, offset);
-            let bitfields = add_fields(&mut struct_gen, &mut offset, properties)?;
+            let fields = add_fields(&mut struct_gen, &mut offset, properties)?;
     
             if offset < structure_size {
                 add_padding(&mut struct_gen, offset, structure_size - offset)?;
             }
+
+            struct_gen.finish()?;
     
-            bitfields
+            fields
         };
     
         bitfields.emit(&mut sdk, &name)?;
+        add_enum_accessors(&mut sdk, &name, enum_fields)?;
     
         if let Some(super_class) = super_class {
             add_deref_impls(&mut sdk, &name, super_class)?;
@@ -351,7 +363,7 @@ impl Generator {
 fn create_file<P: AsRef<Path>>(sdk_path: &Path, file: P) -> Result<Scope<BufWriter<File>>, Error> {
     let full_file_path = sdk_path.join(file);
     let file = File::create(full_file_path).map(BufWriter::new)?;
-    Ok(Scope::new(Writer::from(file)))
+    Ok(Scope::new(Writer::from(file).column_limit(COLUMN_LIMIT)))
 }
 
 pub unsafe fn _names() -> Result<(), Error> {
@@ -360,13 +372,26 @@ pub unsafe fn _names() -> Result<(), Error> {
 
     let mut dump = File::create(NAMES).map(BufWriter::new)?;
 
-    info!("Dumping global names {:?} to {}", GLOBAL_NAMES, NAMES);
+    info!("Dumping global names {:?} to {}", GLOBAL_NAMES.ptr(), NAMES);
 
-    writeln!(&mut dump, "Global names is at {:?}", GLOBAL_NAMES)?;
+    writeln!(&mut dump, "Global names is at {:?}", GLOBAL_NAMES.ptr())?;
+    writeln!(&mut dump, "Each name is followed by how many objects are called it.")?;
 
-    for (i, name) in (*GLOBAL_NAMES).iter().enumerate() {
-        if let Some(text) = (*name).text() {
-            writeln!(&mut dump, "[{}] {}", i, text)?;
+    let names = GLOBAL_NAMES.get();
+
+    // One pass over the objects instead of one per name.
+    let mut references = vec![0_u32; names.len()];
+
+    for object in GLOBAL_OBJECTS.get().iter() {
+        if let Some(count) = references.get_mut((*object).name.index as usize) {
+            *count += 1;
+        }
+    }
+
+    // Not `iter`, which skips null entries and so loses their indices.
+    for (i, &name) in names.into_iter().enumerate() {
+        if let Some(text) = name.as_ref().and_then(|n| n.text()) {
+            writeln!(&mut dump, "[{}] {} ({})", i, text, references[i])?;
         }
     }
 
@@ -379,11 +404,11 @@ pub unsafe fn _objects() -> Result<(), Error> {
 
     let mut dump = File::create(OBJECTS).map(BufWriter::new)?;
 
-    info!("Dumping global objects {:?} to {}", GLOBAL_OBJECTS, OBJECTS);
+    info!("Dumping global objects {:?} to {}", GLOBAL_OBJECTS.ptr(), OBJECTS);
 
-    writeln!(&mut dump, "Global objects is at {:?}", GLOBAL_OBJECTS)?;
+    writeln!(&mut dump, "Global objects is at {:?}", GLOBAL_OBJECTS.ptr())?;
 
-    for object in (*GLOBAL_OBJECTS).iter() {
+    for object in GLOBAL_OBJECTS.get().iter() {
         let address = object as usize;
         let object = &*object;
 
@@ -402,7 +427,7 @@ pub unsafe fn sdk() -> Result<(), Error> {
 
     let mut generator = Generator::new()?;
 
-    for object in (*GLOBAL_OBJECTS).iter() {
+    for object in GLOBAL_OBJECTS.get().iter() {
         generator.write_object(object)?;
     }
 
@@ -415,7 +440,6 @@ unsafe fn find_static_classes() -> Result<(), Error> {
     CLASS = helper::find("Class Core.Class")?;
     CONSTANT = helper::find("Class Core.Const")?;
     ENUMERATION = helper::find("Class Core.Enum")?;
-    FUNCTION = helper::find("Class Core.Function")?;
     STRUCTURE = helper::find("Class Core.ScriptStruct")?;
 
     property_info::find_static_classes()?;
@@ -435,10 +459,9 @@ fn get_unique_name<'a>(name_counts: &mut HashMap<&'a str, u8>, name: &'a str) ->
 
 unsafe fn get_fields(structure: *const Struct, offset: u32) -> Vec<&'static Property> {
     let mut properties: Vec<&Property> = (*structure)
-        .iter_children()
+        .iter_properties()
         .filter(|p| p.element_size > 0)
         .filter(|p| p.offset >= offset)
-        .filter(|p| !p.is(STRUCTURE) && !p.is(CONSTANT) & !p.is(ENUMERATION) && !p.is(FUNCTION))
         .collect();
 
     properties.sort_by(|p, q| property_compare(p, q));
@@ -458,12 +481,20 @@ unsafe fn property_compare(p: &Property, q: &Property) -> Ordering {
     })
 }
 
+/// A byte field that holds an enum, which gets an accessor for the name of
+/// its value.
+struct EnumField {
+    field: String,
+    enumeration: String,
+}
+
 unsafe fn add_fields(
     struct_gen: &mut Structure<impl Write>,
     offset: &mut u32,
     properties: Vec<&Property>,
-) -> Result<Bitfields, Error> {
+) -> Result<(Bitfields, Vec<EnumField>), Error> {
     let mut bitfields = Bitfields::new();
+    let mut enum_fields = vec![];
 
     let mut field_name_counts: HashMap<&str, u8> = HashMap::with_capacity(properties.len());
 
@@ -495,10 +526,20 @@ unsafe fn add_fields(
             name = bitfield::FIELD;
         }
 
-        let field_name = format!(
-            "pub {}",
-            get_unique_name(&mut field_name_counts, scrub_reserved_name(name))
-        );
+        let unique_name = get_unique_name(&mut field_name_counts, scrub_reserved_name(name));
+
+        if property.is(BYTE_PROPERTY) && property.array_dim == 1 {
+            let enumeration = cast::<ByteProperty>(property).enumeration;
+
+            if !enumeration.is_null() {
+                enum_fields.push(EnumField {
+                    field: unique_name.to_string(),
+                    enumeration: helper::get_full_name(enumeration.cast())?,
+                });
+            }
+        }
+
+        let field_name = format!("pub {}", unique_name);
 
         let mut field_type = info.into_typed_comment();
 
@@ -517,7 +558,44 @@ unsafe fn add_fields(
         *offset = property.offset + total_property_size;
     }
 
-    Ok(bitfields)
+    Ok((bitfields, enum_fields))
+}
+
+fn add_enum_accessors(sdk: &mut Scope<impl Write>, structure: &str, enum_fields: Vec<EnumField>) -> Result<(), Error> {
+    if enum_fields.is_empty() {
+        return Ok(());
+    }
+
+    let mut imp = sdk.imp(structure)?;
+
+    for EnumField { field, enumeration } in enum_fields {
+        let mut getter = imp
+            .line(format_args!("// variant name of {}", field))?
+            .function_args_ret(
+                "pub unsafe ",
+                format_args!("{}_name", field.trim_start_matches("r#")),
+                args!("&self"),
+                "Option<&'static str>",
+            )?;
+
+        getter.line("static mut ENUM: Option<*const game::Enum> = None;\n")?;
+
+        let mut if_block = getter.if_block("if ENUM.is_none()")?;
+
+        if_block.line(format_args!(
+            "ENUM = GLOBAL_OBJECTS.get().find(\"{}\").map(|o| o.cast());",
+            enumeration
+        ))?;
+
+        if_block.finish()?;
+
+        getter.line(format_args!("(*ENUM?).variant_name(self.{})", field))?;
+        getter.finish()?;
+    }
+
+    imp.finish()?;
+
+    Ok(())
 }
 
 fn emit_field(
@@ -528,7 +606,7 @@ fn emit_field(
     length: u32,
 ) -> Result<(), Error> {
     struct_gen.line(Nil)?;
-    struct_gen.line(format_args!("// {:#x}({:#x})", offset, length))?;
+    struct_gen.comment(format_args!("{:#x}({:#x})", offset, length))?;
     struct_gen.field(name, typ)?;
     Ok(())
 }
@@ -551,16 +629,26 @@ fn add_padding(struct_gen: &mut Structure<impl Write>, offset: u32, size: u32) -
 }
 
 fn add_deref_impls(sdk: &mut Scope<impl Write>, derived_name: &str, base_name: &str) -> Result<(), Error> {
-    sdk
-        .imp_trait("Deref", derived_name)?
-        .line(format_args!("type Target = {};\n", base_name))?
-        .function_args_ret("", "deref", args!("&self"), "&Self::Target")?
-        .line("&self.base")?;
+    {
+        let mut imp = sdk.imp_trait("Deref", derived_name)?;
+        imp.assoc_type("Target", base_name)?.line(Nil)?;
+
+        let mut deref = imp.function_args_ret("", "deref", args!("&self"), "&Self::Target")?;
+        deref.line("&self.base")?;
+        deref.finish()?;
 
-    sdk
-        .imp_trait("DerefMut", derived_name)?
-        .function_args_ret("", "deref_mut", args!("&mut self"), "&mut Self::Target")?
-        .line("&mut self.base")?;
+        imp.finish()?;
+    }
+
+    {
+        let mut imp = sdk.imp_trait("DerefMut", derived_name)?;
+
+        let mut deref_mut = imp.function_args_ret("", "deref_mut", args!("&mut self"), "&mut Self::Target")?;
+        deref_mut.line("&mut self.base")?;
+        deref_mut.finish()?;
+
+        imp.finish()?;
+    }
 
     Ok(())
 }
@@ -568,16 +656,26 @@ fn add_deref_impls(sdk: &mut Scope<impl Write>, derived_name: &str, base_name: &
 /// Add a `Deref` and `DerefMut` for `&[mut] sdk::Object` (generated) ->
 /// `&[mut] game::Object` (handwritten with helpful impls)
 fn add_object_deref_impl(sdk: &mut Scope<impl Write>) -> Result<(), Error> {
-    sdk
-        .imp_trait("Deref", "Object")?
-        .line("type Target = game::Object;\n")?
-        .function_args_ret("", "deref", args!("&self"), "&Self::Target")?
-        .line("unsafe { &*(self as *const Self as *const Self::Target) }")?;
+    {
+        let mut imp = sdk.imp_trait("Deref", "Object")?;
+        imp.assoc_type("Target", "game::Object")?.line(Nil)?;
+
+        let mut deref = imp.function_args_ret("", "deref", args!("&self"), "&Self::Target")?;
+        deref.line("unsafe { &*(self as *const Self as *const Self::Target) }")?;
+        deref.finish()?;
+
+        imp.finish()?;
+    }
+
+    {
+        let mut imp = sdk.imp_trait("DerefMut", "Object")?;
 
-    sdk
-        .imp_trait("DerefMut", "Object")?
-        .function_args_ret("", "deref_mut", args!("&mut self"), "&mut Self::Target")?
-        .line("unsafe { &mut *(self as *mut Self as *mut Self::Target) }")?;
+        let mut deref_mut = imp.function_args_ret("", "deref_mut", args!("&mut self"), "&mut Self::Target")?;
+        deref_mut.line("unsafe { &mut *(self as *mut Self as *mut Self::Target) }")?;
+        deref_mut.finish()?;
+
+        imp.finish()?;
+    }
 
     Ok(())
 }
@@ -592,14 +690,13 @@ unsafe fn add_methods(sdk: &mut Scope<impl Write>, class: *const Struct) -> Resu
         add_method(&mut impl_gen, &mut method_name_counts, method)?;
     }
 
+    impl_gen.finish()?;
+
     Ok(())
 }
 
 unsafe fn get_methods(class: *const Struct) -> impl Iterator<Item = &'static Function> {
-    (*class)
-        .iter_children()
-        .filter(|p| p.is(FUNCTION))
-        .map(|p| cast::<Function>(p))
+    (*class).iter_functions()
 }
 
 #[derive(PartialEq, Eq)]
@@ -770,6 +867,8 @@ unsafe fn add_method(
                 params_struct.field(&param.name, format_args!("MaybeUninit<{}>", param.typ))?;
             }
         }
+
+        params_struct.finish()?;
     }
 
     {
@@ -783,6 +882,8 @@ unsafe fn add_method(
                 struct_init.line(format_args!("{}: MaybeUninit::uninit(),", &param.name))?;
             }
         }
+
+        struct_init.finish()?;
     }
 
     if_block.line("let old_flags = (*function).flags;")?;
@@ -814,8 +915,9 @@ unsafe fn add_method(
 
     let else_block = if_block.else_block("else")?;
 
-    else_block.line("FUNCTION = (*GLOBAL_OBJECTS)")?;
+    else_block.line("FUNCTION = GLOBAL_OBJECTS")?;
     else_block.indent();
+    else_block.line(".get()")?;
     else_block.line(format_args!(".find_mut(\"{}\")", helper::get_full_name(method as &Object)?))?;
     else_block.line(".map(|o| o.cast());")?;
     else_block.undent();
@@ -824,5 +926,8 @@ unsafe fn add_method(
         else_block.line("None")?;
     }
 
+    if_block.finish()?;
+    function_gen.finish()?;
+
     Ok(())
 }
@@ -1,7 +1,8 @@
 use crate::dump::helper;
 use crate::game::{
-    cast, Array, ArrayProperty, ByteProperty, Class, ClassProperty, FString, InterfaceProperty,
-    NameIndex, ObjectProperty, Property, ScriptDelegate, ScriptInterface, StructProperty,
+    cast, Array, ArrayProperty, ByteProperty, Class, ClassProperty, DelegateProperty, FString,
+    InterfaceProperty, Map, MapProperty, NameIndex, Object, ObjectProperty, Property,
+    ScriptDelegate, ScriptInterface, StructProperty,
 };
 
 use std::borrow::Cow;
@@ -13,8 +14,9 @@ use thiserror::Error;
 
 static mut ARRAY_PROPERTY: *const Class = ptr::null();
 pub static mut BOOL_PROPERTY: *const Class = ptr::null();
-static mut BYTE_PROPERTY: *const Class = ptr::null();
+pub static mut BYTE_PROPERTY: *const Class = ptr::null();
 static mut CLASS_PROPERTY: *const Class = ptr::null();
+static mut COMPONENT_PROPERTY: *const Class = ptr::null();
 static mut DELEGATE_PROPERTY: *const Class = ptr::null();
 static mut FLOAT_PROPERTY: *const Class = ptr::null();
 static mut INT_PROPERTY: *const Class = ptr::null();
@@ -22,6 +24,7 @@ static mut INTERFACE_PROPERTY: *const Class = ptr::null();
 static mut MAP_PROPERTY: *const Class = ptr::null();
 static mut NAME_PROPERTY: *const Class = ptr::null();
 static mut OBJECT_PROPERTY: *const Class = ptr::null();
+static mut QWORD_PROPERTY: *const Class = ptr::null();
 static mut STR_PROPERTY: *const Class = ptr::null();
 static mut STRUCT_PROPERTY: *const Class = ptr::null();
 
@@ -39,11 +42,12 @@ pub enum Error {
     #[error("null meta class for {0:?}")]
     NullMetaClass(*const ClassProperty),
 
-    // #[error("null map key property for {0:?}")]
-    // NullMapKeyProperty(*const MapProperty),
+    #[error("null map key property for {0:?}")]
+    NullMapKeyProperty(*const MapProperty),
+
+    #[error("null map value property for {0:?}")]
+    NullMapValueProperty(*const MapProperty),
 
-    // #[error("null map value property for {0:?}")]
-    // NullMapValueProperty(*const MapProperty),
     #[error("null property class for {0:?}")]
     NullPropertyClass(*const ObjectProperty),
 
@@ -59,6 +63,7 @@ pub unsafe fn find_static_classes() -> Result<(), Error> {
     BOOL_PROPERTY = helper::find("Class Core.BoolProperty")?;
     BYTE_PROPERTY = helper::find("Class Core.ByteProperty")?;
     CLASS_PROPERTY = helper::find("Class Core.ClassProperty")?;
+    COMPONENT_PROPERTY = helper::find("Class Core.ComponentProperty")?;
     DELEGATE_PROPERTY = helper::find("Class Core.DelegateProperty")?;
     FLOAT_PROPERTY = helper::find("Class Core.FloatProperty")?;
     INT_PROPERTY = helper::find("Class Core.IntProperty")?;
@@ -66,6 +71,10 @@ pub unsafe fn find_static_classes() -> Result<(), Error> {
     MAP_PROPERTY = helper::find("Class Core.MapProperty")?;
     NAME_PROPERTY = helper::find("Class Core.NameProperty")?;
     OBJECT_PROPERTY = helper::find("Class Core.ObjectProperty")?;
+
+    // Not every UE3 build has 64-bit integer properties.
+    QWORD_PROPERTY = helper::find("Class Core.QWordProperty").unwrap_or(ptr::null());
+
     STR_PROPERTY = helper::find("Class Core.StrProperty")?;
     STRUCT_PROPERTY = helper::find("Class Core.StructProperty")?;
 
@@ -148,8 +157,27 @@ impl TryFrom<&Property> for PropertyInfo {
                 let typ = format!("*mut {}", name);
 
                 Self::new(size_of::<usize>(), typ.into())
+            } else if property.is(COMPONENT_PROPERTY) {
+                // Before ObjectProperty, which ComponentProperty derives from.
+                let property: &ObjectProperty = cast(property);
+
+                if property.class.is_null() {
+                    return Err(Error::NullPropertyClass(property));
+                }
+
+                let name = helper::get_name(property.class.cast())?;
+                let mut info = Self::new(size_of::<usize>(), format!("*mut {}", name).into());
+                info.comment = "Component".into();
+                info
             } else if property.is(DELEGATE_PROPERTY) {
-                simple!(ScriptDelegate)
+                let property: &DelegateProperty = cast(property);
+                let mut info = simple!(ScriptDelegate);
+
+                if !property.function.is_null() {
+                    info.comment = helper::get_name(property.function.cast())?.into();
+                }
+
+                info
             } else if property.is(FLOAT_PROPERTY) {
                 simple!(f32)
             } else if property.is(INT_PROPERTY) {
@@ -161,15 +189,26 @@ impl TryFrom<&Property> for PropertyInfo {
                     return Err(Error::NullInterfaceClass(property));
                 }
 
-                let mut info = simple!(ScriptInterface);
-                info.comment = helper::get_name(property.class.cast())?.into();
-                info
+                let name = helper::get_name(property.class.cast())?;
+                let typ = format!("ScriptInterface<{}>", name);
+                Self::new(size_of::<ScriptInterface<Object>>(), typ.into())
             } else if property.is(MAP_PROPERTY) {
-                const MAP_SIZE_BYTES: u32 = 60;
-                let typ = format!("[u8; {}]", MAP_SIZE_BYTES);
-                let mut info = Self::new(MAP_SIZE_BYTES, typ.into());
-                info.comment = "Map".into();
-                info
+                let property: &MapProperty = cast(property);
+
+                let key = property
+                    .key
+                    .as_ref()
+                    .ok_or(Error::NullMapKeyProperty(property))?;
+
+                let value = property
+                    .value
+                    .as_ref()
+                    .ok_or(Error::NullMapValueProperty(property))?;
+
+                let key = PropertyInfo::try_from(key)?;
+                let value = PropertyInfo::try_from(value)?;
+                let typ = format!("Map<{}, {}>", key.field_type, value.field_type);
+                Self::new(size_of::<Map<usize, usize>>(), typ.into())
             } else if property.is(NAME_PROPERTY) {
                 simple!(NameIndex)
             } else if property.is(OBJECT_PROPERTY) {
@@ -183,6 +222,8 @@ impl TryFrom<&Property> for PropertyInfo {
                 let typ = format!("*mut {}", name);
 
                 Self::new(size_of::<usize>(), typ.into())
+            } else if property.is(QWORD_PROPERTY) {
+                simple!(u64)
             } else if property.is(STR_PROPERTY) {
                 simple!(FString)
             } else if property.is(STRUCT_PROPERTY) {
@@ -1,13 +1,36 @@
-use crate::GLOBAL_NAMES;
-
+use crate::global::Global;
+use crate::module::Module;
+use crate::profile;
+#[cfg(feature = "hook")]
+use crate::profile::{GameProfile, Signature};
+use crate::{GLOBAL_NAMES, GLOBAL_OBJECTS, PROCESS_EVENT};
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{c_void, CStr, OsString};
 use std::iter;
+use std::marker::PhantomData;
 use std::mem;
+#[cfg(feature = "hook")]
+use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_char;
 use std::os::windows::ffi::OsStringExt;
 use std::ptr;
-use std::slice;
+use std::slice::{self, SliceIndex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "hook")]
+use std::sync::Once;
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "hook")]
+use log::warn;
+use winapi::shared::minwindef::{DWORD, LPCVOID};
+use winapi::um::memoryapi::VirtualQuery;
+use winapi::um::winnt::{
+    MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE,
+    PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
+};
 
 pub type Objects = Array<*mut Object>;
 pub type Names = Array<*const Name>;
@@ -16,15 +39,854 @@ pub unsafe fn cast<To>(from: &Object) -> &To {
     &*(from as *const Object as *const To)
 }
 
+/// Whether `size` bytes at `address` are committed memory that allows reads,
+/// or writes if `write`.
+fn is_accessible(address: usize, size: usize, write: bool) -> bool {
+    const READABLE: DWORD = PAGE_READONLY
+        | PAGE_READWRITE
+        | PAGE_WRITECOPY
+        | PAGE_EXECUTE_READ
+        | PAGE_EXECUTE_READWRITE
+        | PAGE_EXECUTE_WRITECOPY;
+
+    const WRITABLE: DWORD =
+        PAGE_READWRITE | PAGE_WRITECOPY | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
+
+    let end = match address.checked_add(size) {
+        Some(end) if address != 0 => end,
+        _ => return false,
+    };
+
+    let wanted = if write { WRITABLE } else { READABLE };
+    let mut region = address;
+
+    // The range may span regions with different protections.
+    while region < end {
+        let mut info: MEMORY_BASIC_INFORMATION = unsafe { mem::zeroed() };
+        let info_size = mem::size_of::<MEMORY_BASIC_INFORMATION>();
+
+        if unsafe { VirtualQuery(region as LPCVOID, &mut info, info_size) } == 0 {
+            return false;
+        }
+
+        let committed = info.State == MEM_COMMIT;
+        let guarded = info.Protect & PAGE_GUARD != 0;
+
+        if !committed || guarded || info.Protect & wanted == 0 {
+            return false;
+        }
+
+        region = info.BaseAddress as usize + info.RegionSize;
+    }
+
+    true
+}
+
+/// Read `*pointer` if it's readable memory, e.g., while following pointers
+/// that a level transition may have freed.
+pub unsafe fn try_read<T: Copy>(pointer: *const T) -> Option<T> {
+    if is_accessible(pointer as usize, mem::size_of::<T>(), false) {
+        Some(pointer.read_unaligned())
+    } else {
+        None
+    }
+}
+
+/// Write `value` to `*pointer` if it's writable memory. Returns whether it
+/// wrote.
+#[cfg(feature = "hook")]
+pub unsafe fn try_write<T>(pointer: *mut T, value: T) -> bool {
+    let writable = is_accessible(pointer as usize, mem::size_of::<T>(), true);
+
+    if writable {
+        pointer.write_unaligned(value);
+    }
+
+    writable
+}
+
+/// `FName::Init`, found the first time a name is added.
+static FNAME_INIT: Global<c_void> = Global::null();
+
+/// `ProcessEvent`'s index in `UObject`'s vtable, once it's checked.
+static PROCESS_EVENT_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// How many vtable entries to search for `ProcessEvent` when the profile's
+/// index is wrong.
+const VTABLE_SEARCH: usize = 128;
+
+/// `UObject::StaticConstructObject`, found the first time an object is made.
+#[cfg(feature = "hook")]
+static CONSTRUCT_OBJECT: Global<c_void> = Global::null();
+
+/// Maps the text of each name to its index, so that hooks can compare a
+/// `NameIndex` instead of strings.
+static NAME_INDICES: Mutex<Option<NameIndices>> = Mutex::new(None);
+
+struct NameIndices {
+    /// How many names are indexed. Names are never removed, so new ones are
+    /// indexed as the array grows.
+    count: u32,
+
+    indices: HashMap<&'static str, u32>,
+}
+
+impl Names {
+    /// The index of the name whose text is exactly `text`, e.g.,
+    /// `index_of("PlayerTick")`. The first call indexes every name.
+    pub unsafe fn index_of(&self, text: &str) -> Option<u32> {
+        let mut indices = NAME_INDICES.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let indices = indices.get_or_insert_with(|| NameIndices {
+            count: 0,
+            indices: HashMap::new(),
+        });
+
+        if indices.count != self.count {
+            for (index, &name) in self.into_iter().enumerate().skip(indices.count as usize) {
+                if let Some(text) = name.as_ref().and_then(|n| n.text()) {
+                    #[allow(clippy::cast_possible_truncation)]
+                    indices.indices.entry(text).or_insert(index as u32);
+                }
+            }
+
+            indices.count = self.count;
+        }
+
+        indices.indices.get(text).copied()
+    }
+
+    /// Find the name `text`, ignoring case like the engine does. A numeric
+    /// suffix, as in "WillowPlayerController_0", becomes the name's number.
+    pub unsafe fn find(&self, text: &str) -> Option<NameIndex> {
+        let (base, number) = split_number(text);
+
+        let index = match self.index_of(base) {
+            Some(index) => index as usize,
+
+            // Not `iter`, which skips null entries and so loses their indices.
+            None => self.into_iter().position(|&name| {
+                !name.is_null()
+                    && (*name)
+                        .text()
+                        .map_or(false, |t| t.eq_ignore_ascii_case(base))
+            })?,
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        Some(NameIndex {
+            index: index as u32,
+            number,
+        })
+    }
+
+    /// Find the name `text`, or add it with the game's `FName::Init` so that
+    /// the engine can look it up later. `None` if `FName::Init` wasn't found.
+    pub unsafe fn add(&self, text: &str) -> Option<NameIndex> {
+        type Init = unsafe extern "fastcall" fn(
+            this: *mut NameIndex,
+            edx: usize,
+            name: *const u16,
+            number: u32,
+            find_type: u32,
+            split_name: u32,
+            hardcode_index: i32,
+        );
+
+        const FNAME_ADD: u32 = 1;
+
+        if let Some(name) = self.find(text) {
+            return Some(name);
+        }
+
+        if FNAME_INIT.is_null() {
+            let profile = profile::current();
+            let signature = profile.fname_init();
+            let game = Module::from(profile.module()).ok()?;
+            let init = game.find_pattern(signature.pattern)? + signature.offset;
+            FNAME_INIT.set(init as *mut c_void);
+        }
+
+        let init = mem::transmute::<*mut c_void, Init>(FNAME_INIT.ptr());
+        let wide = crate::wide_format!("{}", text);
+
+        let mut name = NameIndex {
+            index: 0,
+            number: 0,
+        };
+
+        init(&mut name, 0, wide.as_ptr(), 0, FNAME_ADD, 1, -1);
+        Some(name)
+    }
+}
+
+/// Split "Name_12" into "Name" and 13, since the engine stores numbers plus
+/// one, with 0 meaning no number.
+fn split_number(text: &str) -> (&str, u32) {
+    if let Some((base, digits)) = text.rsplit_once('_') {
+        let canonical = !digits.is_empty() && (digits == "0" || !digits.starts_with('0'));
+
+        if canonical && digits.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(number) = digits.parse::<u32>() {
+                if let Some(number) = number.checked_add(1) {
+                    return (base, number);
+                }
+            }
+        }
+    }
+
+    (text, 0)
+}
+
+/// Maps full names to object indices, so that finding an object doesn't
+/// rebuild the full name of every object.
+static LOOKUP: Mutex<Option<Lookup>> = Mutex::new(None);
+
+#[derive(Default)]
+struct Lookup {
+    /// How many slots are indexed. New slots are indexed as the array grows.
+    count: u32,
+
+    indices: HashMap<String, u32>,
+
+    /// Full names that weren't found, forgotten when the count changes.
+    missing: HashSet<String>,
+}
+
+impl Lookup {
+    /// Index the slots added since the last call.
+    unsafe fn update(&mut self, objects: &Objects) {
+        if self.count == objects.count {
+            return;
+        }
+
+        if objects.count < self.count {
+            *self = Self::default();
+        }
+
+        for index in self.count..objects.count {
+            if let Some(full_name) = objects.full_name_at(index) {
+                self.indices.entry(full_name).or_insert(index);
+            }
+        }
+
+        self.count = objects.count;
+        self.missing.clear();
+    }
+}
+
 impl Objects {
     pub unsafe fn find(&self, full_name: &str) -> Option<*const Object> {
         self.find_mut(full_name).map(|o| o as *const Object)
     }
 
     pub unsafe fn find_mut(&self, full_name: &str) -> Option<*mut Object> {
-        self.iter()
+        let index = self.lookup(full_name)?;
+        Some(self[index as usize])
+    }
+
+    /// The index of the object called `full_name`. The first call indexes every
+    /// object, and later calls index the objects added since. A name that
+    /// isn't indexed is searched for once until the count changes, since a
+    /// new object can take a freed slot.
+    pub unsafe fn lookup(&self, full_name: &str) -> Option<u32> {
+        let mut lookup = LOOKUP.lock().unwrap_or_else(PoisonError::into_inner);
+        let lookup = lookup.get_or_insert_with(Lookup::default);
+        lookup.update(self);
+
+        if let Some(&index) = lookup.indices.get(full_name) {
+            if self.is_named(index, full_name) {
+                return Some(index);
+            }
+
+            // The slot holds a different object now, so index that one.
+            lookup.indices.remove(full_name);
+
+            if let Some(name) = self.full_name_at(index) {
+                lookup.indices.insert(name, index);
+            }
+        }
+
+        if lookup.missing.contains(full_name) {
+            return None;
+        }
+
+        let index = self
+            .iter()
             .find(|&o| (*o).full_name().map_or(false, |n| n == full_name))
+            .map(|o| (*o).index);
+
+        if let Some(index) = index {
+            lookup.indices.insert(full_name.to_owned(), index);
+        } else {
+            lookup.missing.insert(full_name.to_owned());
+        }
+
+        index
+    }
+
+    /// Every object whose class is `class` or one of its subclasses, except
+    /// for class default objects and objects being destroyed.
+    pub unsafe fn iter_instances_of(
+        &self,
+        class: *const Class,
+    ) -> impl Iterator<Item = *mut Object> + '_ {
+        self.iter().filter(move |&o| {
+            (*o).is(class) && !(*o).is_default_object() && !(*o).is_pending_kill()
+        })
+    }
+
+    /// Like `iter_instances_of`, but for the class called, e.g.,
+    /// "WillowGame.WillowAIPawn". Empty if there's no such class.
+    pub unsafe fn iter_instances_of_name(
+        &self,
+        class: &str,
+    ) -> impl Iterator<Item = *mut Object> + '_ {
+        let class = self.find(&format!("Class {}", class));
+
+        class
+            .map(|class| self.iter_instances_of(class.cast()))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Every object whose name matches `pattern`, where `*` and `?` are
+    /// wildcards, e.g., "WillowGame.Default__WillowWeapon*". A pattern with a
+    /// space is matched against the full name, class included; otherwise
+    /// against the path name after the class.
+    #[cfg(feature = "hook")]
+    pub unsafe fn find_matching<'a>(
+        &'a self,
+        pattern: &'a str,
+    ) -> impl Iterator<Item = *mut Object> + 'a {
+        let with_class = pattern.contains(' ');
+
+        self.iter().filter(move |&o| {
+            let full_name = match o.as_ref().and_then(|o| o.full_name()) {
+                Some(full_name) => full_name,
+                None => return false,
+            };
+
+            let name = if with_class {
+                &full_name
+            } else {
+                full_name
+                    .split_once(' ')
+                    .map_or(&*full_name, |(_, path)| path)
+            };
+
+            crate::wildcard::matches(pattern, name)
+        })
+    }
+
+    /// Forget the index built by `lookup` and the cached full names, e.g.,
+    /// after a map change replaced many objects without changing how many
+    /// there are.
+    pub fn invalidate_lookup() {
+        *LOOKUP.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        *FULL_NAMES.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    unsafe fn is_named(&self, index: u32, full_name: &str) -> bool {
+        self.full_name_at(index).map_or(false, |n| n == full_name)
+    }
+
+    unsafe fn full_name_at(&self, index: u32) -> Option<String> {
+        self.get(index as usize)
+            .and_then(|&o| o.as_ref())
+            .and_then(|o| o.full_name())
+    }
+}
+
+/// Find `ProcessEvent` in `UObject`'s vtable, trying `expected`, the
+/// profile's index, first, and remember where it is for `process_event`.
+/// `None` if it isn't there, e.g., because `PROCESS_EVENT` is wrong. The
+/// search stops at the first unreadable slot, in case the vtable is shorter.
+pub unsafe fn find_process_event_index(expected: usize) -> Option<usize> {
+    let object = GLOBAL_OBJECTS.get().iter().next()?;
+    let vtable = (*object).vtable as *const usize;
+    let address = PROCESS_EVENT.ptr() as usize;
+
+    let slot = |i: usize| try_read(vtable.wrapping_add(i));
+
+    let index = if slot(expected) == Some(address) {
+        expected
+    } else {
+        (0..VTABLE_SEARCH)
+            .map_while(|i| slot(i).map(|entry| (i, entry)))
+            .find(|&(_, entry)| entry == address)
+            .map(|(i, _)| i)?
+    };
+
+    PROCESS_EVENT_INDEX.store(index, Ordering::SeqCst);
+    Some(index)
+}
+
+/// An engine global holding an object, like `GEngine`.
+#[cfg(feature = "hook")]
+struct GlobalObject {
+    /// The variable rather than its value, since the value changes.
+    variable: Global<*mut Object>,
+    search: Once,
+    signature: fn(&dyn GameProfile) -> Signature,
+
+    /// The object's class, to validate the variable and to fall back to
+    /// searching the objects array if the signature is wrong.
+    class: &'static str,
+}
+
+#[cfg(feature = "hook")]
+static GENGINE: GlobalObject = GlobalObject {
+    variable: Global::null(),
+    search: Once::new(),
+    signature: |profile| profile.global_engine(),
+    class: "Engine.GameEngine",
+};
+
+#[cfg(feature = "hook")]
+static GWORLD: GlobalObject = GlobalObject {
+    variable: Global::null(),
+    search: Once::new(),
+    signature: |profile| profile.global_world(),
+    class: "Engine.World",
+};
+
+#[cfg(feature = "hook")]
+impl GlobalObject {
+    unsafe fn get(&self) -> Option<*mut Object> {
+        self.search.call_once(|| {
+            let profile = profile::current();
+            let signature = (self.signature)(profile);
+
+            let address = Module::from(profile.module())
+                .ok()
+                .and_then(|game| game.find_pattern(signature.pattern));
+
+            if let Some(address) = address {
+                let variable = (address + signature.offset) as *const *mut *mut Object;
+                self.variable.set(variable.read_unaligned());
+            }
+        });
+
+        let objects = GLOBAL_OBJECTS.get();
+
+        if !self.variable.is_null() {
+            // The signature may have matched the wrong instruction.
+            let object = try_read(self.variable.ptr()).unwrap_or(ptr::null_mut());
+            let class = objects.find(&format!("Class {}", self.class))?;
+
+            let is_live = !object.is_null()
+                && try_read(ptr::addr_of!((*object).index))
+                    .map_or(false, |i| objects.get(i as usize) == Some(&object))
+                && (*object).is(class.cast());
+
+            if is_live {
+                return Some(object);
+            }
+        }
+
+        objects.iter_instances_of_name(self.class).next()
+    }
+}
+
+/// The game's engine, e.g., a `WillowGameEngine`.
+#[cfg(feature = "hook")]
+pub unsafe fn engine() -> Option<*mut Object> {
+    GENGINE.get()
+}
+
+/// The world, which holds the loaded levels. Changes with the map.
+#[cfg(feature = "hook")]
+pub unsafe fn world() -> Option<*mut Object> {
+    GWORLD.get()
+}
+
+/// The engine's first `LocalPlayer`, whose `Actor` is the player controller.
+#[cfg(feature = "hook")]
+pub unsafe fn local_player() -> Option<*mut Object> {
+    let players: *const Array<*mut Object> = (*engine()?).property("GamePlayers")?.cast();
+    (*players).first().copied().filter(|p| !p.is_null())
+}
+
+/// The local player's controller.
+#[cfg(feature = "hook")]
+pub unsafe fn player_controller() -> Option<*mut Object> {
+    let controller: *const *mut Object = (*local_player()?).property("Actor")?.cast();
+    Some(*controller).filter(|c| !c.is_null())
+}
+
+/// The current map's `WorldInfo`, through the local player's controller.
+#[cfg(feature = "hook")]
+pub unsafe fn world_info() -> Option<*mut Object> {
+    let world_info: *const *mut Object = (*player_controller()?).property("WorldInfo")?.cast();
+    Some(*world_info).filter(|w| !w.is_null())
+}
+
+/// An `FOutputDevice` for `StaticConstructObject` to report to, e.g., that a
+/// class is abstract, since it writes to the device without checking for null.
+#[cfg(feature = "hook")]
+#[repr(C)]
+struct ErrorLog {
+    vtable: &'static OutputDeviceVtable,
+    suppress_event_tag: u32,
+    auto_emit_line_terminator: u32,
+}
+
+// `thiscall` methods, with an unused `edx` to make them `fastcall`.
+#[cfg(feature = "hook")]
+#[repr(C)]
+struct OutputDeviceVtable {
+    serialize: unsafe extern "fastcall" fn(*mut ErrorLog, usize, *const u16, u32),
+    flush: unsafe extern "fastcall" fn(*mut ErrorLog, usize),
+    tear_down: unsafe extern "fastcall" fn(*mut ErrorLog, usize),
+}
+
+#[cfg(feature = "hook")]
+static ERROR_LOG_VTABLE: OutputDeviceVtable = OutputDeviceVtable {
+    serialize: log_error,
+    flush: ignore,
+    tear_down: ignore,
+};
+
+#[cfg(feature = "hook")]
+unsafe extern "fastcall" fn log_error(_: *mut ErrorLog, _: usize, text: *const u16, _: u32) {
+    if !text.is_null() {
+        let length = (0..).take_while(|&i| *text.add(i) != 0).count();
+        let text = OsString::from_wide(slice::from_raw_parts(text, length));
+        warn!("Failed to construct an object: {}", text.to_string_lossy());
+    }
+}
+
+#[cfg(feature = "hook")]
+unsafe extern "fastcall" fn ignore(_: *mut ErrorLog, _: usize) {}
+
+/// Create an object of `class` inside `outer`, e.g., an item in a player's
+/// inventory, with the engine's `StaticConstructObject`. A `None` name lets
+/// the engine pick a unique one. Call on the game thread.
+#[cfg(feature = "hook")]
+pub unsafe fn construct(
+    class: *mut Class,
+    outer: *mut Object,
+    name: Option<&str>,
+) -> Option<*mut Object> {
+    type StaticConstructObject = unsafe extern "C" fn(
+        class: *mut Class,
+        outer: *mut Object,
+        name: NameIndex,
+        flags: u64,
+        template: *mut Object,
+        error: *mut c_void,
+        subobject_root: *mut Object,
+        instance_graph: *mut c_void,
+    ) -> *mut Object;
+
+    if class.is_null() {
+        return None;
+    }
+
+    let name = match name {
+        Some(name) => GLOBAL_NAMES.get().add(name)?,
+        None => NameIndex {
+            index: 0,
+            number: 0,
+        },
+    };
+
+    if CONSTRUCT_OBJECT.is_null() {
+        let profile = profile::current();
+        let signature = profile.construct_object();
+        let game = Module::from(profile.module()).ok()?;
+        let construct = game.find_pattern(signature.pattern)? + signature.offset;
+        CONSTRUCT_OBJECT.set(construct as *mut c_void);
+    }
+
+    let construct = mem::transmute::<*mut c_void, StaticConstructObject>(CONSTRUCT_OBJECT.ptr());
+
+    let mut error = ErrorLog {
+        vtable: &ERROR_LOG_VTABLE,
+        suppress_event_tag: 0,
+        auto_emit_line_terminator: 1,
+    };
+
+    let object = construct(
+        class,
+        outer,
+        name,
+        0,
+        ptr::null_mut(),
+        (&mut error as *mut ErrorLog).cast(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+    );
+
+    Some(object).filter(|o| !o.is_null())
+}
+
+/// The persistent level of the current map, which is the outer of its
+/// `WorldInfo`.
+#[cfg(feature = "hook")]
+pub unsafe fn level() -> Option<*mut Level> {
+    let level = (*world_info()?).outer;
+    Some(level.cast()).filter(|_| !level.is_null() && (*level).is(LEVEL.get()))
+}
+
+/// The name of the current map's package, e.g., "Sanctuary_P".
+#[cfg(feature = "hook")]
+pub unsafe fn map_name() -> Option<String> {
+    let package = (*level()?).outer.as_ref()?;
+    package.name().map(Cow::into_owned)
+}
+
+/// Every actor in the persistent level, a few thousand objects rather than
+/// every object. Actors in streamed levels aren't included.
+#[cfg(feature = "hook")]
+pub unsafe fn actors() -> impl Iterator<Item = *mut Object> {
+    level().into_iter().flat_map(|level| (*level).actors.iter())
+}
+
+/// Run a console command, e.g., "fly" or "set WillowPlayerPawn GroundSpeed
+/// 900", as `controller`, and return its output. Call on the game thread.
+#[cfg(feature = "hook")]
+pub unsafe fn console_command(controller: *mut Object, command: &str) -> Option<String> {
+    #[repr(C)]
+    struct Parameters {
+        command: FString,
+        write_to_log: u32,
+        output: FString,
+    }
+
+    // The command only has to live for the call, so it can use our memory.
+    let mut wide = crate::wide_format!("{}", command);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let length = wide.len() as u32;
+
+    let mut parameters = Parameters {
+        command: FString {
+            data: wide.as_mut_ptr(),
+            count: length,
+            max: length,
+        },
+        write_to_log: 1,
+        output: FString {
+            data: ptr::null_mut(),
+            count: 0,
+            max: 0,
+        },
+    };
+
+    call_checked(
+        controller,
+        "Function Engine.Actor.ConsoleCommand",
+        &mut parameters,
+    )?;
+
+    let output = parameters.output.to_string();
+    let output = output.to_string_lossy().trim_end_matches('\0').to_owned();
+
+    // Only hook builds know the game's allocator, which owns the output.
+    #[cfg(feature = "hook")]
+    {
+        let _ = crate::hook::gmalloc::free(parameters.output.data.cast());
+    }
+
+    Some(output)
+}
+
+/// Call the function called `function` on `object` with `parameters`, unless
+/// the function's parameters aren't the size of `P`, which means our idea of
+/// them is wrong for this game, or `object` is null.
+#[cfg(feature = "hook")]
+pub unsafe fn call_checked<P>(
+    object: *mut Object,
+    function: &str,
+    parameters: &mut P,
+) -> Option<()> {
+    if object.is_null() {
+        return None;
+    }
+
+    let function: *mut Function = GLOBAL_OBJECTS.get().find_mut(function)?.cast();
+
+    if usize::from((*function).params_size) != mem::size_of::<P>() {
+        return None;
+    }
+
+    (*object).process_event(function, (parameters as *mut P).cast());
+    Some(())
+}
+
+/// `Core.Object.Color`, which the SDK uses instead of generating its own.
+#[cfg(feature = "hook")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Color {
+    pub b: u8,
+    pub g: u8,
+    pub r: u8,
+    pub a: u8,
+}
+
+#[cfg(feature = "hook")]
+impl Color {
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { b, g, r, a }
+    }
+}
+
+/// Move where `canvas` draws next to (`x`, `y`) pixels from the top left.
+/// Call these canvas functions from a `PostRender`.
+#[cfg(feature = "hook")]
+pub unsafe fn set_pos(canvas: *mut Object, x: f32, y: f32) -> Option<()> {
+    #[repr(C)]
+    struct Parameters {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    let mut parameters = Parameters { x, y, z: 0.0 };
+    call_checked(canvas, "Function Engine.Canvas.SetPos", &mut parameters)
+}
+
+/// Set the color of what `canvas` draws next.
+#[cfg(feature = "hook")]
+pub unsafe fn set_draw_color(canvas: *mut Object, color: Color) -> Option<()> {
+    #[repr(C)]
+    struct Parameters {
+        r: u8,
+        g: u8,
+        b: u8,
+        a: u8,
+    }
+
+    let mut parameters = Parameters {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+        a: color.a,
+    };
+
+    call_checked(
+        canvas,
+        "Function Engine.Canvas.SetDrawColor",
+        &mut parameters,
+    )
+}
+
+/// Draw a line from (`x1`, `y1`) to (`x2`, `y2`) in `color`.
+#[cfg(feature = "hook")]
+pub unsafe fn draw_line(
+    canvas: *mut Object,
+    (x1, y1): (f32, f32),
+    (x2, y2): (f32, f32),
+    color: Color,
+) -> Option<()> {
+    #[repr(C)]
+    struct Parameters {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        color: Color,
+    }
+
+    let mut parameters = Parameters {
+        x1,
+        y1,
+        x2,
+        y2,
+        color,
+    };
+
+    call_checked(canvas, "Function Engine.Canvas.Draw2DLine", &mut parameters)
+}
+
+/// Where `location` in the world is on the screen, in pixels from the top left.
+/// Locations behind the view project to nonsense, so check those first.
+#[cfg(feature = "hook")]
+pub unsafe fn project(canvas: *mut Object, location: Vector) -> Option<(f32, f32)> {
+    #[repr(C)]
+    struct Parameters {
+        location: Vector,
+        return_value: Vector,
+    }
+
+    let mut parameters = Parameters {
+        location,
+        return_value: Vector::default(),
+    };
+
+    call_checked(canvas, "Function Engine.Canvas.Project", &mut parameters)?;
+    Some((parameters.return_value.x, parameters.return_value.y))
+}
+
+/// Draw `text` at (`x`, `y`) in `color` with the canvas's current font. With
+/// an `outline` color, the text is first drawn offset in each direction to
+/// keep it readable over bright scenery.
+#[cfg(feature = "hook")]
+pub unsafe fn draw_text(
+    canvas: *mut Object,
+    text: &str,
+    x: f32,
+    y: f32,
+    color: Color,
+    outline: Option<Color>,
+) -> Option<()> {
+    if let Some(outline) = outline {
+        set_draw_color(canvas, outline)?;
+
+        for &(dx, dy) in &[(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)] {
+            set_pos(canvas, x + dx, y + dy)?;
+            draw_string(canvas, text)?;
+        }
+    }
+
+    set_draw_color(canvas, color)?;
+    set_pos(canvas, x, y)?;
+    draw_string(canvas, text)
+}
+
+/// `Canvas.DrawText` at the current position and color.
+#[cfg(feature = "hook")]
+unsafe fn draw_string(canvas: *mut Object, text: &str) -> Option<()> {
+    #[repr(C)]
+    struct Parameters {
+        text: FString,
+        carriage_return: u32,
+        x_scale: f32,
+        y_scale: f32,
+
+        /// A zeroed `FontRenderInfo`: no clipping, shadow or glow.
+        render_info: [u8; 40],
     }
+
+    // The text only has to live for the call, so it can use our memory.
+    let mut wide = crate::wide_format!("{}", text);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let length = wide.len() as u32;
+
+    let mut parameters = Parameters {
+        text: FString {
+            data: wide.as_mut_ptr(),
+            count: length,
+            max: length,
+        },
+        carriage_return: 1,
+        x_scale: 1.0,
+        y_scale: 1.0,
+        render_info: [0; 40],
+    };
+
+    call_checked(canvas, "Function Engine.Canvas.DrawText", &mut parameters)
 }
 
 #[repr(C)]
@@ -34,23 +896,146 @@ pub struct Array<T> {
     pub max: u32,
 }
 
+impl<T> Array<T> {
+    /// Whether the header looks like a real array. Garbage or a torn read can
+    /// have `count` past `max`, and empty arrays may have no data.
+    fn is_sane(&self) -> bool {
+        !self.data.is_null() && self.count <= self.max
+    }
+
+    /// The element or range at `index`, if it's below `count`.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.deref().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.deref_mut().get_mut(index)
+    }
+}
+
 impl<T> Deref for Array<T> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
-        unsafe { slice::from_raw_parts(self.data, self.count as usize) }
+        if self.is_sane() {
+            unsafe { slice::from_raw_parts(self.data, self.count as usize) }
+        } else {
+            &[]
+        }
+    }
+}
+
+impl<T> DerefMut for Array<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if self.is_sane() {
+            unsafe { slice::from_raw_parts_mut(self.data, self.count as usize) }
+        } else {
+            &mut []
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Array<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deref().iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Array<T> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deref_mut().iter_mut()
     }
 }
 
 impl<T> Array<*const T> {
     pub fn iter(&self) -> impl Iterator<Item = *const T> + '_ {
-        self.deref().iter().filter(|o| !o.is_null()).copied()
+        self.into_iter().filter(|o| !o.is_null()).copied()
     }
 }
 
 impl<T> Array<*mut T> {
     pub fn iter(&self) -> impl Iterator<Item = *mut T> + '_ {
-        self.deref().iter().filter(|o| !o.is_null()).copied()
+        self.into_iter().filter(|o| !o.is_null()).copied()
+    }
+}
+
+/// A `TMap`, which is a set of key-value pairs in a sparse array, hashed by
+/// key. Only reading is supported, since hashing keys is up to the game.
+#[repr(C)]
+pub struct Map<K, V> {
+    pub elements: Array<MapElement<K, V>>,
+
+    /// Which elements are allocated rather than on the free list.
+    pub allocation_flags: BitArray,
+    pub first_free_index: i32,
+    pub free_count: i32,
+
+    pub hash: [usize; 2],
+    pub hash_size: i32,
+}
+
+#[repr(C)]
+pub struct MapElement<K, V> {
+    pub key: K,
+    pub value: V,
+    pub hash_next_id: i32,
+    pub hash_index: i32,
+}
+
+#[repr(C)]
+pub struct BitArray {
+    pub inline: [u32; 4],
+    pub secondary: *mut u32,
+    pub count: i32,
+    pub max: i32,
+}
+
+#[cfg(feature = "hook")]
+impl BitArray {
+    unsafe fn is_set(&self, index: usize) -> bool {
+        let words = if self.secondary.is_null() {
+            self.inline.as_ptr()
+        } else {
+            self.secondary
+        };
+
+        *words.add(index / 32) & (1 << (index % 32)) != 0
+    }
+}
+
+#[cfg(feature = "hook")]
+impl<K, V> Map<K, V> {
+    /// How many elements are allocated. Zero rather than a wrapped count if
+    /// the map is torn or garbage.
+    #[allow(clippy::cast_sign_loss)]
+    pub fn len(&self) -> usize {
+        (self.elements.count as usize).saturating_sub(self.free_count as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.elements
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| unsafe { self.allocation_flags.is_set(*i) })
+            .map(|(_, element)| (&element.key, &element.value))
+    }
+
+    /// The value of `key`, found by comparing every key.
+    pub fn get(&self, key: &K) -> Option<&V>
+    where
+        K: PartialEq,
+    {
+        self.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
     }
 }
 
@@ -73,8 +1058,9 @@ pub struct NameIndex {
 }
 
 impl NameIndex {
-    pub unsafe fn name(&self) -> Option<&str> {
-        let name = *(*GLOBAL_NAMES).get(self.index as usize)?;
+    /// The name's entry in the names array, without the number.
+    pub unsafe fn text(&self) -> Option<&'static str> {
+        let name = *GLOBAL_NAMES.get().get(self.index as usize)?;
 
         if name.is_null() {
             None
@@ -82,22 +1068,93 @@ impl NameIndex {
             (*name).text()
         }
     }
+
+    /// The name as the engine prints it. Number N > 0 appends "_{N - 1}", as in
+    /// "WillowPlayerController_0".
+    pub unsafe fn name(&self) -> Option<Cow<'static, str>> {
+        let text = self.text()?;
+
+        Some(if self.number == 0 {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(format!("{}_{}", text, self.number - 1))
+        })
+    }
+}
+
+/// Full names by object index, since building one walks every outer.
+static FULL_NAMES: Mutex<Option<HashMap<u32, FullName>>> = Mutex::new(None);
+
+struct FullName {
+    /// What the name was built from, to notice when the slot is reused.
+    object: usize,
+    name: (u32, u32),
+    outer: usize,
+    class: usize,
+
+    text: String,
+}
+
+impl FullName {
+    fn is_of(&self, object: &Object) -> bool {
+        self.object == object as *const Object as usize
+            && self.name == (object.name.index, object.name.number)
+            && self.outer == object.outer as usize
+            && self.class == object.class as usize
+    }
 }
 
 #[repr(C)]
 pub struct Object {
     pub vtable: usize,
-    pub pad0: [u8; 0x1c],
+    pub pad0: [u8; 0x4],
+
+    /// The 64-bit `ObjectFlags` as halves, low first, since the engine only
+    /// aligns it to 4 bytes. Read it with `flags`.
+    pub flags: [u32; 2],
+
+    pub pad1: [u8; 0x10],
     pub index: u32,
-    pub pad1: [u8; 0x4],
+    pub pad2: [u8; 0x4],
     pub outer: *mut Object,
     pub name: NameIndex,
     pub class: *mut Class,
     pub archetype: *mut Object,
 }
 
+// Every reflected struct embeds `Object`, so its size places their fields.
+const _: () = assert!(mem::size_of::<Object>() == 0x3C);
+
 impl Object {
+    /// The class and path of this object, e.g., "Class Core.Object". Cached by
+    /// object index after the first call.
     pub unsafe fn full_name(&self) -> Option<String> {
+        let mut cache = FULL_NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+        let cache = cache.get_or_insert_with(HashMap::new);
+
+        if let Some(cached) = cache.get(&self.index) {
+            if cached.is_of(self) {
+                return Some(cached.text.clone());
+            }
+        }
+
+        let text = self.build_full_name()?;
+
+        cache.insert(
+            self.index,
+            FullName {
+                object: self as *const Self as usize,
+                name: (self.name.index, self.name.number),
+                outer: self.outer as usize,
+                class: self.class as usize,
+                text: text.clone(),
+            },
+        );
+
+        Some(text)
+    }
+
+    unsafe fn build_full_name(&self) -> Option<String> {
         if self.class.is_null() {
             return None;
         }
@@ -107,9 +1164,9 @@ impl Object {
         outer_names.reverse();
         let name = outer_names.join(".");
 
-        let class = String::from((*self.class).field.object.name()?);
+        let class = (*self.class).field.object.name()?.into_owned();
 
-        Some(class + " " + &name)
+        Some(format!("{} {}", class, name))
     }
 
     pub unsafe fn iter_outer(&self) -> impl Iterator<Item = &Self> {
@@ -129,7 +1186,7 @@ impl Object {
         })
     }
 
-    pub unsafe fn name(&self) -> Option<&str> {
+    pub unsafe fn name(&self) -> Option<Cow<'static, str>> {
         self.name.name()
     }
 
@@ -137,22 +1194,101 @@ impl Object {
         self.iter_class().any(|c| ptr::eq(c, class))
     }
 
+    /// The address of the field called `name` in this object's class or its
+    /// bases, found through reflection.
+    pub unsafe fn property(&self, name: &str) -> Option<*mut u8> {
+        let property = self
+            .iter_class()
+            .flat_map(|class| class.iter_properties())
+            .find(|p| p.name().as_deref() == Some(name))?;
+
+        let object = self as *const Self as *mut u8;
+        Some(object.add(property.offset as usize))
+    }
+
+    /// The object's `EObjectFlags`.
+    pub fn flags(&self) -> u64 {
+        (u64::from(self.flags[1]) << 32) | u64::from(self.flags[0])
+    }
+
+    /// Whether this is the template that new instances of its class copy.
+    pub fn is_default_object(&self) -> bool {
+        const CLASS_DEFAULT_OBJECT: u64 = 0x200;
+        self.flags() & CLASS_DEFAULT_OBJECT == CLASS_DEFAULT_OBJECT
+    }
+
+    /// Whether the object is being destroyed and is about to be collected.
+    pub fn is_pending_kill(&self) -> bool {
+        const PENDING_KILL: u64 = 0x2000_0000;
+        self.flags() & PENDING_KILL == PENDING_KILL
+    }
+
+    /// Whether the object is never saved, like most runtime-spawned objects.
+    pub fn is_transient(&self) -> bool {
+        const TRANSIENT: u64 = 0x4000;
+        self.flags() & TRANSIENT == TRANSIENT
+    }
+
     pub unsafe fn process_event(&mut self, function: *mut Function, parameters: *mut c_void) {
-        type ProcessEvent = unsafe extern "fastcall" fn(
-            this: *mut Object,
-            edx: usize,
-            function: *mut Function,
-            parameters: *mut c_void,
-            return_value: *mut usize,
-        );
+        let index = PROCESS_EVENT_INDEX.load(Ordering::Relaxed);
+        let address = VirtualFunction::<usize>::new(index).get(self);
+        profile::current().call_process_event(address, self, function, parameters);
+    }
+}
+
+/// `ULevel`, whose actors aren't reflected.
+#[cfg(feature = "hook")]
+#[repr(C)]
+pub struct Level {
+    pub object: Object,
+    pub actors: Array<*mut Object>,
+    pub actors_owner: *mut Object,
+}
+
+#[cfg(feature = "hook")]
+impl Deref for Level {
+    type Target = Object;
+
+    fn deref(&self) -> &Self::Target {
+        &self.object
+    }
+}
+
+#[cfg(feature = "hook")]
+impl DerefMut for Level {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.object
+    }
+}
 
-        const INDEX: usize = 58;
-        let vtable = *(self as *const Self as *const *const usize);
-        let process_event = mem::transmute::<usize, ProcessEvent>(*vtable.add(INDEX));
+/// A virtual function by its index in an object's vtable, called as `F`, e.g.,
+/// `unsafe extern "fastcall" fn(this: *mut Object, edx: usize, delta: f32)` for
+/// a `Tick(FLOAT)`. Natives called this way skip ProcessEvent entirely, so our
+/// hook and the game's script callbacks don't see them.
+pub struct VirtualFunction<F> {
+    pub index: usize,
+    signature: PhantomData<F>,
+}
 
-        let mut return_value = 0;
-        // log::info!("obj is {:#x} and pe is {:#x}", self as *const Self as usize, process_event as usize);
-        process_event(self, 0, function, parameters, &mut return_value);
+impl<F> VirtualFunction<F> {
+    pub const fn new(index: usize) -> Self {
+        Self {
+            index,
+            signature: PhantomData,
+        }
+    }
+}
+
+impl<F: Copy> VirtualFunction<F> {
+    /// The function in `object`'s vtable, which must have one at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `F` isn't pointer-sized, i.e., isn't a function pointer.
+    pub unsafe fn get(&self, object: &Object) -> F {
+        assert_eq!(mem::size_of::<F>(), mem::size_of::<usize>());
+        let vtable = object.vtable as *const usize;
+        mem::transmute_copy(&*vtable.add(self.index))
     }
 }
 
@@ -183,7 +1319,11 @@ pub struct Struct {
     pub super_field: *mut Field,
     pub children: *mut Field,
     pub property_size: u16,
-    pub pad1: [u8; 0x2e],
+    pub pad1: [u8; 2],
+
+    /// The UnrealScript bytecode of a function or state.
+    pub script: Array<u8>,
+    pub pad2: [u8; 0x20],
 }
 
 impl Deref for Struct {
@@ -200,12 +1340,66 @@ impl DerefMut for Struct {
     }
 }
 
+/// A class that's always loaded, found the first time it's needed.
+struct StaticClass {
+    class: Global<Class>,
+    full_name: &'static str,
+}
+
+static PROPERTY: StaticClass = StaticClass::new("Class Core.Property");
+static FUNCTION: StaticClass = StaticClass::new("Class Core.Function");
+static ENUM: StaticClass = StaticClass::new("Class Core.Enum");
+#[cfg(feature = "hook")]
+static LEVEL: StaticClass = StaticClass::new("Class Engine.Level");
+
+impl StaticClass {
+    const fn new(full_name: &'static str) -> Self {
+        Self {
+            class: Global::null(),
+            full_name,
+        }
+    }
+
+    /// The class, or null if it hasn't loaded yet.
+    unsafe fn get(&self) -> *const Class {
+        if self.class.is_null() {
+            if let Some(class) = GLOBAL_OBJECTS.get().find(self.full_name) {
+                self.class.set(class as *mut Class);
+            }
+        }
+
+        self.class.ptr()
+    }
+}
+
 impl Struct {
     pub unsafe fn iter_children(&self) -> impl Iterator<Item = &Property> {
         iter::successors(self.children.cast::<Property>().as_ref(), |property| {
             property.next.cast::<Property>().as_ref()
         })
     }
+
+    /// The children that are properties, i.e., fields or parameters.
+    pub unsafe fn iter_properties(&self) -> impl Iterator<Item = &Property> {
+        let property = PROPERTY.get();
+        self.iter_children().filter(move |p| p.is(property))
+    }
+
+    pub unsafe fn iter_functions(&self) -> impl Iterator<Item = &Function> {
+        let function = FUNCTION.get();
+
+        self.iter_children()
+            .filter(move |p| p.is(function))
+            .map(|p| cast::<Function>(p))
+    }
+
+    pub unsafe fn iter_enums(&self) -> impl Iterator<Item = &Enum> {
+        let enumeration = ENUM.get();
+
+        self.iter_children()
+            .filter(move |p| p.is(enumeration))
+            .map(|p| cast::<Enum>(p))
+    }
 }
 
 pub type FString = Array<u16>; // &[u16] -> OsString -> Cow<str>
@@ -242,6 +1436,14 @@ pub struct Enum {
     pub variants: Array<NameIndex>,
 }
 
+impl Enum {
+    /// The name of the variant whose value is `value`, e.g., to log a byte
+    /// property that holds this enum. `None` if `value` is out of range.
+    pub unsafe fn variant_name(&self, value: u8) -> Option<&'static str> {
+        self.variants.get(usize::from(value))?.text()
+    }
+}
+
 impl Deref for Enum {
     type Target = Field;
 
@@ -311,6 +1513,11 @@ impl Function {
         const NATIVE: u32 = 0x400;
         self.flags & NATIVE == NATIVE
     }
+
+    pub fn is_exec(&self) -> bool {
+        const EXEC: u32 = 0x200;
+        self.flags & EXEC == EXEC
+    }
 }
 
 #[repr(C)]
@@ -353,6 +1560,17 @@ impl DerefMut for Class {
     }
 }
 
+impl Class {
+    /// The template that new instances of this class copy, e.g.,
+    /// "WillowPlayerController WillowGame.Default__WillowPlayerController".
+    pub unsafe fn default_object(&self) -> Option<*mut Object> {
+        let name = self.name()?;
+        let package = self.package()?.name()?;
+        let full_name = format!("{} {}.Default__{}", name, package, name);
+        GLOBAL_OBJECTS.get().find_mut(&full_name)
+    }
+}
+
 #[repr(C)]
 pub struct Property {
     pub field: Field,
@@ -498,10 +1716,25 @@ impl DerefMut for InterfaceProperty {
     }
 }
 
+/// An object that implements the interface `T`, along with the address of
+/// the object's implementation of the interface's native vtable.
 #[repr(C)]
-pub struct ScriptInterface {
+pub struct ScriptInterface<T> {
     pub object: *mut Object,
     pub interface: *mut c_void,
+    marker: PhantomData<*mut T>,
+}
+
+#[cfg(feature = "hook")]
+impl<T> ScriptInterface<T> {
+    pub fn as_object(&self) -> Option<*mut Object> {
+        Some(self.object).filter(|o| !o.is_null())
+    }
+
+    /// The object as `T`, the interface's class, to call its functions.
+    pub fn get(&self) -> Option<*mut T> {
+        self.as_object().map(|o| o.cast())
+    }
 }
 
 #[repr(C)]
@@ -568,8 +1801,10 @@ impl DerefMut for MapProperty {
 #[repr(C)]
 pub struct DelegateProperty {
     pub property: Property,
-    pub function1: *mut Function,
-    pub function2: *mut Function,
+    pub function: *mut Function,
+
+    /// The delegate this one was declared from, if any.
+    pub source_delegate: *mut Function,
 }
 
 impl Deref for DelegateProperty {
@@ -586,7 +1821,304 @@ impl DerefMut for DelegateProperty {
     }
 }
 
+/// A function bound to an object, like a callback the game runs later.
 #[repr(C)]
 pub struct ScriptDelegate {
-    pad: [u8; 0xC],
+    pub object: *mut Object,
+    pub function: NameIndex,
+}
+
+#[cfg(feature = "hook")]
+impl ScriptDelegate {
+    /// Whether running the delegate calls anything. Index 0 is "None".
+    pub fn is_bound(&self) -> bool {
+        !self.object.is_null() && self.function.index != 0
+    }
+
+    pub unsafe fn function_name(&self) -> Option<Cow<'static, str>> {
+        self.function.name()
+    }
+
+    /// Run `function` on `object` when the delegate runs. The game looks the
+    /// function up by name on `object`'s class, so it may be an override.
+    pub fn bind(&mut self, object: *mut Object, function: &Function) {
+        self.object = object;
+        self.function = NameIndex {
+            index: function.name.index,
+            number: function.name.number,
+        };
+    }
+
+    pub fn unbind(&mut self) {
+        self.object = ptr::null_mut();
+        self.function = NameIndex {
+            index: 0,
+            number: 0,
+        };
+    }
+}
+
+/// `Core.Object.Vector`, which the SDK uses instead of generating its own.
+#[cfg(feature = "hook")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[cfg(feature = "hook")]
+impl Vector {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn distance(self, other: Self) -> f32 {
+        (self - other).length()
+    }
+
+    /// This vector scaled to a length of 1, or the zero vector if it's zero.
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+
+        if length > 0.0 {
+            self / length
+        } else {
+            self
+        }
+    }
+
+    /// The rotation that faces along this vector, without roll.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_rotator(self) -> Rotator {
+        let yaw = self.y.atan2(self.x);
+        let pitch = self.z.atan2((self.x * self.x + self.y * self.y).sqrt());
+
+        Rotator {
+            pitch: (pitch * Rotator::UNITS_PER_RADIAN) as i32,
+            yaw: (yaw * Rotator::UNITS_PER_RADIAN) as i32,
+            roll: 0,
+        }
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Add for Vector {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Sub for Vector {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Mul<f32> for Vector {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        Self::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Div<f32> for Vector {
+    type Output = Self;
+
+    fn div(self, scale: f32) -> Self {
+        Self::new(self.x / scale, self.y / scale, self.z / scale)
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Neg for Vector {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// `Core.Object.Rotator`, in Unreal rotation units, where 65536 is a turn.
+#[cfg(feature = "hook")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rotator {
+    pub pitch: i32,
+    pub yaw: i32,
+    pub roll: i32,
+}
+
+#[cfg(feature = "hook")]
+impl Rotator {
+    pub const UNITS_PER_RADIAN: f32 = 65536.0 / (2.0 * std::f32::consts::PI);
+
+    #[allow(clippy::cast_precision_loss)]
+    fn radians(units: i32) -> f32 {
+        // Wrap first, since rotators accumulate whole turns.
+        (units & 0xFFFF) as f32 / Self::UNITS_PER_RADIAN
+    }
+
+    /// The unit vector this rotation faces along.
+    pub fn to_direction(self) -> Vector {
+        self.axes().0
+    }
+
+    /// The forward, right, and up unit vectors of this rotation, like the
+    /// engine's `GetAxes`.
+    pub fn axes(self) -> (Vector, Vector, Vector) {
+        let (sp, cp) = Self::radians(self.pitch).sin_cos();
+        let (sy, cy) = Self::radians(self.yaw).sin_cos();
+        let (sr, cr) = Self::radians(self.roll).sin_cos();
+
+        let forward = Vector::new(cp * cy, cp * sy, sp);
+        let right = Vector::new(sr * sp * cy - cr * sy, sr * sp * sy + cr * cy, -sr * cp);
+        let up = Vector::new(-(cr * sp * cy + sr * sy), cy * sr - cr * sp * sy, cr * cp);
+
+        (forward, right, up)
+    }
+
+    pub fn to_quat(self) -> Quat {
+        let (sp, cp) = (Self::radians(self.pitch) / 2.0).sin_cos();
+        let (sy, cy) = (Self::radians(self.yaw) / 2.0).sin_cos();
+        let (sr, cr) = (Self::radians(self.roll) / 2.0).sin_cos();
+
+        Quat {
+            x: cr * sp * sy - sr * cp * cy,
+            y: -cr * sp * cy - sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+            w: cr * cp * cy + sr * sp * sy,
+        }
+    }
+}
+
+/// `Core.Object.Quat`.
+#[cfg(feature = "hook")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+#[cfg(feature = "hook")]
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(feature = "hook")]
+impl Quat {
+    pub const IDENTITY: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+
+    /// Rotate `v` by this quaternion, which must be normalized.
+    pub fn rotate(self, v: Vector) -> Vector {
+        let q = Vector::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.w + q.cross(t)
+    }
+}
+
+/// Where `point` appears on a `width` by `height` screen, seen from a camera at
+/// `location` facing `rotation` with a horizontal field of view of `fov`
+/// degrees. `None` if the point is behind the camera.
+#[cfg(feature = "hook")]
+pub fn world_to_screen(
+    point: Vector,
+    location: Vector,
+    rotation: Rotator,
+    fov: f32,
+    width: f32,
+    height: f32,
+) -> Option<(f32, f32)> {
+    let (forward, right, up) = rotation.axes();
+    let delta = point - location;
+    let depth = delta.dot(forward);
+
+    if depth < 1.0 {
+        return None;
+    }
+
+    let center_x = width / 2.0;
+    let center_y = height / 2.0;
+    let scale = center_x / (fov.to_radians() / 2.0).tan() / depth;
+
+    Some((
+        center_x + delta.dot(right) * scale,
+        center_y - delta.dot(up) * scale,
+    ))
+}
+
+#[cfg(all(feature = "hook", feature = "glam"))]
+impl From<Vector> for glam::Vec3 {
+    fn from(v: Vector) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+#[cfg(all(feature = "hook", feature = "glam"))]
+impl From<glam::Vec3> for Vector {
+    fn from(v: glam::Vec3) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+#[cfg(all(feature = "hook", feature = "glam"))]
+impl From<Quat> for glam::Quat {
+    fn from(q: Quat) -> Self {
+        Self::from_xyzw(q.x, q.y, q.z, q.w)
+    }
+}
+
+#[cfg(all(feature = "hook", feature = "glam"))]
+impl From<glam::Quat> for Quat {
+    fn from(q: glam::Quat) -> Self {
+        Self {
+            x: q.x,
+            y: q.y,
+            z: q.z,
+            w: q.w,
+        }
+    }
+}
+
+/// For the way back, use `Vector::to_rotator` on the rotated forward vector.
+#[cfg(all(feature = "hook", feature = "glam"))]
+impl From<Rotator> for glam::Quat {
+    fn from(r: Rotator) -> Self {
+        r.to_quat().into()
+    }
 }
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::ptr;
+
+/// A pointer that one thread publishes and any thread can read, e.g., a game
+/// global that the injector thread finds and the game thread then uses.
+pub struct Global<T>(AtomicPtr<T>);
+
+impl<T> Global<T> {
+    pub const fn null() -> Self {
+        Self(AtomicPtr::new(ptr::null_mut()))
+    }
+
+    pub fn ptr(&self) -> *mut T {
+        self.0.load(Ordering::Acquire)
+    }
+
+    pub fn set(&self, pointer: *mut T) {
+        self.0.store(pointer, Ordering::Release);
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.ptr().is_null()
+    }
+
+    /// The pointee, which must be set and outlive the crate.
+    pub unsafe fn get(&self) -> &'static T {
+        &*self.ptr()
+    }
+
+    /// The address of the pointer itself, for APIs like Detours that
+    /// overwrite it in place. `AtomicPtr<T>` has the same layout as `*mut T`.
+    pub fn as_mut_ptr(&self) -> *mut *mut T {
+        &self.0 as *const AtomicPtr<T> as *mut *mut T
+    }
+}
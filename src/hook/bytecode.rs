@@ -0,0 +1,131 @@
+use crate::game::{Function, Object};
+use crate::hook::reflect;
+use crate::GLOBAL_OBJECTS;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::info;
+use thiserror::Error;
+
+/// `EX_Nothing`, the bytecode instruction that does nothing.
+const EX_NOTHING: u8 = 0x0B;
+
+// Changed from any thread, reverted when unhooking.
+static PATCHES: Mutex<Vec<Patch>> = Mutex::new(Vec::new());
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("no function named \"{0}\"")]
+    FunctionNotFound(String),
+
+    #[error("{function} has {size} bytes of script, so can't patch {count} at {offset}")]
+    OutOfBounds {
+        function: String,
+        offset: usize,
+        count: usize,
+        size: usize,
+    },
+}
+
+/// The bytes a patch replaced, to put them back.
+struct Patch {
+    function: usize,
+
+    /// The function's object index and full name, to check that it wasn't
+    /// freed, e.g., with a map's level script, before reverting.
+    index: u32,
+    full_name: String,
+
+    offset: usize,
+    original: Vec<u8>,
+}
+
+// The function is only dereferenced to revert, after checking it's alive.
+unsafe impl Send for Patch {}
+
+impl Patch {
+    unsafe fn is_alive(&self) -> bool {
+        let function = self.function as *mut Object;
+
+        GLOBAL_OBJECTS
+            .get()
+            .get(self.index as usize)
+            .map_or(false, |&o| o == function)
+            && (*function).full_name().as_deref() == Some(&*self.full_name)
+    }
+}
+
+fn patches() -> MutexGuard<'static, Vec<Patch>> {
+    PATCHES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+unsafe fn find(function: &str) -> Result<*mut Function, Error> {
+    reflect::find_function(function).ok_or_else(|| Error::FunctionNotFound(function.to_owned()))
+}
+
+/// A copy of the script of `function`, e.g.,
+/// "WillowGame.WillowPlayerController.PlayerTick". Native functions have none.
+pub unsafe fn read(function: &str) -> Result<Vec<u8>, Error> {
+    Ok((*find(function)?).script.to_vec())
+}
+
+/// Overwrite the script of `function` at `offset` with `bytes`, remembering the
+/// original bytes until `revert_all`. Patch on the game thread, so that the
+/// function isn't running.
+pub unsafe fn patch(function: &str, offset: usize, bytes: &[u8]) -> Result<(), Error> {
+    let resolved = find(function)?;
+    let script = &mut (*resolved).script;
+    let size = script.len();
+
+    let target = offset
+        .checked_add(bytes.len())
+        .and_then(|end| script.get_mut(offset..end))
+        .ok_or_else(|| Error::OutOfBounds {
+            function: function.to_owned(),
+            offset,
+            count: bytes.len(),
+            size,
+        })?;
+
+    patches().push(Patch {
+        function: resolved as usize,
+        index: (*resolved).index,
+        full_name: (*resolved).full_name().unwrap_or_default(),
+        offset,
+        original: target.to_vec(),
+    });
+
+    target.copy_from_slice(bytes);
+    info!(
+        "Patched {} bytes of {} at {}.",
+        bytes.len(),
+        function,
+        offset
+    );
+
+    Ok(())
+}
+
+/// Replace `count` bytes of the script of `function` at `offset` with
+/// instructions that do nothing, e.g., 3 bytes to remove an `EX_Jump`.
+pub unsafe fn nop(function: &str, offset: usize, count: usize) -> Result<(), Error> {
+    patch(function, offset, &vec![EX_NOTHING; count])
+}
+
+/// Put back the original bytes of every patch, newest first so that
+/// overlapping patches unwind in order. Patches of functions that are gone
+/// are dropped.
+pub unsafe fn revert_all() {
+    for patch in patches().drain(..).rev() {
+        if !patch.is_alive() {
+            continue;
+        }
+
+        let script = &mut (*(patch.function as *mut Function)).script;
+        let end = patch.offset + patch.original.len();
+
+        if let Some(target) = script.get_mut(patch.offset..end) {
+            target.copy_from_slice(&patch.original);
+        }
+    }
+}
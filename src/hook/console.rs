@@ -0,0 +1,426 @@
+use crate::game::Color;
+use crate::hook::{
+    self, bytecode, exec, filter, input, overhead, passive, plugins, profiler, recorder, reflect,
+    scheduler, scripting, user, watchdog, Hook,
+};
+use crate::GLOBAL_OBJECTS;
+
+use std::io::{self, BufRead};
+use std::mem;
+
+use log::{info, warn};
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::synchapi::Sleep;
+use winapi::um::winbase::STD_INPUT_HANDLE;
+use winapi::um::wincon::PeekConsoleInputW;
+use winapi::um::wincontypes::{INPUT_RECORD, KEY_EVENT};
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::VK_RETURN;
+
+const HELP: &str = "\
+Commands:
+    filter include <pattern>    Only record events matching <pattern>.
+    filter exclude <pattern>    Never record events matching <pattern>.
+    filter clear                Record every event.
+    filter show                 Print the current filter.
+    profile start               Start counting calls and time per function.
+    profile stop                Stop profiling and forget the counts.
+    profile report              Write the counts to profile.txt and reset them.
+    overhead start|stop         Log how much time the ProcessEvent hook adds per frame.
+    bind <action> <key>         Change the hotkey of <action>, e.g., bind unload F12.
+    binds                       Print the hotkeys.
+    remap <from> <to>           Run the exec function <to> whenever the game runs <from>.
+    features                    Print the features and whether each is on.
+    toggle <feature>            Turn <feature> on or off, e.g., toggle ammo.
+    script reload               Reload the scripts in the scripts folder.
+    plugin reload               Reload the DLLs in the plugins folder.
+    events <count>              Print the <count> most recent recorded events.
+    replay <index>              Call event <index> from the events list again.
+    hooks                       Print each detour's state and number of calls.
+    diff <full name>            Print the properties of an object that differ from its default.
+    find <pattern>              Print the objects whose names match <pattern>.
+    bytecode <function>         Print the script of <function> in hex.
+    patch <function> <offset> <hex>  Overwrite the script of <function> at <offset>.
+    nop <function> <offset> <count>  Make <count> bytes of script at <offset> do nothing.
+    unpatch                     Put back every patched script.
+    fly <speed>                 Set how fast the fly hotkey flies.
+    pos save|load <slot>        Save the player's position to <slot>, or teleport to it.
+    pos list                    Print the saved positions of the current map.
+    speed <factor>              Run the game at <factor> times its normal speed.
+    give <definition> <level>   Spawn an item from a balance definition into the backpack.
+    fov <degrees>|off           Keep the field of view at <degrees>, or stop.
+    damage <factor>             Multiply the player's damage by <factor> while it's on.
+    vacuum radius <units>       Pull in loot up to <units> away while the vacuum is on.
+    vacuum rarity <level>       Pull in only items of at least rarity <level>.
+    pickup radius <units>       Claim money, ammo and health up to <units> away.
+    kill <meters>               Kill the hostile pawns within <meters> of the player.
+    move speed|jump <factor>    Multiply the player's speed or jump height while it's on.
+    freecam speed <speed>       Set how fast the free camera flies.
+    freecam hud on|off          Show or hide the HUD in the free camera.
+    crosshair size|gap <pixels> Set how long the crosshair's arms are, or how far apart.
+    crosshair color <r> <g> <b> Set the crosshair's color.
+    aim cone <degrees>          Aim only at enemies within <degrees> of the crosshair.
+    vehicle                     Bring the nearest free vehicle, or a new one, to the player.
+    travel list                 Print the fast travel stations.
+    travel unlock               Mark every fast travel station as discovered.
+    travel <station>            Fast travel to <station>.
+    inventory [file]            Write the player's items to [file], or inventory.txt.
+    radar range <units>         Set how far the radar sees.
+    route record <name>         Record the player's route as <name>.
+    route stop                  Stop recording and save the route.
+    route show <name>           Draw the route <name> with a ghost running it.
+    route hide                  Stop drawing the route.
+    farm <sequence> <repeats>   Run farm/<sequence>.txt <repeats> times.
+    farm stop                   Stop farming. The stop farming hotkey also works.
+    passive                     Print whether passive mode is on.
+    pause                       Detach the hooks until resumed. The pause hotkey also works.
+    resume                      Reattach the hooks.
+Patterns may use * and ?. Press enter on an empty line, or the unload hotkey, to unhook.";
+
+/// How often to check for a command or an unload request.
+const POLL_INTERVAL_MS: u32 = 50;
+
+/// The most objects `find` prints.
+const MAX_FOUND: usize = 100;
+
+/// Run commands from the console until an empty line, the end of input, or
+/// an unload request from elsewhere, like a hotkey. Also pauses and resumes
+/// `hook` when asked to.
+pub unsafe fn run(hook: &mut Hook) {
+    println!("{}", HELP);
+
+    let stdin = io::stdin();
+    let input = GetStdHandle(STD_INPUT_HANDLE);
+
+    while !hook::unload_requested() {
+        if watchdog::game_exiting() {
+            info!("The game window is gone. Unhooking.");
+            break;
+        }
+
+        // The game thread doesn't poll hotkeys while the hooks are detached.
+        if !hook.is_enabled() {
+            input::poll_action("pause");
+            input::poll_action("unload");
+        }
+
+        if hook::take_toggle_pause() {
+            set_enabled(hook, !hook.is_enabled());
+        }
+
+        if !line_ready(input) {
+            Sleep(POLL_INTERVAL_MS);
+            continue;
+        }
+
+        let mut line = String::new();
+
+        if let Err(e) = stdin.lock().read_line(&mut line) {
+            warn!("Failed to read from the console: {}", e);
+            break;
+        }
+
+        if !execute(hook, line.trim()) {
+            break;
+        }
+    }
+}
+
+/// Whether a whole line is waiting in the console, so that reading it won't
+/// block. The input buffer also holds focus and mouse events, so it being
+/// signaled isn't enough.
+unsafe fn line_ready(input: HANDLE) -> bool {
+    let mut records: [INPUT_RECORD; 512] = mem::zeroed();
+    let mut count = 0;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let capacity = records.len() as u32;
+
+    if PeekConsoleInputW(input, records.as_mut_ptr(), capacity, &mut count) == 0 {
+        return false;
+    }
+
+    records[..count as usize].iter().any(|record| {
+        record.EventType == KEY_EVENT && {
+            let key = record.Event.KeyEvent();
+            key.bKeyDown != 0 && i32::from(key.wVirtualKeyCode) == VK_RETURN
+        }
+    })
+}
+
+unsafe fn set_enabled(hook: &mut Hook, enabled: bool) {
+    let result = if enabled {
+        hook.enable()
+    } else {
+        hook.disable()
+    };
+
+    if let Err(e) = result {
+        warn!("{}", e);
+    }
+}
+
+/// Run `f` on the game thread at the next frame, for commands that touch
+/// objects.
+fn on_game_thread(f: impl FnOnce() + Send + 'static) {
+    scheduler::after_frames(0, f);
+}
+
+/// Print the full names of the objects matching `pattern` on the game thread,
+/// while the objects can't change under us.
+fn log_matching(pattern: String) {
+    scheduler::after_frames(0, move || unsafe {
+        let mut count = 0;
+
+        for object in GLOBAL_OBJECTS.get().find_matching(&pattern) {
+            if count < MAX_FOUND {
+                info!("{}", (*object).full_name().as_deref().unwrap_or("?"));
+            }
+
+            count += 1;
+        }
+
+        if count > MAX_FOUND {
+            info!("... and {} more.", count - MAX_FOUND);
+        }
+
+        info!("{} objects match \"{}\".", count, pattern);
+    });
+}
+
+/// Print the script of `function` on the game thread, 16 bytes per line.
+fn log_bytecode(function: String) {
+    scheduler::after_frames(0, move || unsafe {
+        match bytecode::read(&function) {
+            Ok(script) => {
+                for (i, line) in script.chunks(16).enumerate() {
+                    let hex: Vec<String> = line.iter().map(|b| format!("{:02X}", b)).collect();
+                    info!("{:>6}: {}", i * 16, hex.join(" "));
+                }
+
+                info!("{} has {} bytes of script.", function, script.len());
+            }
+            Err(e) => warn!("{}", e),
+        }
+    });
+}
+
+/// Parse bytes written as hex digits, e.g., "0B0B0B".
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Run `line`. Returns false if it asks to unhook.
+unsafe fn execute(hook: &mut Hook, line: &str) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    match words.as_slice() {
+        [] => return false,
+        ["filter", "include", pattern] => filter::include(pattern),
+        ["filter", "exclude", pattern] => filter::exclude(pattern),
+        ["filter", "clear"] => filter::clear(),
+        ["filter", "show"] => filter::show(),
+        ["profile", "start"] => profiler::start(),
+        ["profile", "stop"] => profiler::stop(),
+        ["profile", "report"] => {
+            if let Err(e) = profiler::report() {
+                warn!("Failed to write the profile: {}", e);
+            }
+        }
+        ["overhead", "start"] => overhead::start(),
+        ["overhead", "stop"] => overhead::stop(),
+        ["bind", action, key] => {
+            if let Err(e) = input::rebind(action, key) {
+                warn!("{}", e);
+            }
+        }
+        ["binds"] => input::show(),
+        ["remap", from, to] => {
+            if !passive::refuses("Remapping") {
+                exec::remap(from, to);
+                info!("{} now runs {}.", from, to);
+            }
+        }
+        ["features"] => user::feature::show(),
+        ["toggle", action @ ..] if !action.is_empty() => {
+            let action = action.join(" ");
+            on_game_thread(move || unsafe { user::feature::toggle(&action) });
+        }
+        ["script", "reload"] => {
+            if !passive::refuses("Scripting") {
+                scripting::request_reload();
+            }
+        }
+        ["plugin", "reload"] => {
+            if !passive::refuses("Plugins") {
+                plugins::request_reload();
+            }
+        }
+        ["events", count] => match count.parse() {
+            Ok(count) => recorder::list(count),
+            Err(_) => println!("Expected a number of events, not \"{}\".", count),
+        },
+        ["replay", index] => match index.parse() {
+            Ok(_) if passive::refuses("Replaying") => {}
+            Ok(index) => recorder::replay(index),
+            Err(_) => println!("Expected an event index, not \"{}\".", index),
+        },
+        ["hooks"] => hook.report(),
+        ["diff", full_name @ ..] if !full_name.is_empty() => {
+            reflect::log_diff_default(full_name.join(" "));
+        }
+        ["find", pattern @ ..] if !pattern.is_empty() => log_matching(pattern.join(" ")),
+        ["bytecode", function] => log_bytecode((*function).to_owned()),
+        ["patch", function, offset, hex] => match (offset.parse(), parse_hex(hex)) {
+            _ if passive::refuses("Patching") => {}
+            (Ok(offset), Some(bytes)) => {
+                let function = (*function).to_owned();
+
+                on_game_thread(move || unsafe {
+                    if let Err(e) = bytecode::patch(&function, offset, &bytes) {
+                        warn!("{}", e);
+                    }
+                });
+            }
+            _ => println!(
+                "Expected an offset and hex bytes, not \"{} {}\".",
+                offset, hex
+            ),
+        },
+        ["nop", function, offset, count] => match (offset.parse(), count.parse()) {
+            _ if passive::refuses("Patching") => {}
+            (Ok(offset), Ok(count)) => {
+                let function = (*function).to_owned();
+
+                on_game_thread(move || unsafe {
+                    if let Err(e) = bytecode::nop(&function, offset, count) {
+                        warn!("{}", e);
+                    }
+                });
+            }
+            _ => println!(
+                "Expected an offset and a count, not \"{} {}\".",
+                offset, count
+            ),
+        },
+        ["unpatch"] => on_game_thread(|| unsafe { bytecode::revert_all() }),
+        ["fly", speed] => match speed.parse() {
+            Ok(speed) => user::fly::set_speed(speed),
+            Err(_) => println!("Expected a speed, not \"{}\".", speed),
+        },
+        ["pos", "list"] => on_game_thread(|| unsafe { user::teleport::list() }),
+        ["pos", command @ ("save" | "load"), slot] => match slot.parse() {
+            Ok(slot) if *command == "save" => on_game_thread(move || unsafe {
+                user::teleport::save(slot);
+            }),
+            Ok(slot) => on_game_thread(move || unsafe { user::teleport::teleport(slot) }),
+            Err(_) => println!("Expected a slot number, not \"{}\".", slot),
+        },
+        ["speed", factor] => match factor.parse() {
+            Ok(factor) => on_game_thread(move || unsafe { user::speed::set(factor) }),
+            Err(_) => println!("Expected a speed factor, not \"{}\".", factor),
+        },
+        ["give", definition, level] => match level.parse() {
+            Ok(level) => {
+                let definition = (*definition).to_owned();
+                on_game_thread(move || unsafe { user::give::give(&definition, level) });
+            }
+            Err(_) => println!("Expected a level, not \"{}\".", level),
+        },
+        ["damage", factor] => match factor.parse() {
+            Ok(factor) => user::damage::set_factor(factor),
+            Err(_) => println!("Expected a factor, not \"{}\".", factor),
+        },
+        ["vacuum", "radius", radius] => match radius.parse() {
+            Ok(radius) => user::vacuum::set_radius(radius),
+            Err(_) => println!("Expected a radius, not \"{}\".", radius),
+        },
+        ["vacuum", "rarity", rarity] => match rarity.parse() {
+            Ok(rarity) => user::vacuum::set_rarity(rarity),
+            Err(_) => println!("Expected a rarity level, not \"{}\".", rarity),
+        },
+        ["pickup", "radius", radius] => match radius.parse() {
+            Ok(radius) => user::pickup::set_radius(radius),
+            Err(_) => println!("Expected a radius, not \"{}\".", radius),
+        },
+        ["kill", meters] => match meters.parse() {
+            Ok(meters) => on_game_thread(move || unsafe { user::kill::within(meters) }),
+            Err(_) => println!("Expected meters, not \"{}\".", meters),
+        },
+        ["move", "speed", factor] => match factor.parse() {
+            Ok(factor) => user::movement::set_speed(factor),
+            Err(_) => println!("Expected a factor, not \"{}\".", factor),
+        },
+        ["move", "jump", factor] => match factor.parse() {
+            Ok(factor) => user::movement::set_jump(factor),
+            Err(_) => println!("Expected a factor, not \"{}\".", factor),
+        },
+        ["freecam", "speed", speed] => match speed.parse() {
+            Ok(speed) => user::freecam::set_speed(speed),
+            Err(_) => println!("Expected a speed, not \"{}\".", speed),
+        },
+        ["freecam", "hud", "on"] => user::freecam::set_hide_hud(false),
+        ["freecam", "hud", "off"] => user::freecam::set_hide_hud(true),
+        ["crosshair", "size", pixels] => match pixels.parse() {
+            Ok(pixels) => user::crosshair::set_size(pixels),
+            Err(_) => println!("Expected pixels, not \"{}\".", pixels),
+        },
+        ["crosshair", "gap", pixels] => match pixels.parse() {
+            Ok(pixels) => user::crosshair::set_gap(pixels),
+            Err(_) => println!("Expected pixels, not \"{}\".", pixels),
+        },
+        ["crosshair", "color", r, g, b] => match (r.parse(), g.parse(), b.parse()) {
+            (Ok(r), Ok(g), Ok(b)) => user::crosshair::set_color(Color::rgb(r, g, b)),
+            _ => println!("Expected a color from 0 0 0 to 255 255 255."),
+        },
+        ["aim", "cone", degrees] => match degrees.parse() {
+            Ok(degrees) => user::aim::set_cone(degrees),
+            Err(_) => println!("Expected degrees, not \"{}\".", degrees),
+        },
+        ["vehicle"] => on_game_thread(|| unsafe { user::vehicle::summon() }),
+        ["travel", "list"] => on_game_thread(|| unsafe { user::travel::list() }),
+        ["travel", "unlock"] => on_game_thread(|| unsafe { user::travel::unlock_all() }),
+        ["travel", station @ ..] if !station.is_empty() => {
+            let station = station.join(" ");
+            on_game_thread(move || unsafe { user::travel::travel(&station) });
+        }
+        ["inventory"] => {
+            on_game_thread(|| unsafe { user::inventory::dump(user::inventory::DEFAULT_PATH) });
+        }
+        ["inventory", path] => {
+            let path = (*path).to_owned();
+            on_game_thread(move || unsafe { user::inventory::dump(&path) });
+        }
+        ["radar", "range", range] => match range.parse() {
+            Ok(range) => user::radar::set_range(range),
+            Err(_) => println!("Expected a range, not \"{}\".", range),
+        },
+        ["route", "record", name] => user::route::start(name),
+        ["route", "stop"] => user::route::stop(),
+        ["route", "show", name] => user::route::show(name),
+        ["route", "hide"] => user::route::hide(),
+        ["farm", "stop"] => user::farm::stop(),
+        ["farm", sequence, repeats] => match repeats.parse() {
+            Ok(repeats) => user::farm::start(sequence, repeats),
+            Err(_) => println!("Expected a number of repeats, not \"{}\".", repeats),
+        },
+        ["passive"] => passive::show(),
+        ["fov", "off"] => user::fov::set(None),
+        ["fov", degrees] => match degrees.parse() {
+            Ok(degrees) => user::fov::set(Some(degrees)),
+            Err(_) => println!("Expected degrees, not \"{}\".", degrees),
+        },
+        ["pause"] => set_enabled(hook, false),
+        ["resume"] => set_enabled(hook, true),
+        _ => println!("Unknown command \"{}\".\n{}", line, HELP),
+    }
+
+    true
+}
@@ -0,0 +1,129 @@
+use crate::global::Global;
+use crate::module::{self, Module};
+
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::error;
+use winapi::ctypes::c_void;
+use winapi::shared::ntdef::LONG;
+use winapi::um::errhandlingapi::{AddVectoredExceptionHandler, RemoveVectoredExceptionHandler};
+use winapi::um::minwinbase::{
+    EXCEPTION_ACCESS_VIOLATION, EXCEPTION_ARRAY_BOUNDS_EXCEEDED, EXCEPTION_ILLEGAL_INSTRUCTION,
+    EXCEPTION_INT_DIVIDE_BY_ZERO, EXCEPTION_PRIV_INSTRUCTION,
+};
+use winapi::um::winnt::EXCEPTION_POINTERS;
+use winapi::vc::excpt::{EXCEPTION_CONTINUE_EXECUTION, EXCEPTION_CONTINUE_SEARCH};
+
+static HANDLER: Global<c_void> = Global::null();
+
+// The address range of this DLL. We only step in for faults in our own code.
+static CODE_START: AtomicUsize = AtomicUsize::new(0);
+static CODE_END: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// How many `contain` calls this thread is inside.
+    static DEPTH: Cell<u32> = Cell::new(0);
+
+    /// The exception code and address that `raise_fault` turns into a panic.
+    static FAULT: Cell<Option<(u32, usize)>> = Cell::new(None);
+}
+
+/// Log panics instead of printing them, and turn hardware exceptions in our
+/// code, like access violations, into panics that `contain` can catch.
+pub unsafe fn install() -> Result<(), module::Error> {
+    let module = Module::containing(install as usize)?;
+    CODE_START.store(module.base, Ordering::SeqCst);
+    CODE_END.store(module.end, Ordering::SeqCst);
+
+    HANDLER.set(AddVectoredExceptionHandler(1, Some(handler)));
+    panic::set_hook(Box::new(|info| error!("{}", info)));
+
+    Ok(())
+}
+
+pub unsafe fn uninstall() {
+    let handler = HANDLER.ptr();
+
+    if !handler.is_null() {
+        RemoveVectoredExceptionHandler(handler);
+        HANDLER.set(ptr::null_mut());
+    }
+
+    let _ = panic::take_hook();
+}
+
+/// Run `f`, which is feature code called `name`. If it panics or faults,
+/// log it and return `None` so that the caller can disable the feature
+/// instead of taking the game down with it.
+pub fn contain<R>(name: &str, f: impl FnOnce() -> R) -> Option<R> {
+    DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    DEPTH.with(|depth| depth.set(depth.get() - 1));
+
+    match result {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            error!("Disabled {} because it crashed: {}", name, message(&*payload));
+            None
+        }
+    }
+}
+
+fn message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+fn is_recoverable(code: u32) -> bool {
+    // Not stack overflows, since there's no stack left to unwind with.
+    [
+        EXCEPTION_ACCESS_VIOLATION,
+        EXCEPTION_ARRAY_BOUNDS_EXCEEDED,
+        EXCEPTION_ILLEGAL_INSTRUCTION,
+        EXCEPTION_INT_DIVIDE_BY_ZERO,
+        EXCEPTION_PRIV_INSTRUCTION,
+    ]
+    .contains(&code)
+}
+
+/// Make a faulting instruction in our code look like it called `raise_fault`,
+/// which panics, so that the panic unwinds to the nearest `contain`.
+unsafe extern "system" fn handler(pointers: *mut EXCEPTION_POINTERS) -> LONG {
+    let record = &*(*pointers).ExceptionRecord;
+    let context = &mut *(*pointers).ContextRecord;
+    let address = record.ExceptionAddress as usize;
+
+    let ours = (CODE_START.load(Ordering::SeqCst)..CODE_END.load(Ordering::SeqCst))
+        .contains(&address);
+
+    if !ours || !is_recoverable(record.ExceptionCode) || DEPTH.with(Cell::get) == 0 {
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+
+    FAULT.with(|fault| fault.set(Some((record.ExceptionCode, address))));
+
+    // Push the faulting address as the return address, then jump.
+    context.Esp -= 4;
+    *(context.Esp as *mut u32) = context.Eip;
+
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        context.Eip = raise_fault as usize as u32;
+    }
+
+    EXCEPTION_CONTINUE_EXECUTION
+}
+
+fn raise_fault() -> ! {
+    let (code, address) = FAULT.with(Cell::take).unwrap_or_default();
+    panic!("exception {:#010x} at {:#x}", code, address);
+}
@@ -0,0 +1,199 @@
+use crate::game::{Function, Object};
+use crate::hook::contain::contain;
+use crate::hook::reflect;
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+static EVENTS: Mutex<Option<Events>> = Mutex::new(None);
+
+/// The arguments of an intercepted `ProcessEvent` call.
+pub struct Context {
+    pub this: *mut Object,
+    pub function: *mut Function,
+    pub parameters: *mut c_void,
+    pub return_value: *mut c_void,
+}
+
+/// What a `before` subscriber wants to happen to the intercepted call.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+
+    /// Don't call the original `ProcessEvent`. The remaining `before`
+    /// subscribers still run, but the `after` subscribers don't.
+    Block,
+}
+
+enum Callback {
+    Before(Box<dyn FnMut(&mut Context) -> Action>),
+    After(Box<dyn FnMut(&mut Context)>),
+}
+
+struct Subscription {
+    name: &'static str,
+
+    /// Taken out while it runs, so that it can subscribe too.
+    callback: Option<Callback>,
+
+    /// Set once the callback crashes, so that it's never called again.
+    disabled: bool,
+}
+
+#[derive(Default)]
+struct Events {
+    subscriptions: Vec<Subscription>,
+
+    /// Maps a full function name, e.g., "Function Engine.Actor.Tick", to the
+    /// subscriptions listening to it.
+    by_name: HashMap<String, Vec<usize>>,
+
+    /// Maps a UFunction's object index to the subscriptions listening to it,
+    /// filled in the first time the function runs.
+    by_index: HashMap<u32, Vec<usize>>,
+}
+
+// Callbacks are only run on the game thread.
+unsafe impl Send for Events {}
+
+impl Events {
+    /// The subscriptions listening to `function`, looking its name up only the
+    /// first time it runs.
+    unsafe fn listening(&mut self, function: *mut Function) -> Vec<usize> {
+        let by_name = &self.by_name;
+
+        self.by_index
+            .entry((*function).index)
+            .or_insert_with(|| {
+                (*function)
+                    .full_name()
+                    .and_then(|n| by_name.get(&n))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .clone()
+    }
+
+    fn subscribe(&mut self, name: &'static str, callback: Callback) {
+        let index = self.subscriptions.len();
+
+        self.subscriptions.push(Subscription {
+            name,
+            callback: Some(callback),
+            disabled: false,
+        });
+
+        self.by_name
+            .entry(reflect::function_full_name(name))
+            .or_default()
+            .push(index);
+
+        // Functions that already ran look their subscriptions up again.
+        self.by_index.clear();
+    }
+}
+
+fn events() -> MutexGuard<'static, Option<Events>> {
+    EVENTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run the subscribers of the call described by `context`, either the `before`
+/// or the `after` ones. The lock is only held between callbacks.
+unsafe fn dispatch(context: &mut Context, before: bool) -> Action {
+    let listening = match events().as_mut() {
+        Some(events) => events.listening(context.function),
+        None => return Action::Continue,
+    };
+
+    let mut action = Action::Continue;
+
+    for i in listening {
+        let (name, mut callback) = {
+            let mut events = events();
+
+            let subscription = match events.as_mut().and_then(|e| e.subscriptions.get_mut(i)) {
+                Some(subscription) => subscription,
+                None => break,
+            };
+
+            let wanted = match subscription.callback {
+                Some(Callback::Before(_)) => before,
+                Some(Callback::After(_)) => !before,
+                None => false,
+            };
+
+            if subscription.disabled || !wanted {
+                continue;
+            }
+
+            match subscription.callback.take() {
+                Some(callback) => (subscription.name, callback),
+                None => continue,
+            }
+        };
+
+        let result = match &mut callback {
+            Callback::Before(f) => contain(name, || f(context)),
+            Callback::After(f) => contain(name, || {
+                f(context);
+                Action::Continue
+            }),
+        };
+
+        if result == Some(Action::Block) {
+            action = Action::Block;
+        }
+
+        if let Some(subscription) = events().as_mut().and_then(|e| e.subscriptions.get_mut(i)) {
+            subscription.callback = Some(callback);
+            subscription.disabled |= result.is_none();
+        }
+    }
+
+    action
+}
+
+/// Call `callback` whenever `ProcessEvent` runs the UFunction named `function`,
+/// e.g., "WillowGame.WillowPlayerController.PlayerTick", just before the
+/// original `ProcessEvent` runs.
+///
+/// The function's name is only compared when a function first runs, so that
+/// subscribing never scans the global objects. A misspelled function is never
+/// called.
+pub unsafe fn on(function: &'static str, mut callback: impl FnMut(&mut Context) + 'static) {
+    before(function, move |context| {
+        callback(context);
+        Action::Continue
+    });
+}
+
+/// Like `on`, but `callback` can return `Action::Block` to suppress the call.
+pub unsafe fn before(function: &'static str, callback: impl FnMut(&mut Context) -> Action + 'static) {
+    events()
+        .get_or_insert_with(Events::default)
+        .subscribe(function, Callback::Before(Box::new(callback)));
+}
+
+/// Call `callback` after the original `ProcessEvent` returns from running
+/// `function`, e.g., to read its outputs.
+pub unsafe fn after(function: &'static str, callback: impl FnMut(&mut Context) + 'static) {
+    events()
+        .get_or_insert_with(Events::default)
+        .subscribe(function, Callback::After(Box::new(callback)));
+}
+
+/// Run the `before` subscribers of the call described by `context`.
+pub unsafe fn dispatch_before(context: &mut Context) -> Action {
+    dispatch(context, true)
+}
+
+/// Run the `after` subscribers of the call described by `context`.
+pub unsafe fn dispatch_after(context: &mut Context) {
+    dispatch(context, false);
+}
+
+/// Drop every subscription.
+pub fn clear() {
+    *events() = None;
+}
@@ -0,0 +1,199 @@
+use crate::game::{self, Class, Function, Object};
+use crate::hook::contain::contain;
+use crate::hook::events::{self, Action, Context};
+use crate::hook::reflect::{self, Value};
+use crate::GLOBAL_OBJECTS;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::iter;
+use std::mem;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::{error, info, warn};
+
+// Changed from any thread, dispatched on the game thread.
+static EXEC: Mutex<Exec> = Mutex::new(Exec {
+    observers: Vec::new(),
+    remaps: Vec::new(),
+    resolved: None,
+    queue: Vec::new(),
+});
+
+/// The local player's objects besides their controller that exec functions
+/// can be declared on, by the controller's property that holds each.
+const PLAYER_OBJECTS: [&str; 4] = ["PlayerInput", "Pawn", "myHUD", "CheatManager"];
+
+type Observer = Box<dyn FnMut(&str, &mut Context) -> Action + Send>;
+
+struct Exec {
+    /// Observers and whether each crashed.
+    observers: Vec<(Observer, bool)>,
+
+    /// Pairs of function names, looked up on the next dispatch.
+    remaps: Vec<(String, String)>,
+
+    /// Maps a remapped exec function's object index to its replacement.
+    resolved: Option<HashMap<u32, *mut Function>>,
+
+    /// Calls to make on the next frame.
+    queue: Vec<(String, Vec<Value>)>,
+}
+
+// Functions and values are only used on the game thread.
+unsafe impl Send for Exec {}
+
+fn exec() -> MutexGuard<'static, Exec> {
+    EXEC.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Make the queued calls once per frame.
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| unsafe {
+        let queue = mem::take(&mut exec().queue);
+
+        for (function, arguments) in queue {
+            synthesize_now(&function, &arguments);
+        }
+    });
+}
+
+/// Call `observer` with the name and context of every exec function the game
+/// runs, e.g., "StartFire" when a key bound to `StartFire` is pressed. Return
+/// `Action::Block` to swallow the command.
+pub fn on_exec(observer: impl FnMut(&str, &mut Context) -> Action + Send + 'static) {
+    exec().observers.push((Box::new(observer), false));
+}
+
+/// Run the exec function `to` instead of `from` whenever the game runs `from`,
+/// e.g., `remap("Engine.PlayerController.Jump", "...")`. Both must take the
+/// same parameters, since `to` gets `from`'s.
+pub fn remap(from: &str, to: &str) {
+    let mut exec = exec();
+    exec.remaps.push((from.to_owned(), to.to_owned()));
+    exec.resolved = None;
+}
+
+/// Run the exec function `function` with `arguments` on the next frame, as if
+/// a key bound to it was pressed. It runs on the local player's object of the
+/// class that declares it, or else on the first live instance of the class.
+/// Observers and remaps don't see synthesized calls.
+pub fn synthesize(function: &str, arguments: Vec<Value>) {
+    exec().queue.push((function.to_owned(), arguments));
+}
+
+/// Drop every observer, remap, and queued call.
+pub fn clear() {
+    let mut exec = exec();
+    exec.observers.clear();
+    exec.remaps.clear();
+    exec.resolved = None;
+    exec.queue.clear();
+}
+
+/// Run the observers and remaps of the exec function in `context`. Neither
+/// runs under the lock, so that they can observe, remap, and synthesize.
+pub unsafe fn dispatch(context: &mut Context) -> Action {
+    let name = (*context.function).name().unwrap_or(Cow::Borrowed("None"));
+    let name = name.as_ref();
+
+    let mut action = Action::Continue;
+
+    // Taken out while they run. Observers added meanwhile go after them.
+    let mut observers = mem::take(&mut exec().observers);
+
+    for (observer, disabled) in observers.iter_mut().filter(|(_, d)| !d) {
+        match contain(name, || observer(name, context)) {
+            Some(Action::Block) => action = Action::Block,
+            Some(Action::Continue) => {}
+            None => *disabled = true,
+        }
+    }
+
+    {
+        let mut exec = exec();
+        observers.append(&mut exec.observers);
+        exec.observers = observers;
+    }
+
+    if action == Action::Block {
+        return action;
+    }
+
+    let to = exec().resolve().get(&(*context.function).index).copied();
+
+    if let Some(to) = to {
+        (*context.this).process_event(to, context.parameters);
+        action = Action::Block;
+    }
+
+    action
+}
+
+impl Exec {
+    unsafe fn resolve(&mut self) -> &HashMap<u32, *mut Function> {
+        let remaps = &self.remaps;
+
+        self.resolved.get_or_insert_with(|| {
+            let mut resolved = HashMap::new();
+
+            for (from, to) in remaps {
+                match (reflect::find_function(from), reflect::find_function(to)) {
+                    (Some(f), Some(t)) if (*f).params_size == (*t).params_size => {
+                        info!("Remapped {} to {}.", from, to);
+                        resolved.insert((*f).index, t);
+                    }
+                    (Some(_), Some(_)) => warn!("{} and {} take different parameters.", from, to),
+                    (None, _) => warn!("Unable to find \"{}\" to remap.", from),
+                    (_, None) => warn!("Unable to find \"{}\" to remap to.", to),
+                }
+            }
+
+            resolved
+        })
+    }
+}
+
+unsafe fn synthesize_now(function: &str, arguments: &[Value]) {
+    let resolved = match reflect::find_function(function) {
+        Some(resolved) => resolved,
+        None => {
+            warn!("Unable to find \"{}\" to synthesize.", function);
+            return;
+        }
+    };
+
+    let class: *const Class = (*resolved).outer.cast();
+
+    let receiver: *mut Object = match receiver(class) {
+        Some(receiver) => receiver,
+        None => {
+            warn!("No object can run \"{}\" yet.", function);
+            return;
+        }
+    };
+
+    if let Err(e) = reflect::call(receiver, resolved, arguments) {
+        error!("Failed to synthesize {}: {}", function, e);
+    }
+}
+
+/// The local player's object of `class`, or else the first live instance, e.g.,
+/// for the game info.
+unsafe fn receiver(class: *const Class) -> Option<*mut Object> {
+    if let Some(controller) = game::player_controller() {
+        let player_objects = PLAYER_OBJECTS
+            .iter()
+            .filter_map(|&name| (*controller).get_property::<*mut Object>(name).ok())
+            .filter(|o| !o.is_null());
+
+        if let Some(object) = iter::once(controller)
+            .chain(player_objects)
+            .find(|&o| (*o).is(class))
+        {
+            return Some(object);
+        }
+    }
+
+    GLOBAL_OBJECTS.get().iter_instances_of(class).next()
+}
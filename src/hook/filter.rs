@@ -0,0 +1,100 @@
+use crate::wildcard;
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::info;
+
+// Edited from the console thread and read from the game thread.
+static FILTER: Mutex<Filter> = Mutex::new(Filter {
+    include: Vec::new(),
+    exclude: Vec::new(),
+});
+
+/// Whether `FILTER` has any pattern, so that the recorder can check without
+/// taking the lock while everything passes.
+static FILTERING: AtomicBool = AtomicBool::new(false);
+
+/// Decides which events the recorder keeps, by matching patterns against the
+/// full names of the caller and the called function.
+///
+/// An event passes if it matches any include pattern (or there are none) and
+/// matches no exclude pattern. A pattern without wildcards matches anywhere in
+/// the name, e.g., "PlayerTick" is the same as "*PlayerTick*".
+struct Filter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Filter {
+    fn allows(&self, names: &[&str]) -> bool {
+        let any_match = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|p| names.iter().any(|n| wildcard::matches(p, n)))
+        };
+
+        (self.include.is_empty() || any_match(&self.include)) && !any_match(&self.exclude)
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return write!(f, "everything passes");
+        }
+
+        write!(f, "include {:?}, exclude {:?}", self.include, self.exclude)
+    }
+}
+
+fn filter() -> MutexGuard<'static, Filter> {
+    FILTER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Publish `filter`'s patterns to `allows` and log them.
+fn changed(filter: &Filter) {
+    let filtering = !filter.include.is_empty() || !filter.exclude.is_empty();
+    FILTERING.store(filtering, Ordering::Relaxed);
+    info!("Filter: {}", filter);
+}
+
+fn normalize(pattern: &str) -> String {
+    if wildcard::has_wildcards(pattern) {
+        pattern.to_owned()
+    } else {
+        format!("*{}*", pattern)
+    }
+}
+
+/// Only let through events that match `pattern` or another include pattern.
+pub fn include(pattern: &str) {
+    let mut filter = filter();
+    filter.include.push(normalize(pattern));
+    changed(&filter);
+}
+
+/// Drop events that match `pattern`, even if they match an include pattern.
+pub fn exclude(pattern: &str) {
+    let mut filter = filter();
+    filter.exclude.push(normalize(pattern));
+    changed(&filter);
+}
+
+/// Remove every pattern so that everything passes.
+pub fn clear() {
+    let mut filter = filter();
+    filter.include.clear();
+    filter.exclude.clear();
+    changed(&filter);
+}
+
+pub fn show() {
+    info!("Filter: {}", filter());
+}
+
+/// Whether an event involving the objects called `names` should be reported.
+pub fn allows(names: &[&str]) -> bool {
+    !FILTERING.load(Ordering::Relaxed) || filter().allows(names)
+}
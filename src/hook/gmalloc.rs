@@ -0,0 +1,238 @@
+use crate::game::{Array, FString};
+use crate::global::Global;
+use crate::module::Module;
+use crate::profile;
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::ffi::c_void;
+use std::mem;
+use std::ptr;
+
+use log::info;
+use thiserror::Error;
+
+/// Let the allocator pick, as the engine's own arrays do.
+const DEFAULT_ALIGNMENT: u32 = 0;
+
+// FMalloc's vtable starts with FExec::Exec.
+const MALLOC_INDEX: usize = 1;
+const REALLOC_INDEX: usize = 2;
+const FREE_INDEX: usize = 3;
+
+/// The game's `GMalloc` variable, rather than its value, since the engine may
+/// swap allocators, e.g., to a thread-safe proxy.
+static GMALLOC: Global<*mut FMalloc> = Global::null();
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0}")]
+    Module(#[from] crate::module::Error),
+
+    #[error("cannot find GMalloc")]
+    NotFound,
+
+    #[error("GMalloc failed to allocate {0} bytes")]
+    OutOfMemory(usize),
+
+    #[error("an array of {0} elements is too large")]
+    TooLarge(usize),
+}
+
+#[repr(C)]
+struct FMalloc {
+    vtable: *const usize,
+}
+
+/// Find the game's allocator, so that memory the game frees, like an array's
+/// data, is allocated the way the game expects.
+pub unsafe fn find() -> Result<(), Error> {
+    let profile = profile::current();
+    let game = Module::from(profile.module())?;
+    let signature = profile.global_malloc();
+
+    let address = game
+        .find_pattern(signature.pattern)
+        .ok_or(Error::NotFound)?;
+
+    let gmalloc = ((address + signature.offset) as *const *mut *mut FMalloc).read_unaligned();
+    GMALLOC.set(gmalloc);
+    info!("GMALLOC = {:?}", GMALLOC.ptr());
+
+    Ok(())
+}
+
+unsafe fn allocator() -> Result<*mut FMalloc, Error> {
+    if GMALLOC.is_null() || GMALLOC.get().is_null() {
+        Err(Error::NotFound)
+    } else {
+        Ok(*GMALLOC.get())
+    }
+}
+
+unsafe fn method(allocator: *mut FMalloc, index: usize) -> usize {
+    *(*allocator).vtable.add(index)
+}
+
+pub unsafe fn malloc(size: usize) -> Result<*mut c_void, Error> {
+    type Malloc = unsafe extern "fastcall" fn(
+        this: *mut FMalloc,
+        edx: usize,
+        count: usize,
+        alignment: u32,
+    ) -> *mut c_void;
+
+    let allocator = allocator()?;
+    let malloc = mem::transmute::<usize, Malloc>(method(allocator, MALLOC_INDEX));
+    let memory = malloc(allocator, 0, size, DEFAULT_ALIGNMENT);
+
+    if memory.is_null() {
+        Err(Error::OutOfMemory(size))
+    } else {
+        Ok(memory)
+    }
+}
+
+/// Grow or shrink `memory`, which must come from GMalloc or be null.
+pub unsafe fn realloc(memory: *mut c_void, size: usize) -> Result<*mut c_void, Error> {
+    type Realloc = unsafe extern "fastcall" fn(
+        this: *mut FMalloc,
+        edx: usize,
+        original: *mut c_void,
+        count: usize,
+        alignment: u32,
+    ) -> *mut c_void;
+
+    let allocator = allocator()?;
+    let realloc = mem::transmute::<usize, Realloc>(method(allocator, REALLOC_INDEX));
+    let memory = realloc(allocator, 0, memory, size, DEFAULT_ALIGNMENT);
+
+    if memory.is_null() && size != 0 {
+        Err(Error::OutOfMemory(size))
+    } else {
+        Ok(memory)
+    }
+}
+
+/// Free `memory`, which must come from GMalloc or be null.
+pub unsafe fn free(memory: *mut c_void) -> Result<(), Error> {
+    type Free = unsafe extern "fastcall" fn(this: *mut FMalloc, edx: usize, original: *mut c_void);
+
+    let allocator = allocator()?;
+    let free = mem::transmute::<usize, Free>(method(allocator, FREE_INDEX));
+    free(allocator, 0, memory);
+
+    Ok(())
+}
+
+/// A null-terminated copy of `text` in the game's memory, for the game to
+/// keep, e.g., in an array of strings.
+pub unsafe fn string(text: &str) -> Result<FString, Error> {
+    let mut string = FString {
+        data: ptr::null_mut(),
+        count: 0,
+        max: 0,
+    };
+
+    let wide: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+    string.reserve(wide.len())?;
+
+    for c in wide {
+        string.push(c)?;
+    }
+
+    Ok(string)
+}
+
+// Game-owned arrays, grown with the game's allocator so that the game can
+// grow or free them in turn. Elements are never dropped, since they belong to
+// the game.
+impl<T> Array<T> {
+    /// Make room for at least `capacity` elements.
+    pub unsafe fn reserve(&mut self, capacity: usize) -> Result<(), Error> {
+        if capacity <= self.max as usize {
+            return Ok(());
+        }
+
+        let max = cmp::max(capacity, self.max as usize * 3 / 2 + 4);
+        let max_u32 = to_u32(max)?;
+
+        let size = max
+            .checked_mul(mem::size_of::<T>())
+            .ok_or(Error::TooLarge(max))?;
+
+        self.data = realloc(self.data.cast(), size)?.cast();
+        self.max = max_u32;
+
+        Ok(())
+    }
+
+    /// Append `value`.
+    pub unsafe fn push(&mut self, value: T) -> Result<(), Error> {
+        let count = self.count as usize;
+        self.reserve(count + 1)?;
+        ptr::write(self.data.add(count), value);
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Put `value` at `index`, shifting the later elements up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of elements.
+    pub unsafe fn insert(&mut self, index: usize, value: T) -> Result<(), Error> {
+        let count = self.count as usize;
+        assert!(index <= count, "insert index {} > count {}", index, count);
+
+        self.reserve(count + 1)?;
+
+        let slot = self.data.add(index);
+        ptr::copy(slot, slot.add(1), count - index);
+        ptr::write(slot, value);
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Take the element at `index` out, shifting the later elements down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub unsafe fn remove(&mut self, index: usize) -> T {
+        let count = self.count as usize;
+        assert!(index < count, "remove index {} >= count {}", index, count);
+
+        let slot = self.data.add(index);
+        let value = ptr::read(slot);
+        ptr::copy(slot.add(1), slot, count - index - 1);
+        self.count -= 1;
+
+        value
+    }
+
+    /// Forget every element, keeping the memory for later pushes.
+    pub fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    /// Set the number of elements, filling new slots with `value`.
+    pub unsafe fn resize(&mut self, count: usize, value: T) -> Result<(), Error>
+    where
+        T: Copy,
+    {
+        self.reserve(count)?;
+
+        for i in self.count as usize..count {
+            ptr::write(self.data.add(i), value);
+        }
+
+        self.count = to_u32(count)?;
+        Ok(())
+    }
+}
+
+fn to_u32(n: usize) -> Result<u32, Error> {
+    u32::try_from(n).map_err(|_| Error::TooLarge(n))
+}
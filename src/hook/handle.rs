@@ -0,0 +1,86 @@
+use crate::game::Object;
+use crate::GLOBAL_OBJECTS;
+
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A weak reference to a game object that notices when the object is gone.
+///
+/// Raw pointers go stale when, e.g., the map changes, and the freed slot in the
+/// global objects may be reused by an unrelated object. A handle remembers the
+/// object's index and name, and checks both before each use.
+pub struct Handle<T> {
+    index: u32,
+    name_index: u32,
+    name_number: u32,
+    object: PhantomData<fn() -> *mut T>,
+}
+
+impl<T> Handle<T> {
+    /// # Safety
+    /// `object` must point to a live object of type `T`.
+    pub unsafe fn new(object: *mut T) -> Self {
+        let object = &*object.cast::<Object>();
+
+        Self {
+            index: object.index,
+            name_index: object.name.index,
+            name_number: object.name.number,
+            object: PhantomData,
+        }
+    }
+
+    /// The object, or `None` if it was destroyed. Only valid until the game
+    /// next collects garbage, so use it on the game thread and don't keep it.
+    pub unsafe fn get(&self) -> Option<*mut T> {
+        let object = *GLOBAL_OBJECTS.get().get(self.index as usize)?;
+
+        if object.is_null() {
+            return None;
+        }
+
+        let name = &(*object).name;
+
+        if name.index == self.name_index && name.number == self.name_number {
+            Some(object.cast())
+        } else {
+            None
+        }
+    }
+
+    pub unsafe fn is_valid(&self) -> bool {
+        self.get().is_some()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+            && self.name_index == other.name_index
+            && self.name_number == other.name_number
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("name_index", &self.name_index)
+            .field("name_number", &self.name_number)
+            .finish()
+    }
+}
@@ -0,0 +1,209 @@
+use crate::hook::contain::contain;
+use crate::hook::events;
+
+use std::convert::TryFrom;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::info;
+use thiserror::Error;
+use winapi::um::processthreadsapi::GetCurrentProcessId;
+use winapi::um::winuser::{
+    GetAsyncKeyState, GetForegroundWindow, GetWindowThreadProcessId, VK_BACK, VK_DELETE, VK_DOWN,
+    VK_END, VK_F1, VK_HOME, VK_INSERT, VK_LEFT, VK_NEXT, VK_NUMPAD0, VK_PAUSE, VK_PRIOR,
+    VK_RETURN, VK_RIGHT, VK_SPACE, VK_TAB, VK_UP,
+};
+
+// Bound on the game thread, rebound from the console thread.
+static HOTKEYS: Mutex<Vec<Hotkey>> = Mutex::new(Vec::new());
+
+const NAMED_KEYS: [(&str, i32); 15] = [
+    ("BACKSPACE", VK_BACK),
+    ("TAB", VK_TAB),
+    ("ENTER", VK_RETURN),
+    ("PAUSE", VK_PAUSE),
+    ("SPACE", VK_SPACE),
+    ("PAGEUP", VK_PRIOR),
+    ("PAGEDOWN", VK_NEXT),
+    ("END", VK_END),
+    ("HOME", VK_HOME),
+    ("LEFT", VK_LEFT),
+    ("UP", VK_UP),
+    ("RIGHT", VK_RIGHT),
+    ("DOWN", VK_DOWN),
+    ("INSERT", VK_INSERT),
+    ("DELETE", VK_DELETE),
+];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("no action named \"{0}\" is bound")]
+    UnknownAction(String),
+
+    #[error("unknown key \"{0}\"")]
+    UnknownKey(String),
+}
+
+struct Hotkey {
+    action: &'static str,
+    key: i32,
+    was_down: bool,
+
+    /// Taken out while it runs, so that it can bind, rebind, and show hotkeys.
+    callback: Option<Box<dyn FnMut() + Send>>,
+
+    /// Set once the callback crashes, so that it's never called again.
+    disabled: bool,
+}
+
+fn hotkeys() -> MutexGuard<'static, Vec<Hotkey>> {
+    HOTKEYS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Poll the hotkeys once per frame.
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| poll());
+}
+
+/// Call `callback` on the game thread whenever `key`, a virtual-key code, is
+/// pressed while the game has focus. `action` names the binding so that it
+/// can be changed later with `rebind`.
+pub fn bind(action: &'static str, key: i32, callback: impl FnMut() + Send + 'static) {
+    hotkeys().push(Hotkey {
+        action,
+        key,
+        was_down: false,
+        callback: Some(Box::new(callback)),
+        disabled: false,
+    });
+}
+
+/// Move every binding of `action` to `key`, a key name like "F5" or "END".
+pub fn rebind(action: &str, key: &str) -> Result<(), Error> {
+    let key = parse_key(key).ok_or_else(|| Error::UnknownKey(key.to_owned()))?;
+    let mut found = false;
+
+    for hotkey in hotkeys().iter_mut().filter(|h| h.action == action) {
+        hotkey.key = key;
+        hotkey.was_down = false;
+        found = true;
+    }
+
+    if found {
+        info!("Bound {} to {}.", action, key_name(key));
+        Ok(())
+    } else {
+        Err(Error::UnknownAction(action.to_owned()))
+    }
+}
+
+pub fn show() {
+    for hotkey in hotkeys().iter() {
+        info!("{:>10} -> {}", key_name(hotkey.key), hotkey.action);
+    }
+}
+
+/// Drop every binding.
+pub fn clear() {
+    hotkeys().clear();
+}
+
+/// Poll only the hotkeys bound to `action`, e.g., from another thread while
+/// the hook is paused and the per-frame poll doesn't run.
+pub unsafe fn poll_action(action: &str) {
+    poll_matching(|hotkey| hotkey.action == action);
+}
+
+/// Run the callback of each hotkey that went down since the last poll.
+unsafe fn poll() {
+    poll_matching(|_| true);
+}
+
+unsafe fn poll_matching(wanted: impl Fn(&Hotkey) -> bool) {
+    if !game_has_focus() {
+        return;
+    }
+
+    let mut due = Vec::new();
+
+    for (index, hotkey) in hotkeys().iter_mut().enumerate() {
+        if hotkey.disabled || !wanted(hotkey) {
+            continue;
+        }
+
+        let is_down = GetAsyncKeyState(hotkey.key) < 0;
+
+        if is_down && !hotkey.was_down {
+            if let Some(callback) = hotkey.callback.take() {
+                due.push((index, hotkey.action, callback));
+            }
+        }
+
+        hotkey.was_down = is_down;
+    }
+
+    for (index, action, mut callback) in due {
+        let crashed = contain(action, &mut callback).is_none();
+
+        // Bindings are only ever appended until `clear`.
+        if let Some(hotkey) = hotkeys().get_mut(index) {
+            hotkey.callback = Some(callback);
+            hotkey.disabled |= crashed;
+        }
+    }
+}
+
+/// Whether `key`, a virtual-key code, is held while the game has focus, for
+/// features that act for as long as a key is down rather than once per press.
+pub unsafe fn is_down(key: i32) -> bool {
+    game_has_focus() && GetAsyncKeyState(key) < 0
+}
+
+unsafe fn game_has_focus() -> bool {
+    let mut process = 0;
+    GetWindowThreadProcessId(GetForegroundWindow(), &mut process);
+    process == GetCurrentProcessId()
+}
+
+/// Parse a key name like "F5", "K", "7", "NUMPAD7", or "PAGEUP". Ignores case.
+pub fn parse_key(name: &str) -> Option<i32> {
+    let name = name.to_ascii_uppercase();
+
+    if let Some(&(_, key)) = NAMED_KEYS.iter().find(|(n, _)| *n == name) {
+        return Some(key);
+    }
+
+    if let Some(n) = name.strip_prefix("NUMPAD") {
+        return match n.parse::<i32>() {
+            Ok(n @ 0..=9) => Some(VK_NUMPAD0 + n),
+            _ => None,
+        };
+    }
+
+    if let Some(n) = name.strip_prefix('F') {
+        if let Ok(n @ 1..=24) = n.parse::<i32>() {
+            return Some(VK_F1 + n - 1);
+        }
+    }
+
+    match name.as_bytes() {
+        // Letters and digits are their own virtual-key codes.
+        &[c] if c.is_ascii_alphanumeric() => Some(i32::from(c)),
+        _ => None,
+    }
+}
+
+/// The inverse of `parse_key`.
+pub fn key_name(key: i32) -> String {
+    if let Some(&(name, _)) = NAMED_KEYS.iter().find(|(_, k)| *k == key) {
+        name.to_owned()
+    } else if (VK_F1..VK_F1 + 24).contains(&key) {
+        format!("F{}", key - VK_F1 + 1)
+    } else if (VK_NUMPAD0..VK_NUMPAD0 + 10).contains(&key) {
+        format!("NUMPAD{}", key - VK_NUMPAD0)
+    } else {
+        match u8::try_from(key) {
+            Ok(c) if c.is_ascii_alphanumeric() => char::from(c).to_string(),
+            _ => format!("0x{:02X}", key),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::game::Object;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::{info, warn};
+
+/// Relative to the game's working directory, Binaries/Win32.
+const LOCALIZATION: &str = "../../WillowGame/Localization";
+
+const DEFAULT_LANGUAGE: &str = "INT";
+
+static TABLE: Mutex<Option<Table>> = Mutex::new(None);
+
+/// Localized text by lowercase package, section, and key, as in the game's
+/// .int files, e.g., `[Name_Shotgun]` `ItemName="Shotgun"` in a package's file.
+struct Table {
+    language: String,
+    strings: HashMap<(String, String, String), String>,
+}
+
+fn table() -> MutexGuard<'static, Option<Table>> {
+    TABLE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Read every localization file of `language`, e.g., "INT" or "FRA",
+/// replacing any loaded before.
+pub fn load(language: &str) -> io::Result<()> {
+    let directory = Path::new(LOCALIZATION).join(language);
+    let extension = language.to_ascii_lowercase();
+    let mut strings = HashMap::new();
+
+    for entry in fs::read_dir(&directory)? {
+        let path = entry?.path();
+
+        if path.extension().map_or(true, |e| e != extension.as_str()) {
+            continue;
+        }
+
+        let package = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(package) => package.to_ascii_lowercase(),
+            None => continue,
+        };
+
+        match fs::read(&path) {
+            Ok(bytes) => parse(&package, &decode(&bytes), &mut strings),
+            Err(e) => warn!("Failed to read {}: {}", path.display(), e),
+        }
+    }
+
+    info!("Loaded {} {} localized strings.", strings.len(), language);
+
+    *table() = Some(Table {
+        language: language.to_owned(),
+        strings,
+    });
+
+    Ok(())
+}
+
+/// The localized text of `key` in `section` of `package`'s file, loading the
+/// default language first if none is loaded.
+pub fn localize(package: &str, section: &str, key: &str) -> Option<String> {
+    if table().is_none() {
+        if let Err(e) = load(DEFAULT_LANGUAGE) {
+            warn!("Failed to load the localization: {}", e);
+
+            // Don't read the directory again on every lookup.
+            *table() = Some(Table {
+                language: DEFAULT_LANGUAGE.to_owned(),
+                strings: HashMap::new(),
+            });
+        }
+    }
+
+    let key = (
+        package.to_ascii_lowercase(),
+        section.to_ascii_lowercase(),
+        key.to_ascii_lowercase(),
+    );
+
+    table().as_ref()?.strings.get(&key).cloned()
+}
+
+/// The localized text of `object`'s `property`, e.g., a weapon part's
+/// "PartName". The engine stores it in the file of the object's package, in
+/// the section named for the object's path inside the package.
+pub unsafe fn localize_property(object: &Object, property: &str) -> Option<String> {
+    let mut path: Vec<_> = object
+        .iter_outer()
+        .map(|o| o.name())
+        .collect::<Option<_>>()?;
+    let package = path.pop()?;
+    path.reverse();
+
+    localize(&package, &path.join("."), property)
+}
+
+/// The language of the loaded localization.
+pub fn language() -> Option<String> {
+    table().as_ref().map(|t| t.language.clone())
+}
+
+/// Text of a localization file, which is usually UTF-16 with a byte order mark.
+fn decode(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let wide: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+
+            String::from_utf16_lossy(&wide)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn parse(package: &str, text: &str, strings: &mut HashMap<(String, String, String), String>) {
+    let mut section = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].to_ascii_lowercase();
+            continue;
+        }
+
+        if let Some(equals) = line.find('=') {
+            let key = line[..equals].trim().to_ascii_lowercase();
+            let value = line[equals + 1..].trim();
+
+            let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                &value[1..value.len() - 1]
+            } else {
+                value
+            };
+
+            strings.insert((package.to_owned(), section.clone(), key), value.to_owned());
+        }
+    }
+}
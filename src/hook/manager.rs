@@ -0,0 +1,286 @@
+use std::ffi::c_void;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use detours_sys::{
+    DetourAttach, DetourDetach, DetourTransactionAbort, DetourTransactionBegin,
+    DetourTransactionCommit, DetourUpdateThread, LONG as DetourErrorCode,
+};
+use log::info;
+use thiserror::Error;
+use winapi::um::processthreadsapi::GetCurrentThread;
+
+const NO_ERROR: DetourErrorCode = 0;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("a hook named \"{0}\" is already registered")]
+    AlreadyRegistered(&'static str),
+
+    #[error("detour error: {0} returned {1}")]
+    Detour(&'static str, DetourErrorCode),
+
+    #[error("failed to {verb} {}", list_failures(.failures))]
+    Hooks {
+        verb: &'static str,
+        failures: Vec<(&'static str, DetourErrorCode)>,
+    },
+
+    #[error("no hook named \"{0}\" is registered")]
+    NotRegistered(String),
+}
+
+fn list_failures(failures: &[(&'static str, DetourErrorCode)]) -> String {
+    let mut list = String::new();
+
+    for (i, (name, error_code)) in failures.iter().enumerate() {
+        if i > 0 {
+            list.push_str(", ");
+        }
+
+        let _ = write!(list, "\"{}\" ({})", name, error_code);
+    }
+
+    list
+}
+
+/// A helper macro to call Detour functions and wrap any error codes into a
+/// variant of the top-level `Error` enum.
+macro_rules! det {
+    ($call:expr) => {{
+        let error_code = $call;
+
+        if error_code == NO_ERROR {
+            Ok(())
+        } else {
+            Err(Error::Detour(stringify!($call), error_code))
+        }
+    }};
+}
+
+struct Detour {
+    name: &'static str,
+
+    /// Points to the function to hook. While the hook is enabled, Detours
+    /// rewrites the pointee to a trampoline that calls the original function.
+    target: *mut *mut c_void,
+
+    detour: *mut c_void,
+    enabled: bool,
+
+    /// Incremented by the detour on every call.
+    calls: &'static AtomicUsize,
+
+    /// When the hook was last enabled, while it's enabled.
+    attached_at: Option<Instant>,
+
+    last_error: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Transaction {
+    Attach,
+    Detach,
+}
+
+impl Transaction {
+    fn verb(self) -> &'static str {
+        match self {
+            Self::Attach => "attach",
+            Self::Detach => "detach",
+        }
+    }
+}
+
+/// Owns any number of named detours. Enabling or disabling several hooks at
+/// once happens in a single Detours transaction, so either all of them change
+/// or none do.
+#[derive(Default)]
+pub struct HookManager {
+    detours: Vec<Detour>,
+}
+
+impl HookManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a disabled hook that redirects `*target` to `detour`.
+    ///
+    /// `target` must stay valid for as long as the hook is registered, so it's
+    /// usually a `Global` that the detour also reads to call the original.
+    /// `detour` should increment `calls` every time it runs, for `report`.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        target: *mut *mut c_void,
+        detour: *mut c_void,
+        calls: &'static AtomicUsize,
+    ) -> Result<(), Error> {
+        if self.detours.iter().any(|d| d.name == name) {
+            return Err(Error::AlreadyRegistered(name));
+        }
+
+        self.detours.push(Detour {
+            name,
+            target,
+            detour,
+            enabled: false,
+            calls,
+            attached_at: None,
+            last_error: None,
+        });
+
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.detours.iter().any(|d| d.name == name && d.enabled)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.detours.iter().map(|d| d.name)
+    }
+
+    /// Log each hook's state, calls, and last error, to see which hooks fire.
+    pub fn report(&self) {
+        info!(
+            "{:<16} {:<9} {:>12} {:>12}  last error",
+            "hook", "state", "attached (s)", "calls"
+        );
+
+        for detour in &self.detours {
+            let attached = detour.attached_at.map_or_else(
+                || String::from("-"),
+                |t| format!("{:.1}", t.elapsed().as_secs_f32()),
+            );
+
+            let state = if detour.enabled { "enabled" } else { "disabled" };
+
+            info!(
+                "{:<16} {:<9} {:>12} {:>12}  {}",
+                detour.name,
+                state,
+                attached,
+                detour.calls.load(Ordering::Relaxed),
+                detour.last_error.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
+    pub unsafe fn enable(&mut self, names: &[&str]) -> Result<(), Error> {
+        self.transact(names, Transaction::Attach)
+    }
+
+    pub unsafe fn disable(&mut self, names: &[&str]) -> Result<(), Error> {
+        self.transact(names, Transaction::Detach)
+    }
+
+    pub unsafe fn enable_all(&mut self) -> Result<(), Error> {
+        let names: Vec<&str> = self.names().collect();
+        self.enable(&names)
+    }
+
+    pub unsafe fn disable_all(&mut self) -> Result<(), Error> {
+        let names: Vec<&str> = self.names().collect();
+        self.disable(&names)
+    }
+
+    /// Disable and then forget the named hooks.
+    pub unsafe fn remove(&mut self, names: &[&str]) -> Result<(), Error> {
+        self.disable(names)?;
+        self.detours.retain(|d| !names.contains(&d.name));
+        Ok(())
+    }
+
+    pub unsafe fn remove_all(&mut self) -> Result<(), Error> {
+        self.disable_all()?;
+        self.detours.clear();
+        Ok(())
+    }
+
+    unsafe fn transact(&mut self, names: &[&str], transaction: Transaction) -> Result<(), Error> {
+        if let Some(unknown) = names.iter().find(|&&n| self.detours.iter().all(|d| d.name != n)) {
+            return Err(Error::NotRegistered(String::from(*unknown)));
+        }
+
+        let want_enabled = transaction == Transaction::Attach;
+
+        let pending: Vec<usize> = self
+            .detours
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| names.contains(&d.name) && d.enabled != want_enabled)
+            .map(|(i, _)| i)
+            .collect();
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.commit(&pending, transaction);
+
+        match &result {
+            Ok(()) => {
+                for &i in &pending {
+                    let detour = &mut self.detours[i];
+                    detour.enabled = want_enabled;
+                    detour.attached_at = want_enabled.then(Instant::now);
+                }
+            }
+
+            Err(Error::Hooks { failures, .. }) => {
+                for (name, error_code) in failures {
+                    if let Some(detour) = self.detours.iter_mut().find(|d| d.name == *name) {
+                        detour.last_error =
+                            Some(format!("{} returned {}", transaction.verb(), error_code));
+                    }
+                }
+            }
+
+            Err(e) => {
+                for &i in &pending {
+                    self.detours[i].last_error = Some(e.to_string());
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Attach or detach the `pending` detours in one transaction.
+    unsafe fn commit(&self, pending: &[usize], transaction: Transaction) -> Result<(), Error> {
+        det!(DetourTransactionBegin())?;
+
+        if let Err(e) = det!(DetourUpdateThread(GetCurrentThread())) {
+            DetourTransactionAbort();
+            return Err(e);
+        }
+
+        let mut failures = vec![];
+
+        for &i in pending {
+            let detour = &self.detours[i];
+
+            let error_code = match transaction {
+                Transaction::Attach => DetourAttach(detour.target, detour.detour),
+                Transaction::Detach => DetourDetach(detour.target, detour.detour),
+            };
+
+            if error_code != NO_ERROR {
+                failures.push((detour.name, error_code));
+            }
+        }
+
+        if !failures.is_empty() {
+            DetourTransactionAbort();
+            return Err(Error::Hooks {
+                verb: transaction.verb(),
+                failures,
+            });
+        }
+
+        det!(DetourTransactionCommit())
+    }
+}
@@ -1,88 +1,335 @@
 use crate::game;
 use crate::PROCESS_EVENT;
 
+use std::cell::Cell;
 use std::ffi::c_void;
 use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use detours_sys::{
-    DetourAttach, DetourDetach, DetourTransactionBegin, DetourTransactionCommit,
-    DetourUpdateThread, LONG as DetourErrorCode,
-};
-use log::error;
+use log::{error, info, warn, LevelFilter};
 use thiserror::Error;
-use winapi::um::processthreadsapi::GetCurrentThread;
-
-mod cached_function_indexes;
-use cached_function_indexes::CachedFunctionIndexes;
+use winapi::um::synchapi::Sleep;
+use winapi::um::winuser::{VK_END, VK_F1, VK_PAUSE};
 
 mod bitfield;
+
+pub mod bytecode;
+
+pub mod console;
+
+mod contain;
+
+pub mod events;
+use events::{Action, Context};
+
+pub mod exec;
+
+pub mod filter;
+
+pub mod gmalloc;
+
+mod handle;
+pub use handle::Handle;
+
+pub mod input;
+
+pub mod localization;
+
+mod manager;
+use manager::HookManager;
+
+pub mod overhead;
+
+pub mod overlay;
+
+pub mod passive;
+
+pub mod plugins;
+
+pub mod profiler;
+
+pub mod recorder;
+
+pub mod reflect;
+pub use reflect::{call_by_name as call, Value};
+
+pub mod render;
+
+pub mod scheduler;
+
+pub mod scripting;
+
 mod sdk;
 
+pub mod tracker;
+
 mod user;
 
-pub static mut CACHED_FUNCTION_INDEXES: Option<CachedFunctionIndexes> = None;
+mod watchdog;
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("cached function indexes error: {0}")]
-    CFI(#[from] cached_function_indexes::Error),
+    #[error("hook manager error: {0}")]
+    Manager(#[from] manager::Error),
 
-    #[error("detour error: {0} returned {1}")]
-    Detour(&'static str, DetourErrorCode),
+    #[error("{0}")]
+    Module(#[from] crate::module::Error),
 }
 
-/// A helper macro to call Detour functions and wrap any error codes into a
-/// variant of the top-level `Error` enum.
-macro_rules! det {
-    ($call:expr) => {{
-        const NO_ERROR: DetourErrorCode = 0;
+/// Set from any thread to ask the console loop to return and unhook.
+static UNLOAD: AtomicBool = AtomicBool::new(false);
 
-        let error_code = $call;
+pub fn request_unload() {
+    UNLOAD.store(true, Ordering::SeqCst);
+}
 
-        if error_code == NO_ERROR {
-            Ok(())
-        } else {
-            Err(Error::Detour(stringify!($call), error_code))
+pub fn unload_requested() -> bool {
+    UNLOAD.load(Ordering::SeqCst)
+}
+
+/// Set from any thread to ask the console loop to pause or resume the hook.
+static TOGGLE_PAUSE: AtomicBool = AtomicBool::new(false);
+
+pub fn request_toggle_pause() {
+    TOGGLE_PAUSE.store(true, Ordering::SeqCst);
+}
+
+fn take_toggle_pause() -> bool {
+    TOGGLE_PAUSE.swap(false, Ordering::SeqCst)
+}
+
+fn toggle_logging() {
+    if log::max_level() == LevelFilter::Off {
+        log::set_max_level(LevelFilter::Info);
+        info!("Enabled logging.");
+    } else {
+        info!("Disabled logging.");
+        log::set_max_level(LevelFilter::Off);
+    }
+}
+
+static PROCESS_EVENT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// How many calls are currently inside one of our detours, on any thread.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set when a detour couldn't be detached on unload, so the game can still
+/// call into this module.
+static STUCK: AtomicBool = AtomicBool::new(false);
+
+/// Whether the module can be freed, i.e., the hook didn't leave a detour
+/// attached.
+pub fn can_unload() -> bool {
+    !STUCK.load(Ordering::SeqCst)
+}
+
+/// How long to wait for in-flight calls to return before giving up.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Counts a call as in flight until dropped.
+struct InFlight;
+
+impl InFlight {
+    fn enter() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wait until no thread is inside one of our detours, so that their code and
+/// the state they use can be freed. Only meaningful once the hook is detached,
+/// since otherwise new calls keep arriving.
+unsafe fn drain() {
+    let start = Instant::now();
+
+    while IN_FLIGHT.load(Ordering::SeqCst) != 0 {
+        if start.elapsed() > DRAIN_TIMEOUT {
+            warn!(
+                "{} calls are still in flight after {:?}. Unloading anyway.",
+                IN_FLIGHT.load(Ordering::SeqCst),
+                DRAIN_TIMEOUT
+            );
+            return;
         }
-    }};
+
+        Sleep(1);
+    }
+
+    info!("Drained in-flight calls in {:?}.", start.elapsed());
 }
 
-pub struct Hook;
+thread_local! {
+    /// How many of our `ProcessEvent` dispatches are running on this thread.
+    static DISPATCH_DEPTH: Cell<u32> = Cell::new(0);
+}
+
+/// Marks the current thread as running our subscribers until dropped.
+struct Dispatching;
+
+impl Dispatching {
+    fn enter() -> Self {
+        DISPATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Self
+    }
+}
+
+impl Drop for Dispatching {
+    fn drop(&mut self) {
+        DISPATCH_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Whether the current thread is running one of our `ProcessEvent`
+/// subscribers. `ProcessEvent` calls made from a subscriber, e.g., through the
+/// generated SDK, go straight to the game without being dispatched again.
+pub fn is_dispatching() -> bool {
+    DISPATCH_DEPTH.with(|depth| depth.get() != 0)
+}
+
+pub struct Hook {
+    manager: HookManager,
+}
 
 impl Hook {
     pub unsafe fn new() -> Result<Hook, Error> {
-        CACHED_FUNCTION_INDEXES = Some(CachedFunctionIndexes::new()?);
-        hook_process_event()?;
-        Ok(Hook)
+        passive::load();
+
+        if let Err(e) = gmalloc::find() {
+            warn!("Arrays can't be resized: {}", e);
+        }
+
+        input::subscribe();
+        input::bind("unload", VK_END, request_unload);
+        input::bind("logging", VK_F1, toggle_logging);
+        input::bind("pause", VK_PAUSE, request_toggle_pause);
+        overlay::install();
+        watchdog::install();
+
+        scheduler::subscribe();
+        overhead::subscribe();
+        exec::subscribe();
+        tracker::subscribe();
+
+        // Scripts and plugins can call anything.
+        if !passive::is_on() {
+            plugins::subscribe();
+            scripting::subscribe();
+        }
+
+        user::subscribe();
+
+        // Loading a map replaces most objects.
+        events::on("Engine.GameInfo.InitGame", |_| {
+            game::Objects::invalidate_lookup();
+            reflect::invalidate_property_maps();
+        });
+
+        let mut manager = HookManager::new();
+        manager.register(
+            "ProcessEvent",
+            PROCESS_EVENT.as_mut_ptr(),
+            my_process_event as *mut _,
+            &PROCESS_EVENT_CALLS,
+        )?;
+
+        // Only the overlay and the render callbacks need the device.
+        match render::find() {
+            Ok(()) => {
+                manager.register(
+                    "EndScene",
+                    render::END_SCENE.as_mut_ptr(),
+                    render::my_end_scene as *mut _,
+                    &render::END_SCENE_CALLS,
+                )?;
+                manager.register(
+                    "Reset",
+                    render::RESET.as_mut_ptr(),
+                    render::my_reset as *mut _,
+                    &render::RESET_CALLS,
+                )?;
+            }
+            Err(e) => warn!(
+                "Failed to find the Direct3D device, so there's no overlay: {}",
+                e
+            ),
+        }
+
+        // Installed last, since only `Drop` uninstalls it.
+        contain::install()?;
+
+        if let Err(e) = manager.enable_all() {
+            contain::uninstall();
+            return Err(e.into());
+        }
+
+        Ok(Hook { manager })
+    }
+
+    /// Detach every detour, so that the game runs stock until `enable`.
+    /// Subscriptions, hotkeys, scripts, and plugins stay loaded.
+    pub unsafe fn disable(&mut self) -> Result<(), Error> {
+        overlay::hide();
+        self.manager.disable_all()?;
+        info!("Paused the hook.");
+        Ok(())
+    }
+
+    /// Reattach the detours detached by `disable`.
+    pub unsafe fn enable(&mut self) -> Result<(), Error> {
+        self.manager.enable_all()?;
+        info!("Resumed the hook.");
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.manager.is_enabled("ProcessEvent")
+    }
+
+    /// Log how often each detour has run.
+    pub fn report(&self) {
+        self.manager.report();
     }
 }
 
 impl Drop for Hook {
     fn drop(&mut self) {
         unsafe {
-            if let Err(e) = unhook_process_event() {
+            overlay::restore_wnd_proc();
+            watchdog::uninstall();
+            user::feature::clear();
+
+            // The game may still call an attached detour, so its state has to
+            // outlive us.
+            if let Err(e) = self.manager.remove_all() {
                 error!("{}", e);
+                STUCK.store(true, Ordering::SeqCst);
+                return;
             }
+
+            // Subscribers may still be running on the game thread.
+            drain();
+            bytecode::revert_all();
+            events::clear();
+            input::clear();
+            render::clear();
+            overlay::clear();
+            scheduler::clear();
+            exec::clear();
+            tracker::clear();
+            scripting::clear();
+            plugins::clear();
+            contain::uninstall();
         }
     }
 }
 
-unsafe fn hook_process_event() -> Result<(), Error> {
-    det!(DetourTransactionBegin())?;
-    det!(DetourUpdateThread(GetCurrentThread()))?;
-    det!(DetourAttach(&mut PROCESS_EVENT, my_process_event as *mut _))?;
-    det!(DetourTransactionCommit())?;
-    Ok(())
-}
-
-unsafe fn unhook_process_event() -> Result<(), Error> {
-    det!(DetourTransactionBegin())?;
-    det!(DetourUpdateThread(GetCurrentThread()))?;
-    det!(DetourDetach(&mut PROCESS_EVENT, my_process_event as *mut _))?;
-    det!(DetourTransactionCommit())?;
-    Ok(())
-}
-
 unsafe extern "fastcall" fn my_process_event(
     this: *mut game::Object,
     edx: usize,
@@ -98,7 +345,70 @@ unsafe extern "fastcall" fn my_process_event(
         return_value: *mut c_void,
     );
 
-    let original = mem::transmute::<*mut c_void, ProcessEvent>(PROCESS_EVENT);
-    user::process_event(this, function, parameters, return_value);
-    original(this, edx, function, parameters, return_value);
+    let _in_flight = InFlight::enter();
+    PROCESS_EVENT_CALLS.fetch_add(1, Ordering::Relaxed);
+
+    let original = mem::transmute::<*mut c_void, ProcessEvent>(PROCESS_EVENT.ptr());
+
+    if is_dispatching() {
+        original(this, edx, function, parameters, return_value);
+        return;
+    }
+
+    let mut measurement = overhead::Measurement::begin();
+
+    let mut context = Context {
+        this,
+        function,
+        parameters,
+        return_value,
+    };
+
+    let action = {
+        let _dispatching = Dispatching::enter();
+
+        if recorder::is_recording() {
+            recorder::record(&context);
+        }
+
+        scripting::dispatch(&context);
+
+        let plugins = plugins::dispatch(&mut context);
+
+        let exec = if (*function).is_exec() {
+            exec::dispatch(&mut context)
+        } else {
+            Action::Continue
+        };
+
+        if events::dispatch_before(&mut context) == Action::Continue && exec == Action::Continue {
+            plugins
+        } else {
+            Action::Block
+        }
+    };
+
+    if action == Action::Block {
+        return;
+    }
+
+    // Not dispatching, since the game's own nested calls should reach us.
+    if profiler::is_profiling() || measurement.is_some() {
+        let start = Instant::now();
+        original(this, edx, function, parameters, return_value);
+        let elapsed = start.elapsed();
+
+        if profiler::is_profiling() {
+            profiler::add(function, elapsed);
+        }
+
+        if let Some(measurement) = measurement.as_mut() {
+            measurement.add_original(elapsed);
+        }
+    } else {
+        original(this, edx, function, parameters, return_value);
+    }
+
+    let _dispatching = Dispatching::enter();
+    events::dispatch_after(&mut context);
 }
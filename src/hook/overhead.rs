@@ -0,0 +1,173 @@
+use crate::hook::events;
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::info;
+
+/// How many frames the rolling average covers.
+const WINDOW_FRAMES: usize = 120;
+
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+static MEASURING: AtomicBool = AtomicBool::new(false);
+
+// Nanoseconds spent this frame in our ProcessEvent code and in the original.
+static OVERHEAD_NS: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_NS: AtomicU64 = AtomicU64::new(0);
+
+static FRAMES: Mutex<Frames> = Mutex::new(Frames {
+    samples: VecDeque::new(),
+    last_log: None,
+});
+
+thread_local! {
+    /// How many measured ProcessEvent calls are running on this thread.
+    static DEPTH: Cell<u32> = Cell::new(0);
+}
+
+struct Frames {
+    /// The overhead and original time of each recent frame.
+    samples: VecDeque<(Duration, Duration)>,
+    last_log: Option<Instant>,
+}
+
+fn frames() -> MutexGuard<'static, Frames> {
+    FRAMES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Times one call of our ProcessEvent detour until dropped.
+pub struct Measurement {
+    start: Instant,
+    original: Duration,
+    top_level: bool,
+}
+
+impl Measurement {
+    /// Start timing the current call, if measuring.
+    pub fn begin() -> Option<Self> {
+        if !is_measuring() {
+            return None;
+        }
+
+        let top_level = DEPTH.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get() == 1
+        });
+
+        Some(Self {
+            start: Instant::now(),
+            original: Duration::default(),
+            top_level,
+        })
+    }
+
+    /// Count `elapsed` as spent in the original ProcessEvent rather than ours.
+    pub fn add_original(&mut self, elapsed: Duration) {
+        self.original += elapsed;
+    }
+}
+
+impl Drop for Measurement {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+
+        // Nested calls measure their own overhead, which the original's time
+        // already includes, so only the outermost original counts.
+        let overhead = self.start.elapsed().saturating_sub(self.original);
+        OVERHEAD_NS.fetch_add(nanos(overhead), Ordering::Relaxed);
+
+        if self.top_level {
+            ORIGINAL_NS.fetch_add(nanos(self.original), Ordering::Relaxed);
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn nanos(duration: Duration) -> u64 {
+    duration.as_nanos() as u64
+}
+
+/// Close each frame's measurements on the game thread.
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| {
+        if is_measuring() {
+            end_frame();
+        }
+    });
+}
+
+/// Log a rolling average of the time per frame spent in our ProcessEvent hook,
+/// including subscribers, and in the original ProcessEvent.
+pub fn start() {
+    if !MEASURING.swap(true, Ordering::SeqCst) {
+        info!("Started measuring the ProcessEvent hook's overhead.");
+    }
+}
+
+pub fn stop() {
+    if MEASURING.swap(false, Ordering::SeqCst) {
+        let mut frames = frames();
+        frames.samples.clear();
+        frames.last_log = None;
+        info!("Stopped measuring the ProcessEvent hook's overhead.");
+    }
+}
+
+pub fn is_measuring() -> bool {
+    MEASURING.load(Ordering::Relaxed)
+}
+
+fn end_frame() {
+    let overhead = Duration::from_nanos(OVERHEAD_NS.swap(0, Ordering::Relaxed));
+    let original = Duration::from_nanos(ORIGINAL_NS.swap(0, Ordering::Relaxed));
+
+    let mut frames = frames();
+
+    if frames.samples.len() == WINDOW_FRAMES {
+        frames.samples.pop_front();
+    }
+
+    frames.samples.push_back((overhead, original));
+
+    let now = Instant::now();
+
+    if frames.last_log.map_or(false, |t| now - t < LOG_INTERVAL) {
+        return;
+    }
+
+    if frames.last_log.is_some() {
+        log(&frames.samples);
+    }
+
+    frames.last_log = Some(now);
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn log(samples: &VecDeque<(Duration, Duration)>) {
+    let count = samples.len() as u32;
+
+    if count == 0 {
+        return;
+    }
+
+    let overhead: Duration = samples.iter().map(|s| s.0).sum::<Duration>() / count;
+    let original: Duration = samples.iter().map(|s| s.1).sum::<Duration>() / count;
+
+    let percent = if original.as_nanos() == 0 {
+        0.0
+    } else {
+        overhead.as_secs_f64() / original.as_secs_f64() * 100.0
+    };
+
+    info!(
+        "Hook overhead: {:.1} us/frame, original: {:.1} us/frame ({:.1}%), {} frames.",
+        overhead.as_secs_f64() * 1e6,
+        original.as_secs_f64() * 1e6,
+        percent,
+        count
+    );
+}
@@ -0,0 +1,342 @@
+use crate::global::Global;
+use crate::hook::contain::contain;
+use crate::hook::{input, render, InFlight};
+
+use std::convert::TryFrom;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use imgui::{Context, Key, Ui};
+use imgui_dx9_renderer::Renderer;
+use log::{error, info};
+use winapi::shared::d3d9::IDirect3DDevice9;
+use winapi::shared::d3d9types::D3DDEVICE_CREATION_PARAMETERS;
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::{HWND, HWND__, RECT};
+use winapi::shared::windowsx::{GET_X_LPARAM, GET_Y_LPARAM};
+use winapi::um::winuser::{
+    CallWindowProcW, GetClientRect, SetWindowLongPtrW, GET_WHEEL_DELTA_WPARAM, GWLP_WNDPROC,
+    VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_INSERT, VK_LEFT,
+    VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+    WHEEL_DELTA, WM_CHAR, WM_KEYDOWN, WM_KEYFIRST, WM_KEYLAST, WM_KEYUP, WM_LBUTTONDOWN,
+    WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEFIRST, WM_MOUSELAST, WM_MOUSEMOVE,
+    WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
+};
+
+/// Whether the overlay is drawn and takes input.
+static VISIBLE: AtomicBool = AtomicBool::new(false);
+
+// Copied from imgui every frame, so that the window procedure, which runs on
+// another thread, knows whether to keep input from the game.
+static CAPTURE_MOUSE: AtomicBool = AtomicBool::new(false);
+static CAPTURE_KEYBOARD: AtomicBool = AtomicBool::new(false);
+
+// Only touched on the render thread, except for `clear` once the hooks are gone.
+static OVERLAY: Mutex<Option<RenderThreadOnly<Overlay>>> = Mutex::new(None);
+
+// Filled in by the window procedure and drained into imgui every frame.
+static INPUT: Mutex<Input> = Mutex::new(Input {
+    mouse_position: [0.0; 2],
+    mouse_down: [false; 5],
+    wheel: 0.0,
+    characters: Vec::new(),
+    keys_down: [false; 256],
+});
+
+/// The windows to build and whether each crashed.
+static WINDOWS: Mutex<Vec<(Window, bool)>> = Mutex::new(Vec::new());
+
+// The game's window and its window procedure, which we forward messages to.
+static WINDOW: Global<HWND__> = Global::null();
+static ORIGINAL_WND_PROC: AtomicI32 = AtomicI32::new(0);
+
+/// Set by `restore_wnd_proc`, so that `create` doesn't subclass the window
+/// again before the hooks are gone. Held while subclassing, so that the two
+/// can't interleave.
+static CLOSING: Mutex<bool> = Mutex::new(false);
+
+type Window = Box<dyn FnMut(&Ui) + Send>;
+
+/// imgui's context and renderer aren't `Send`, but we only use them on the
+/// render thread.
+struct RenderThreadOnly<T>(T);
+
+unsafe impl<T> Send for RenderThreadOnly<T> {}
+
+struct Overlay {
+    context: Context,
+
+    /// Dropped when the device is lost and recreated on the next frame.
+    renderer: Option<Renderer>,
+
+    window: HWND,
+    last_frame: Instant,
+}
+
+struct Input {
+    mouse_position: [f32; 2],
+    mouse_down: [bool; 5],
+    wheel: f32,
+    characters: Vec<char>,
+    keys_down: [bool; 256],
+}
+
+fn overlay() -> MutexGuard<'static, Option<RenderThreadOnly<Overlay>>> {
+    OVERLAY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn input() -> MutexGuard<'static, Input> {
+    INPUT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn windows() -> MutexGuard<'static, Vec<(Window, bool)>> {
+    WINDOWS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Build imgui windows every frame while the overlay is visible, e.g.,
+/// `overlay::on_draw(|ui| Window::new(im_str!("Cheats")).build(ui, || ...))`.
+/// Runs on the render thread.
+pub fn on_draw(callback: impl FnMut(&Ui) + Send + 'static) {
+    windows().push((Box::new(callback), false));
+}
+
+/// Draw the overlay on top of every frame, and let INSERT show or hide it.
+pub fn install() {
+    render::on_frame(|device| unsafe { frame(device) });
+
+    render::on_lost(|_| {
+        if let Some(overlay) = overlay().as_mut() {
+            overlay.0.renderer = None;
+        }
+    });
+
+    input::bind("overlay", VK_INSERT, || {
+        let visible = !VISIBLE.load(Ordering::SeqCst);
+        VISIBLE.store(visible, Ordering::SeqCst);
+        info!("{} the overlay.", if visible { "Showing" } else { "Hiding" });
+    });
+}
+
+/// Stop drawing the overlay and taking input.
+pub fn hide() {
+    VISIBLE.store(false, Ordering::SeqCst);
+}
+
+/// Give the game back its window procedure. Call before draining in-flight
+/// calls, since the window procedure is one of them.
+pub unsafe fn restore_wnd_proc() {
+    let mut closing = CLOSING.lock().unwrap_or_else(PoisonError::into_inner);
+    *closing = true;
+
+    if !WINDOW.is_null() {
+        SetWindowLongPtrW(WINDOW.ptr(), GWLP_WNDPROC, ORIGINAL_WND_PROC.load(Ordering::SeqCst));
+        WINDOW.set(ptr::null_mut());
+    }
+}
+
+/// Drop the imgui context, the renderer, and the windows.
+pub fn clear() {
+    *overlay() = None;
+    windows().clear();
+}
+
+unsafe fn frame(device: *mut IDirect3DDevice9) {
+    if !VISIBLE.load(Ordering::SeqCst) {
+        return;
+    }
+
+    // Taken out while drawing, so that the windows don't run under its lock.
+    let mut state = overlay().take();
+
+    if state.is_none() {
+        state = create(device).map(RenderThreadOnly);
+    }
+
+    if let Some(state) = state.as_mut() {
+        draw(&mut state.0, device);
+    }
+
+    *overlay() = state;
+}
+
+unsafe fn draw(overlay: &mut Overlay, device: *mut IDirect3DDevice9) {
+    if overlay.renderer.is_none() {
+        match Renderer::new_raw(&mut overlay.context, device) {
+            Ok(renderer) => overlay.renderer = Some(renderer),
+            Err(e) => {
+                error!("Failed to create the overlay renderer: {:?}", e);
+                VISIBLE.store(false, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+
+    update_io(overlay);
+
+    let ui = overlay.context.frame();
+
+    // Taken out while they run, so that they can add windows.
+    let mut running = mem::take(&mut *windows());
+
+    for (window, crashed) in running.iter_mut().filter(|(_, crashed)| !crashed) {
+        if contain("an overlay window", || window(&ui)).is_none() {
+            *crashed = true;
+        }
+    }
+
+    let mut added = windows();
+    running.append(&mut added);
+    *added = running;
+
+    let draw_data = ui.render();
+
+    if let Some(renderer) = overlay.renderer.as_mut() {
+        if let Err(e) = renderer.render(draw_data) {
+            error!("Failed to render the overlay: {:?}", e);
+        }
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+unsafe fn create(device: *mut IDirect3DDevice9) -> Option<Overlay> {
+    let closing = CLOSING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if *closing {
+        return None;
+    }
+
+    let mut parameters: D3DDEVICE_CREATION_PARAMETERS = mem::zeroed();
+    (*device).GetCreationParameters(&mut parameters);
+    let window = parameters.hFocusWindow;
+
+    let mut context = Context::create();
+    context.set_ini_filename(None);
+
+    let io = context.io_mut();
+
+    for &(key, vk) in &[
+        (Key::Tab, VK_TAB),
+        (Key::LeftArrow, VK_LEFT),
+        (Key::RightArrow, VK_RIGHT),
+        (Key::UpArrow, VK_UP),
+        (Key::DownArrow, VK_DOWN),
+        (Key::PageUp, VK_PRIOR),
+        (Key::PageDown, VK_NEXT),
+        (Key::Home, VK_HOME),
+        (Key::End, VK_END),
+        (Key::Insert, VK_INSERT),
+        (Key::Delete, VK_DELETE),
+        (Key::Backspace, VK_BACK),
+        (Key::Space, VK_SPACE),
+        (Key::Enter, VK_RETURN),
+        (Key::Escape, VK_ESCAPE),
+    ] {
+        io[key] = vk as u32;
+    }
+
+    // A `LONG` on x86, which is the only target, so the address fits.
+    #[allow(clippy::cast_possible_wrap)]
+    let original = SetWindowLongPtrW(window, GWLP_WNDPROC, my_wnd_proc as usize as i32);
+    ORIGINAL_WND_PROC.store(original, Ordering::SeqCst);
+    WINDOW.set(window);
+
+    Some(Overlay {
+        context,
+        renderer: None,
+        window,
+        last_frame: Instant::now(),
+    })
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+unsafe fn update_io(overlay: &mut Overlay) {
+    let mut client: RECT = mem::zeroed();
+    GetClientRect(overlay.window, &mut client);
+
+    let now = Instant::now();
+    let io = overlay.context.io_mut();
+
+    io.display_size = [
+        (client.right - client.left) as f32,
+        (client.bottom - client.top) as f32,
+    ];
+
+    io.delta_time = now.duration_since(overlay.last_frame).as_secs_f32().max(1e-5);
+    overlay.last_frame = now;
+
+    let mut input = input();
+    io.mouse_pos = input.mouse_position;
+    io.mouse_down = input.mouse_down;
+    io.mouse_wheel = mem::take(&mut input.wheel);
+
+    for c in input.characters.drain(..) {
+        io.add_input_character(c);
+    }
+
+    io.keys_down[..256].copy_from_slice(&input.keys_down);
+    io.key_ctrl = input.keys_down[VK_CONTROL as usize];
+    io.key_shift = input.keys_down[VK_SHIFT as usize];
+    io.key_alt = input.keys_down[VK_MENU as usize];
+    io.mouse_draw_cursor = true;
+
+    CAPTURE_MOUSE.store(io.want_capture_mouse, Ordering::SeqCst);
+    CAPTURE_KEYBOARD.store(io.want_capture_keyboard, Ordering::SeqCst);
+}
+
+/// Record the input imgui needs, and hide it from the game while imgui wants
+/// it. The game may still see the mouse through raw input.
+unsafe extern "system" fn my_wnd_proc(
+    window: HWND,
+    message: UINT,
+    w: WPARAM,
+    l: LPARAM,
+) -> LRESULT {
+    let _in_flight = InFlight::enter();
+
+    if VISIBLE.load(Ordering::SeqCst) {
+        record(message, w, l);
+
+        let is_mouse = (WM_MOUSEFIRST..=WM_MOUSELAST).contains(&message);
+        let is_keyboard = (WM_KEYFIRST..=WM_KEYLAST).contains(&message);
+
+        if is_mouse && CAPTURE_MOUSE.load(Ordering::SeqCst)
+            || is_keyboard && CAPTURE_KEYBOARD.load(Ordering::SeqCst)
+        {
+            return 0;
+        }
+    }
+
+    let original = ORIGINAL_WND_PROC.load(Ordering::SeqCst);
+    CallWindowProcW(mem::transmute(original), window, message, w, l)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn record(message: UINT, w: WPARAM, l: LPARAM) {
+    let mut input = input();
+
+    match message {
+        WM_MOUSEMOVE => {
+            input.mouse_position = [GET_X_LPARAM(l) as f32, GET_Y_LPARAM(l) as f32];
+        }
+        WM_LBUTTONDOWN | WM_LBUTTONUP => input.mouse_down[0] = message == WM_LBUTTONDOWN,
+        WM_RBUTTONDOWN | WM_RBUTTONUP => input.mouse_down[1] = message == WM_RBUTTONDOWN,
+        WM_MBUTTONDOWN | WM_MBUTTONUP => input.mouse_down[2] = message == WM_MBUTTONDOWN,
+        WM_MOUSEWHEEL => {
+            input.wheel += f32::from(GET_WHEEL_DELTA_WPARAM(w)) / f32::from(WHEEL_DELTA);
+        }
+        WM_KEYDOWN | WM_SYSKEYDOWN | WM_KEYUP | WM_SYSKEYUP => {
+            if let Some(down) = input.keys_down.get_mut(w) {
+                *down = message == WM_KEYDOWN || message == WM_SYSKEYDOWN;
+            }
+        }
+        WM_CHAR => {
+            if let Some(c) = u32::try_from(w).ok().and_then(char::from_u32) {
+                input.characters.push(c);
+            }
+        }
+        _ => {}
+    }
+}
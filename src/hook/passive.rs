@@ -0,0 +1,46 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::{info, warn};
+
+/// Start in passive mode if a file with this name is next to the game, e.g.,
+/// an empty "passive" in Binaries/Win32.
+const CONFIG: &str = "passive";
+
+static PASSIVE: AtomicBool = AtomicBool::new(false);
+
+/// Read the configuration. Call before anything subscribes, since passive
+/// mode only holds if it's set from the start.
+pub fn load() {
+    let passive = Path::new(CONFIG).exists();
+    PASSIVE.store(passive, Ordering::SeqCst);
+
+    if passive {
+        info!(
+            "Passive mode: only read-only features run, so nothing changes the game or its saves."
+        );
+    }
+}
+
+/// Whether features that change the game's state are disabled, leaving the
+/// read-only ones like dumping, logging, and overlays that don't reveal
+/// anything.
+pub fn is_on() -> bool {
+    PASSIVE.load(Ordering::SeqCst)
+}
+
+/// Check passive mode before `feature` changes the game. Returns true, and
+/// says so, if it mustn't.
+pub fn refuses(feature: &str) -> bool {
+    let refuses = is_on();
+
+    if refuses {
+        warn!("{} is disabled in passive mode.", feature);
+    }
+
+    refuses
+}
+
+pub fn show() {
+    info!("Passive mode is {}.", if is_on() { "on" } else { "off" });
+}
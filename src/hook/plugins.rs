@@ -0,0 +1,377 @@
+use crate::hook::contain::contain;
+use crate::hook::events::{self, Action, Context};
+use crate::hook::reflect;
+use crate::{GLOBAL_NAMES, GLOBAL_OBJECTS};
+
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, OsStr};
+use std::fs;
+use std::io;
+use std::iter;
+use std::mem;
+use std::os::raw::c_char;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use log::{error, info, warn};
+use winapi::shared::minwindef::HMODULE;
+use winapi::um::libloaderapi::{FreeLibrary, GetProcAddress, LoadLibraryW};
+
+const PLUGINS: &str = "plugins";
+
+/// How often to look for rebuilt plugins.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Only used on the game thread. As with scripts, the lock also keeps events
+// raised by plugin callbacks from reaching plugins again.
+static PLUGINS_STATE: Mutex<Option<Plugins>> = Mutex::new(None);
+
+// Filled by `abi::Api::subscribe` while a plugin loads.
+static PENDING: Mutex<Vec<(String, Subscription)>> = Mutex::new(Vec::new());
+
+static RELOAD: AtomicBool = AtomicBool::new(false);
+
+/// Makes each loaded copy's file name unique, since Windows may not let us
+/// delete the previous copy right away.
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+/// The interface between blps and plugin DLLs. Plugins export
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub unsafe extern "C" fn blps_plugin_load(api: *const Api) -> bool
+///
+/// #[no_mangle]
+/// pub unsafe extern "C" fn blps_plugin_unload()
+/// ```
+///
+/// and copy these definitions. `api` is only valid during the call, so copy out
+/// what you need. Only add fields to the end of `Api`, and bump `VERSION` when
+/// doing so.
+pub mod abi {
+    use crate::game::{Function, Names, Object, Objects};
+
+    use std::ffi::c_void;
+    use std::os::raw::c_char;
+
+    pub const VERSION: u32 = 1;
+
+    /// Called before the original `ProcessEvent`. Return true to block it.
+    pub type Callback = unsafe extern "C" fn(
+        user_data: *mut c_void,
+        this: *mut Object,
+        function: *mut Function,
+        parameters: *mut c_void,
+    ) -> bool;
+
+    pub type Load = unsafe extern "C" fn(api: *const Api) -> bool;
+    pub type Unload = unsafe extern "C" fn();
+
+    #[repr(C)]
+    pub struct Api {
+        pub version: u32,
+        pub global_names: *const Names,
+        pub global_objects: *const Objects,
+
+        /// Log a NUL-terminated UTF-8 message.
+        pub log: unsafe extern "C" fn(message: *const c_char),
+
+        /// Call `callback` whenever `ProcessEvent` runs the UFunction with the
+        /// NUL-terminated name `function`, e.g.,
+        /// "WillowGame.WillowPlayerController.PlayerTick". Only valid during
+        /// `blps_plugin_load`. Subscriptions end when the plugin unloads.
+        pub subscribe: unsafe extern "C" fn(
+            function: *const c_char,
+            callback: Callback,
+            user_data: *mut c_void,
+        ),
+    }
+}
+
+struct Subscription {
+    callback: abi::Callback,
+    user_data: *mut c_void,
+}
+
+// Plugins only run on the game thread.
+unsafe impl Send for Subscription {}
+
+struct Plugin {
+    name: String,
+    source: PathBuf,
+    modified: Option<SystemTime>,
+    copy: PathBuf,
+    library: HMODULE,
+}
+
+// Plugins only run on the game thread.
+unsafe impl Send for Plugin {}
+
+#[derive(Default)]
+struct Plugins {
+    plugins: Vec<Plugin>,
+
+    /// Maps a UFunction's object index to the plugin callbacks handling it.
+    subscriptions: HashMap<u32, Vec<Subscription>>,
+
+    last_check: Option<Instant>,
+}
+
+fn state() -> MutexGuard<'static, Option<Plugins>> {
+    PLUGINS_STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn pending() -> MutexGuard<'static, Vec<(String, Subscription)>> {
+    PENDING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Unload and load every plugin on the next frame.
+pub fn request_reload() {
+    RELOAD.store(true, Ordering::SeqCst);
+}
+
+/// Load the plugins on the game thread once it's running, and reload them
+/// whenever one is rebuilt.
+pub unsafe fn subscribe() {
+    request_reload();
+
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| unsafe {
+        let mut state = state();
+
+        let rebuilt = state.as_mut().map_or(false, Plugins::any_rebuilt);
+
+        if RELOAD.swap(false, Ordering::SeqCst) || rebuilt {
+            if let Some(mut plugins) = state.take() {
+                plugins.unload();
+            }
+
+            match Plugins::load(Path::new(PLUGINS)) {
+                Ok(plugins) => *state = Some(plugins),
+                Err(e) => error!("Failed to load plugins from \"{}\": {}", PLUGINS, e),
+            }
+        }
+    });
+}
+
+/// Unload every plugin.
+pub unsafe fn clear() {
+    if let Some(mut plugins) = state().take() {
+        plugins.unload();
+    }
+}
+
+/// Run the plugin callbacks of the call described by `context`.
+pub unsafe fn dispatch(context: &mut Context) -> Action {
+    let mut state = match PLUGINS_STATE.try_lock() {
+        Ok(state) => state,
+        Err(_) => return Action::Continue,
+    };
+
+    let subscriptions = state
+        .as_ref()
+        .and_then(|p| p.subscriptions.get(&(*context.function).index));
+
+    let dispatched = contain("plugin dispatch", || {
+        let mut action = Action::Continue;
+
+        for subscription in subscriptions.into_iter().flatten() {
+            let block = (subscription.callback)(
+                subscription.user_data,
+                context.this,
+                context.function,
+                context.parameters,
+            );
+
+            if block {
+                action = Action::Block;
+            }
+        }
+
+        action
+    });
+
+    match dispatched {
+        Some(action) => action,
+        None => {
+            if let Some(mut plugins) = state.take() {
+                plugins.unload();
+            }
+
+            Action::Continue
+        }
+    }
+}
+
+impl Plugins {
+    unsafe fn load(directory: &Path) -> io::Result<Self> {
+        let mut plugins = Self::default();
+
+        let mut paths: Vec<_> = fs::read_dir(directory)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |e| e == "dll"))
+            .collect();
+
+        paths.sort();
+
+        for path in paths {
+            match Plugin::load(path) {
+                Ok(plugin) => {
+                    for (function, subscription) in pending().drain(..) {
+                        plugins.subscribe(&plugin.name, &function, subscription);
+                    }
+
+                    info!("Loaded plugin {}.", plugin.name);
+                    plugins.plugins.push(plugin);
+                }
+
+                Err(e) => {
+                    pending().clear();
+                    error!("{}", e);
+                }
+            }
+        }
+
+        Ok(plugins)
+    }
+
+    unsafe fn subscribe(&mut self, plugin: &str, function: &str, subscription: Subscription) {
+        let full_name = reflect::function_full_name(function);
+
+        match GLOBAL_OBJECTS.get().find(&full_name) {
+            Some(function) => self
+                .subscriptions
+                .entry((*function).index)
+                .or_default()
+                .push(subscription),
+
+            None => warn!("{}: unable to find \"{}\".", plugin, full_name),
+        }
+    }
+
+    fn any_rebuilt(&mut self) -> bool {
+        let now = Instant::now();
+
+        if self.last_check.map_or(false, |t| now - t < CHECK_INTERVAL) {
+            return false;
+        }
+
+        self.last_check = Some(now);
+        self.plugins.iter().any(|p| modified(&p.source) != p.modified)
+    }
+
+    unsafe fn unload(&mut self) {
+        self.subscriptions.clear();
+
+        for plugin in self.plugins.drain(..) {
+            plugin.unload();
+        }
+    }
+}
+
+impl Plugin {
+    /// Load a copy of the DLL at `source`, so that the original can be rebuilt
+    /// while the game runs.
+    unsafe fn load(source: PathBuf) -> Result<Self, String> {
+        let name = source
+            .file_stem()
+            .map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+
+        let generation = GENERATION.fetch_add(1, Ordering::SeqCst);
+        let copy = std::env::temp_dir().join(format!("blps-{}-{}.dll", name, generation));
+        let modified = modified(&source);
+
+        fs::copy(&source, &copy).map_err(|e| format!("{}: failed to copy: {}", name, e))?;
+
+        let library = LoadLibraryW(wide(copy.as_os_str()).as_ptr());
+
+        if library.is_null() {
+            let _ = fs::remove_file(&copy);
+            return Err(format!("{}: LoadLibraryW failed", name));
+        }
+
+        let plugin = Self {
+            name,
+            source,
+            modified,
+            copy,
+            library,
+        };
+
+        let load = GetProcAddress(library, "blps_plugin_load\0".as_ptr().cast());
+
+        if load.is_null() {
+            let error = format!("{}: missing blps_plugin_load", plugin.name);
+            plugin.free();
+            return Err(error);
+        }
+
+        let load: abi::Load = mem::transmute(load);
+
+        if !load(&api()) {
+            let error = format!("{}: blps_plugin_load failed", plugin.name);
+            plugin.free();
+            return Err(error);
+        }
+
+        Ok(plugin)
+    }
+
+    unsafe fn unload(self) {
+        let unload = GetProcAddress(self.library, "blps_plugin_unload\0".as_ptr().cast());
+
+        if !unload.is_null() {
+            let unload: abi::Unload = mem::transmute(unload);
+            unload();
+        }
+
+        self.free();
+    }
+
+    unsafe fn free(self) {
+        FreeLibrary(self.library);
+
+        if let Err(e) = fs::remove_file(&self.copy) {
+            warn!("Failed to delete {}: {}", self.copy.display(), e);
+        }
+    }
+}
+
+fn api() -> abi::Api {
+    abi::Api {
+        version: abi::VERSION,
+        global_names: GLOBAL_NAMES.ptr(),
+        global_objects: GLOBAL_OBJECTS.ptr(),
+        log: api_log,
+        subscribe: api_subscribe,
+    }
+}
+
+unsafe extern "C" fn api_log(message: *const c_char) {
+    if !message.is_null() {
+        info!("{}", CStr::from_ptr(message).to_string_lossy());
+    }
+}
+
+unsafe extern "C" fn api_subscribe(
+    function: *const c_char,
+    callback: abi::Callback,
+    user_data: *mut c_void,
+) {
+    if function.is_null() {
+        return;
+    }
+
+    let function = CStr::from_ptr(function).to_string_lossy().into_owned();
+    pending().push((function, Subscription { callback, user_data }));
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(iter::once(0)).collect()
+}
@@ -0,0 +1,116 @@
+use crate::game::Function;
+use crate::GLOBAL_OBJECTS;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use log::info;
+
+// Started and reported from the console thread, fed from the game thread.
+static PROFILER: Mutex<Option<Profiler>> = Mutex::new(None);
+
+/// Whether `PROFILER` is `Some`, so that `ProcessEvent` can check without
+/// taking the lock.
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+const REPORT: &str = "profile.txt";
+
+#[derive(Default)]
+struct Stats {
+    calls: u64,
+
+    /// Time spent in the original `ProcessEvent`, including nested calls.
+    total: Duration,
+}
+
+/// Maps a UFunction's object index to how often and how long it ran.
+#[derive(Default)]
+struct Profiler {
+    stats: HashMap<u32, Stats>,
+}
+
+fn profiler() -> MutexGuard<'static, Option<Profiler>> {
+    PROFILER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn start() {
+    let mut profiler = profiler();
+
+    if profiler.is_none() {
+        *profiler = Some(Profiler::default());
+        PROFILING.store(true, Ordering::Relaxed);
+        info!("Started profiling events.");
+    }
+}
+
+pub fn stop() {
+    let mut profiler = profiler();
+    PROFILING.store(false, Ordering::Relaxed);
+
+    if profiler.take().is_some() {
+        info!("Stopped profiling events.");
+    }
+}
+
+pub fn is_profiling() -> bool {
+    PROFILING.load(Ordering::Relaxed)
+}
+
+/// Count one call of `function` that took `elapsed`.
+pub unsafe fn add(function: *const Function, elapsed: Duration) {
+    if !is_profiling() {
+        return;
+    }
+
+    if let Some(profiler) = profiler().as_mut() {
+        let stats = profiler.stats.entry((*function).index).or_default();
+        stats.calls += 1;
+        stats.total += elapsed;
+    }
+}
+
+/// Write the collected stats to `profile.txt`, most total time first, and
+/// start counting from zero again.
+#[allow(clippy::cast_precision_loss)]
+pub unsafe fn report() -> Result<(), io::Error> {
+    let stats: Option<Vec<(u32, Stats)>> = profiler()
+        .as_mut()
+        .map(|p| p.stats.drain().collect());
+
+    // Resolve names and write the file without holding up the game thread.
+    if let Some(mut stats) = stats {
+        stats.sort_by(|(_, a), (_, b)| b.total.cmp(&a.total));
+
+        let mut file = File::create(REPORT).map(BufWriter::new)?;
+
+        writeln!(&mut file, "{:>12} {:>12} {:>12}  function", "calls", "total (ms)", "mean (us)")?;
+
+        for (index, stats) in &stats {
+            let name = GLOBAL_OBJECTS
+                .get()
+                .get(*index as usize)
+                .and_then(|o| o.as_ref())
+                .and_then(|o| o.full_name())
+                .unwrap_or_else(|| format!("<object {}>", index));
+
+            let total = stats.total.as_secs_f64();
+
+            writeln!(
+                &mut file,
+                "{:>12} {:>12.3} {:>12.3}  {}",
+                stats.calls,
+                total * 1e3,
+                total * 1e6 / stats.calls as f64,
+                name
+            )?;
+        }
+
+        info!("Wrote profile of {} functions to {}.", stats.len(), REPORT);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use crate::hook::events::Context;
+use crate::hook::{filter, reflect, scheduler};
+use crate::GLOBAL_OBJECTS;
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::{info, warn};
+
+// Started, stopped and flushed by hotkeys, and fed by `ProcessEvent`.
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Whether `RECORDER` is `Some`, so that `ProcessEvent` can check without
+/// taking the lock.
+static RECORDING_EVENTS: AtomicBool = AtomicBool::new(false);
+
+const DEFAULT_CAPACITY: usize = 10_000;
+const RECORDING: &str = "events.txt";
+
+/// Keeps the most recent `capacity` ProcessEvent calls.
+struct Recorder {
+    records: VecDeque<Record>,
+    capacity: usize,
+}
+
+#[derive(Clone)]
+struct Record {
+    caller: String,
+    function: String,
+
+    /// A copy of the parameters, to replay the call with.
+    parameters: Vec<u8>,
+
+    /// `caller called function(parameter: value, ...)`.
+    text: String,
+}
+
+impl Recorder {
+    fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    unsafe fn record(&mut self, context: &Context) {
+        let caller = context
+            .this
+            .as_ref()
+            .and_then(|o| o.full_name())
+            .unwrap_or_else(|| String::from("None"));
+
+        let function = &*context.function;
+        let name = function.full_name().unwrap_or_else(|| String::from("?"));
+
+        if !filter::allows(&[&caller, &name]) {
+            return;
+        }
+
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+
+        let formatted = reflect::format_parameters(function, context.parameters.cast());
+        let text = format!("{} called {}({})", caller, name, formatted);
+
+        let parameters = if context.parameters.is_null() {
+            Vec::new()
+        } else {
+            let size = usize::from(function.params_size);
+            slice::from_raw_parts(context.parameters.cast::<u8>(), size).to_vec()
+        };
+
+        self.records.push_back(Record {
+            caller,
+            function: name,
+            parameters,
+            text,
+        });
+    }
+}
+
+fn recorder() -> MutexGuard<'static, Option<Recorder>> {
+    RECORDER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn start() {
+    let mut recorder = recorder();
+
+    if recorder.is_none() {
+        *recorder = Some(Recorder::new(DEFAULT_CAPACITY));
+        RECORDING_EVENTS.store(true, Ordering::Relaxed);
+        info!("Started recording events.");
+    }
+}
+
+pub fn stop() {
+    let mut recorder = recorder();
+    RECORDING_EVENTS.store(false, Ordering::Relaxed);
+
+    if recorder.take().is_some() {
+        info!("Stopped recording events.");
+    }
+}
+
+pub fn is_recording() -> bool {
+    RECORDING_EVENTS.load(Ordering::Relaxed)
+}
+
+pub unsafe fn record(context: &Context) {
+    if !is_recording() {
+        return;
+    }
+
+    if let Some(recorder) = recorder().as_mut() {
+        recorder.record(context);
+    }
+}
+
+/// Write the recorded events, oldest first, to `events.txt` and forget them.
+pub fn flush() -> Result<(), io::Error> {
+    // Take the records so that the file is written without holding up the
+    // game thread.
+    let records = match recorder().as_mut() {
+        Some(recorder) => mem::take(&mut recorder.records),
+        None => return Ok(()),
+    };
+
+    let mut file = File::create(RECORDING).map(BufWriter::new)?;
+
+    for record in &records {
+        writeln!(&mut file, "{}", record.text)?;
+    }
+
+    info!("Flushed {} events to {}.", records.len(), RECORDING);
+    Ok(())
+}
+
+/// Print the `count` most recent events, numbered for `replay`, with 0 being
+/// the newest.
+pub fn list(count: usize) {
+    scheduler::after_frames(0, move || match recorder().as_ref() {
+        Some(recorder) => {
+            for (i, record) in recorder.records.iter().rev().take(count).enumerate().rev() {
+                info!("{:>5}: {}", i, record.text);
+            }
+        }
+        None => warn!("Not recording events."),
+    });
+}
+
+/// Call recorded event `index`, as numbered by `list`, again on the game
+/// thread. The caller is looked up by its full name, so it must still exist.
+/// Pointers in the parameters, such as objects and strings, are restored as
+/// they were and may have gone stale.
+pub fn replay(index: usize) {
+    scheduler::after_frames(0, move || unsafe { replay_now(index) });
+}
+
+unsafe fn replay_now(index: usize) {
+    // Copied out, since the replayed call is recorded too.
+    let record = recorder()
+        .as_ref()
+        .and_then(|r| r.records.iter().rev().nth(index))
+        .cloned();
+
+    let record = match record {
+        Some(record) => record,
+        None => {
+            warn!("There's no recorded event {}.", index);
+            return;
+        }
+    };
+
+    let caller = match GLOBAL_OBJECTS.get().find_mut(&record.caller) {
+        Some(caller) => caller,
+        None => {
+            warn!("Unable to find {} to replay its call.", record.caller);
+            return;
+        }
+    };
+
+    let function = match reflect::find_function(&record.function) {
+        Some(function) => function,
+        None => {
+            warn!("Unable to find {} to replay.", record.function);
+            return;
+        }
+    };
+
+    // ProcessEvent may write outputs into the parameters, so this uses the
+    // copy rather than the recorded ones.
+    let mut parameters = record.parameters;
+    parameters.resize(usize::from((*function).params_size), 0);
+
+    info!("Replaying {}", record.text);
+    (*caller).process_event(function, parameters.as_mut_ptr().cast());
+}
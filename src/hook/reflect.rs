@@ -0,0 +1,703 @@
+use crate::game::{
+    cast, BoolProperty, Class, FString, Function, NameIndex, Object, Property, Struct,
+};
+use crate::global::Global;
+use crate::hook::{gmalloc, scheduler};
+use crate::GLOBAL_OBJECTS;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter, Write};
+use std::iter;
+use std::slice;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// Set once every property class is found. Core's classes are never freed.
+static CLASSES: Global<PropertyClasses> = Global::null();
+
+/// Maps function names, as passed to `find_function`, to UFunction addresses.
+static FUNCTIONS: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+/// Maps the address of a class, struct or function to its property map.
+static PROPERTY_MAPS: Mutex<Option<HashMap<usize, CachedMap>>> = Mutex::new(None);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("property classes haven't loaded yet")]
+    ClassesNotFound,
+
+    #[error("expected {expected} arguments but got {got}")]
+    ArgumentCount { expected: usize, got: usize },
+
+    #[error("cannot write {value} to {property}")]
+    Mismatch { property: String, value: String },
+
+    #[error("allocator error: {0}")]
+    Gmalloc(#[from] gmalloc::Error),
+
+    #[error("no function named \"{0}\"")]
+    FunctionNotFound(String),
+
+    #[error("null object")]
+    NullObject,
+
+    #[error("no property named \"{0}\"")]
+    PropertyNotFound(String),
+
+    #[error("no name \"{0}\"")]
+    NameNotFound(String),
+
+    #[error("cannot read {property} as {expected}")]
+    WrongType {
+        property: String,
+        expected: &'static str,
+    },
+}
+
+struct PropertyClasses {
+    bool_property: *const Class,
+    byte_property: *const Class,
+    float_property: *const Class,
+    int_property: *const Class,
+    name_property: *const Class,
+    object_property: *const Class,
+    str_property: *const Class,
+}
+
+impl PropertyClasses {
+    unsafe fn find() -> Option<Self> {
+        Some(Self {
+            bool_property: find("Class Core.BoolProperty")?,
+            byte_property: find("Class Core.ByteProperty")?,
+            float_property: find("Class Core.FloatProperty")?,
+            int_property: find("Class Core.IntProperty")?,
+            name_property: find("Class Core.NameProperty")?,
+            object_property: find("Class Core.ObjectProperty")?,
+            str_property: find("Class Core.StrProperty")?,
+        })
+    }
+}
+
+unsafe fn find(class: &str) -> Option<*const Class> {
+    GLOBAL_OBJECTS.get().find(class).map(|o| o.cast())
+}
+
+unsafe fn classes() -> Option<&'static PropertyClasses> {
+    if CLASSES.is_null() {
+        let classes = PropertyClasses::find()?;
+        CLASSES.set(Box::into_raw(Box::new(classes)));
+    }
+
+    Some(CLASSES.get())
+}
+
+/// A property value of one of the simple property types.
+pub enum Value {
+    Bool(bool),
+    Byte(u8),
+    Float(f32),
+    Int(i32),
+    Name(NameIndex),
+    Object(*mut Object),
+    Str(String),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Bool(b) => b.fmt(f),
+            Self::Byte(b) => b.fmt(f),
+            Self::Float(x) => x.fmt(f),
+            Self::Int(i) => i.fmt(f),
+            Self::Name(n) => unsafe { n.name().as_deref().unwrap_or("<bad name>").fmt(f) },
+            Self::Object(o) => match unsafe { o.as_ref().and_then(|o| o.full_name()) } {
+                Some(name) => name.fmt(f),
+                None => "None".fmt(f),
+            },
+            Self::Str(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// The type of a property, as far as `read` and `write` understand it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Bool {
+        bitmask: u32,
+    },
+    Byte,
+    Float,
+    Int,
+    Name,
+    Object,
+    Str,
+
+    /// Structs, arrays and the other types we only have the bytes of.
+    Other,
+}
+
+impl Kind {
+    /// The kind of `property`. `None` if the property classes haven't loaded.
+    pub unsafe fn of(property: &Property) -> Option<Self> {
+        let classes = classes()?;
+
+        Some(if property.is(classes.bool_property) {
+            Self::Bool {
+                bitmask: cast::<BoolProperty>(property).bitmask,
+            }
+        } else if property.is(classes.byte_property) {
+            Self::Byte
+        } else if property.is(classes.float_property) {
+            Self::Float
+        } else if property.is(classes.int_property) {
+            Self::Int
+        } else if property.is(classes.name_property) {
+            Self::Name
+        } else if property.is(classes.object_property) {
+            Self::Object
+        } else if property.is(classes.str_property) {
+            Self::Str
+        } else {
+            Self::Other
+        })
+    }
+}
+
+/// Where a property lives in its container and what it holds.
+#[derive(Clone, Copy, Debug)]
+pub struct PropertyEntry {
+    pub property: *const Property,
+    pub offset: u32,
+
+    /// The size of every element, for static arrays.
+    pub size: u32,
+
+    pub kind: Kind,
+}
+
+impl PropertyEntry {
+    pub unsafe fn new(property: &Property) -> Option<Self> {
+        Some(Self {
+            property,
+            offset: property.offset,
+            size: property.element_size * property.array_dim,
+            kind: Kind::of(property)?,
+        })
+    }
+
+    /// The bytes of this property in `container`.
+    pub unsafe fn bytes<'a>(&self, container: *const u8) -> &'a [u8] {
+        slice::from_raw_parts(container.add(self.offset as usize), self.size as usize)
+    }
+
+    /// Read the value out of `container`, which is an object or a parameter
+    /// block. Returns `None` for property types we don't decode, such as
+    /// structs and arrays.
+    pub unsafe fn read(&self, container: *const u8) -> Option<Value> {
+        let address = container.add(self.offset as usize);
+
+        Some(match self.kind {
+            Kind::Bool { bitmask } => {
+                Value::Bool(address.cast::<u32>().read_unaligned() & bitmask != 0)
+            }
+            Kind::Byte => Value::Byte(address.read()),
+            Kind::Float => Value::Float(address.cast::<f32>().read_unaligned()),
+            Kind::Int => Value::Int(address.cast::<i32>().read_unaligned()),
+            Kind::Name => Value::Name(address.cast::<NameIndex>().read_unaligned()),
+            Kind::Object => Value::Object(address.cast::<*mut Object>().read_unaligned()),
+            Kind::Str => {
+                let string = &*address.cast::<FString>();
+
+                let string = if string.data.is_null() {
+                    String::new()
+                } else {
+                    string
+                        .to_string()
+                        .to_string_lossy()
+                        .trim_end_matches('\0')
+                        .to_owned()
+                };
+
+                Value::Str(string)
+            }
+            Kind::Other => return None,
+        })
+    }
+
+    /// Write `value` into `container`. Ints convert to bytes and floats where
+    /// they fit. Strings are copied into the game's memory, and the string
+    /// they replace is freed.
+    #[allow(clippy::cast_precision_loss)]
+    pub unsafe fn write(&self, container: *mut u8, value: &Value) -> Result<(), Error> {
+        let address = container.add(self.offset as usize);
+
+        let mismatch = || Error::Mismatch {
+            property: (*self.property)
+                .full_name()
+                .unwrap_or_else(|| String::from("?")),
+            value: value.to_string(),
+        };
+
+        match (self.kind, value) {
+            (Kind::Bool { bitmask }, Value::Bool(b)) => {
+                let address = address.cast::<u32>();
+                let bits = address.read_unaligned() & !bitmask;
+                address.write_unaligned(if *b { bits | bitmask } else { bits });
+            }
+
+            (Kind::Byte, Value::Byte(b)) => address.write(*b),
+
+            (Kind::Byte, Value::Int(i)) => {
+                address.write(u8::try_from(*i).map_err(|_| mismatch())?);
+            }
+
+            (Kind::Float, Value::Float(x)) => address.cast::<f32>().write_unaligned(*x),
+            (Kind::Float, Value::Int(i)) => address.cast::<f32>().write_unaligned(*i as f32),
+            (Kind::Int, Value::Int(i)) => address.cast::<i32>().write_unaligned(*i),
+
+            (Kind::Int, Value::Byte(b)) => {
+                address.cast::<i32>().write_unaligned(i32::from(*b));
+            }
+
+            (Kind::Name, Value::Name(n)) => {
+                address.cast::<NameIndex>().write_unaligned(NameIndex {
+                    index: n.index,
+                    number: n.number,
+                });
+            }
+
+            (Kind::Object, Value::Object(o)) => address.cast::<*mut Object>().write_unaligned(*o),
+
+            (Kind::Str, Value::Str(s)) => {
+                let address = address.cast::<FString>();
+                let old = address.read_unaligned();
+                address.write_unaligned(gmalloc::string(s)?);
+
+                if !old.data.is_null() {
+                    gmalloc::free(old.data.cast())?;
+                }
+            }
+
+            _ => return Err(mismatch()),
+        }
+
+        Ok(())
+    }
+}
+
+/// Maps property names to where they are in a class, struct or function.
+pub type PropertyMap = HashMap<String, PropertyEntry>;
+
+// Entries point into reflection data, which is only read, and a cached map is
+// rebuilt when its struct's slot holds another object.
+unsafe impl Send for PropertyEntry {}
+unsafe impl Sync for PropertyEntry {}
+
+struct CachedMap {
+    /// The index of the struct the map was built for, to notice when its
+    /// address is reused by another object.
+    index: u32,
+
+    map: Arc<PropertyMap>,
+}
+
+impl Struct {
+    /// The properties of this struct and its bases by name, built once per
+    /// struct, e.g., `class.property_map()["Health"].offset`. A property
+    /// hides base properties with the same name. Empty if the property
+    /// classes haven't loaded.
+    pub unsafe fn property_map(&self) -> Arc<PropertyMap> {
+        let mut maps = PROPERTY_MAPS.lock().unwrap_or_else(PoisonError::into_inner);
+        let maps = maps.get_or_insert_with(HashMap::new);
+        let address = self as *const Self as usize;
+
+        if let Some(cached) = maps.get(&address) {
+            if cached.index == self.index {
+                return Arc::clone(&cached.map);
+            }
+        }
+
+        let mut map = PropertyMap::new();
+
+        let structs = iter::successors(Some(self), |current| {
+            current
+                .super_field
+                .as_ref()
+                .map(|field| cast::<Struct>(field))
+        });
+
+        for property in structs.flat_map(|s| s.iter_properties()) {
+            let name = match property.name() {
+                Some(name) => name.into_owned(),
+                None => continue,
+            };
+
+            let entry = match PropertyEntry::new(property) {
+                Some(entry) => entry,
+                None => return Arc::default(),
+            };
+
+            map.entry(name).or_insert(entry);
+        }
+
+        let map = Arc::new(map);
+
+        maps.insert(
+            address,
+            CachedMap {
+                index: self.index,
+                map: Arc::clone(&map),
+            },
+        );
+
+        map
+    }
+}
+
+/// Forget every property map and found function, e.g., after a map change
+/// freed the classes and functions of the previous level's scripts.
+pub fn invalidate_property_maps() {
+    *PROPERTY_MAPS.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    *FUNCTIONS.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Read the value of `property` out of `container`, which is an object or a
+/// parameter block. Returns `None` for property types we don't decode, such as
+/// structs and arrays.
+pub unsafe fn read(property: &Property, container: *const u8) -> Option<Value> {
+    PropertyEntry::new(property)?.read(container)
+}
+
+/// Write `value` into `property` of `container`, converting like
+/// `PropertyEntry::write`.
+pub unsafe fn write(property: &Property, container: *mut u8, value: &Value) -> Result<(), Error> {
+    PropertyEntry::new(property)
+        .ok_or(Error::ClassesNotFound)?
+        .write(container, value)
+}
+
+/// Find the property called `name` in the class of `object` or its bases.
+pub unsafe fn find_property<'a>(object: &'a Object, name: &str) -> Option<&'a Property> {
+    let class = object.class.as_ref()?;
+    let entry = *class.property_map().get(name)?;
+    Some(&*entry.property)
+}
+
+/// A Rust type that a property of a simple type can be read as or written from.
+pub trait PropertyValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+    fn into_value(self) -> Value;
+}
+
+macro_rules! property_value {
+    ($typ:ty, $variant:ident) => {
+        impl PropertyValue for $typ {
+            fn from_value(value: Value) -> Option<Self> {
+                match value {
+                    Value::$variant(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            fn into_value(self) -> Value {
+                Value::$variant(self)
+            }
+        }
+    };
+}
+
+property_value!(bool, Bool);
+property_value!(u8, Byte);
+property_value!(f32, Float);
+property_value!(NameIndex, Name);
+property_value!(*mut Object, Object);
+property_value!(String, Str);
+
+impl PropertyValue for i32 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Int(i) => Some(i),
+            Value::Byte(b) => Some(i32::from(b)),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Int(self)
+    }
+}
+
+impl Object {
+    /// Read the property called `name` of this object's class or its bases,
+    /// e.g., `pawn.get_property::<i32>("Health")`. Fails if the property isn't
+    /// a `T`.
+    pub unsafe fn get_property<T: PropertyValue>(&self, name: &str) -> Result<T, Error> {
+        let property =
+            find_property(self, name).ok_or_else(|| Error::PropertyNotFound(name.to_owned()))?;
+
+        let wrong_type = || Error::WrongType {
+            property: property.full_name().unwrap_or_else(|| String::from("?")),
+            expected: std::any::type_name::<T>(),
+        };
+
+        let value = read(property, (self as *const Self).cast()).ok_or_else(wrong_type)?;
+        T::from_value(value).ok_or_else(wrong_type)
+    }
+
+    /// Write the property called `name`, converting `value` like `write`.
+    pub unsafe fn set_property<T: PropertyValue>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), Error> {
+        let object: *mut Self = self;
+
+        let property = find_property(&*object, name)
+            .ok_or_else(|| Error::PropertyNotFound(name.to_owned()))?;
+
+        write(property, object.cast(), &value.into_value())
+    }
+}
+
+/// A property whose value in an object differs from its class default object.
+pub struct Difference<'a> {
+    pub property: &'a Property,
+    pub value: String,
+    pub default: String,
+}
+
+/// The properties of `object` that differ from its class default object, to
+/// find which field controls a behavior. `None` if there's no default object.
+pub unsafe fn diff_default(object: &Object) -> Option<Vec<Difference>> {
+    let class = &*object.class;
+    let default = &*class.default_object()?;
+
+    let object_bytes: *const u8 = (object as *const Object).cast();
+    let default_bytes: *const u8 = (default as *const Object).cast();
+
+    let properties = class.property_map();
+
+    let mut differences: Vec<_> = properties
+        .values()
+        .filter(|entry| {
+            let value = entry.bytes(object_bytes);
+            let default = entry.bytes(default_bytes);
+
+            if let Kind::Bool { bitmask } = entry.kind {
+                let bits = |bytes: &[u8]| bytes.as_ptr().cast::<u32>().read_unaligned() & bitmask;
+                bits(value) != bits(default)
+            } else {
+                value != default
+            }
+        })
+        .collect();
+
+    differences.sort_by_key(|entry| entry.offset);
+
+    let differences = differences
+        .into_iter()
+        .map(|entry| Difference {
+            property: &*entry.property,
+            value: describe(entry, object_bytes),
+            default: describe(entry, default_bytes),
+        })
+        .collect();
+
+    Some(differences)
+}
+
+/// Log the properties of the object called `full_name` that differ from its
+/// class default object, on the game thread.
+pub fn log_diff_default(full_name: String) {
+    scheduler::after_frames(0, move || unsafe {
+        let object = match GLOBAL_OBJECTS.get().find(&full_name) {
+            Some(object) => &*object,
+            None => {
+                warn!("Unable to find \"{}\".", full_name);
+                return;
+            }
+        };
+
+        match diff_default(object) {
+            Some(differences) => {
+                for difference in &differences {
+                    info!(
+                        "{}: {} (default: {})",
+                        difference.property.name().as_deref().unwrap_or("?"),
+                        difference.value,
+                        difference.default
+                    );
+                }
+
+                info!("{} properties differ from the default.", differences.len());
+            }
+            None => warn!("{} has no class default object.", full_name),
+        }
+    });
+}
+
+/// The value of `entry` in `container`, or its bytes if it isn't a simple
+/// type.
+unsafe fn describe(entry: &PropertyEntry, container: *const u8) -> String {
+    if let Some(value) = entry.read(container) {
+        return value.to_string();
+    }
+
+    entry
+        .bytes(container)
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+/// Prefix `name` with "Function " unless it already is, so that callers can
+/// write "WillowGame.WillowPlayerController.PlayerTick".
+pub fn function_full_name(name: &str) -> String {
+    if name.starts_with("Function ") {
+        name.to_owned()
+    } else {
+        format!("Function {}", name)
+    }
+}
+
+/// Find the UFunction called `name`, with or without the "Function " prefix.
+/// Remembers the answer until `invalidate_property_maps`, since searching
+/// every object is slow.
+pub unsafe fn find_function(name: &str) -> Option<*mut Function> {
+    let mut functions = FUNCTIONS.lock().unwrap_or_else(PoisonError::into_inner);
+    let functions = functions.get_or_insert_with(HashMap::new);
+
+    if let Some(&function) = functions.get(name) {
+        return Some(function as *mut Function);
+    }
+
+    let function: *mut Function = GLOBAL_OBJECTS
+        .get()
+        .find_mut(&function_full_name(name))?
+        .cast();
+
+    functions.insert(name.to_owned(), function as usize);
+    Some(function)
+}
+
+/// Call the UFunction called `function` on `this`, e.g.,
+/// `call("WillowGame.WillowPlayerController.ServerSay", this, &[...])`.
+pub unsafe fn call_by_name(
+    function: &str,
+    this: *mut Object,
+    arguments: &[Value],
+) -> Result<Option<Value>, Error> {
+    if this.is_null() {
+        return Err(Error::NullObject);
+    }
+
+    let function =
+        find_function(function).ok_or_else(|| Error::FunctionNotFound(function.to_owned()))?;
+
+    call(this, function, arguments)
+}
+
+/// Call `function` on `object` with `arguments` in parameter order, and return
+/// the return value, if any. Out parameters start zeroed.
+pub unsafe fn call(
+    object: *mut Object,
+    function: *mut Function,
+    arguments: &[Value],
+) -> Result<Option<Value>, Error> {
+    let mut parameters = vec![0_u8; usize::from((*function).params_size)];
+
+    let inputs: Vec<&Property> = (*function)
+        .iter_children()
+        .filter(|p| p.is_param() && !p.is_return_param())
+        .collect();
+
+    if inputs.len() != arguments.len() {
+        return Err(Error::ArgumentCount {
+            expected: inputs.len(),
+            got: arguments.len(),
+        });
+    }
+
+    for (input, argument) in inputs.iter().zip(arguments) {
+        write(input, parameters.as_mut_ptr(), argument)?;
+    }
+
+    (*object).process_event(function, parameters.as_mut_ptr().cast());
+
+    let return_value = (*function)
+        .iter_children()
+        .find(|p| p.is_return_param())
+        .and_then(|p| read(p, parameters.as_ptr()));
+
+    // The function copies the strings it keeps, so ours are ours to free.
+    for (input, argument) in inputs.iter().zip(arguments) {
+        if let Value::Str(_) = argument {
+            let string = parameters
+                .as_ptr()
+                .add(input.offset as usize)
+                .cast::<FString>();
+            gmalloc::free(string.read_unaligned().data.cast())?;
+        }
+    }
+
+    Ok(return_value)
+}
+
+/// Call `function` on `object` with only the parameters named in `arguments`,
+/// leaving the rest zeroed, e.g., optional parameters and out arrays. Returns
+/// the parameters afterwards, to read outputs from with the function's
+/// `property_map`.
+pub unsafe fn call_named(
+    object: *mut Object,
+    function: *mut Function,
+    arguments: &[(&str, Value)],
+) -> Result<Vec<u8>, Error> {
+    if object.is_null() {
+        return Err(Error::NullObject);
+    }
+
+    let mut parameters = vec![0_u8; usize::from((*function).params_size)];
+    let properties = (*function).property_map();
+
+    for (name, argument) in arguments {
+        let entry = properties
+            .get(*name)
+            .filter(|e| (*e.property).is_param())
+            .ok_or_else(|| Error::PropertyNotFound((*name).to_owned()))?;
+
+        entry.write(parameters.as_mut_ptr(), argument)?;
+    }
+
+    (*object).process_event(function, parameters.as_mut_ptr().cast());
+    Ok(parameters)
+}
+
+/// Format each parameter of `function` in `parameters` as `name: value`.
+pub unsafe fn format_parameters(function: &Function, parameters: *const u8) -> String {
+    let mut formatted = String::new();
+
+    if parameters.is_null() {
+        return formatted;
+    }
+
+    for parameter in function.iter_children().filter(|p| p.is_param()) {
+        if !formatted.is_empty() {
+            formatted.push_str(", ");
+        }
+
+        formatted.push_str(parameter.name().as_deref().unwrap_or("?"));
+        formatted.push_str(": ");
+
+        match read(parameter, parameters) {
+            Some(value) => formatted.push_str(&value.to_string()),
+            None => formatted.push('?'),
+        }
+    }
+
+    formatted
+}
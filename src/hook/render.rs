@@ -0,0 +1,207 @@
+use crate::global::Global;
+use crate::hook::contain::contain;
+use crate::hook::InFlight;
+
+use std::ffi::c_void;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use thiserror::Error;
+use winapi::shared::d3d9::{
+    Direct3DCreate9, IDirect3D9, IDirect3DDevice9, D3DADAPTER_DEFAULT,
+    D3DCREATE_DISABLE_DRIVER_MANAGEMENT, D3DCREATE_SOFTWARE_VERTEXPROCESSING, D3D_SDK_VERSION,
+};
+use winapi::shared::d3d9types::{
+    D3DDEVTYPE_HAL, D3DFMT_UNKNOWN, D3DPRESENT_PARAMETERS, D3DSWAPEFFECT_DISCARD,
+};
+use winapi::shared::minwindef::TRUE;
+use winapi::shared::ntdef::HRESULT;
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::winuser::{CreateWindowExA, DestroyWindow, WS_OVERLAPPEDWINDOW};
+
+/// The original `IDirect3DDevice9::EndScene`, or its trampoline while hooked.
+pub static END_SCENE: Global<c_void> = Global::null();
+
+/// The original `IDirect3DDevice9::Reset`, or its trampoline while hooked.
+pub static RESET: Global<c_void> = Global::null();
+
+pub static END_SCENE_CALLS: AtomicUsize = AtomicUsize::new(0);
+pub static RESET_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+// Registered from any thread, called on the render thread.
+static CALLBACKS: Mutex<Callbacks> = Mutex::new(Callbacks {
+    frame: Vec::new(),
+    lost: Vec::new(),
+    reset: Vec::new(),
+});
+
+const RESET_INDEX: usize = 16;
+const END_SCENE_INDEX: usize = 42;
+
+type Callback = Box<dyn FnMut(*mut IDirect3DDevice9) + Send>;
+
+/// A callback and whether it crashed.
+type Entry = (Callback, bool);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("failed to create a dummy window")]
+    Window,
+
+    #[error("Direct3DCreate9 failed")]
+    Direct3D,
+
+    #[error("failed to create a dummy device ({0:#x})")]
+    Device(HRESULT),
+}
+
+struct Callbacks {
+    frame: Vec<Entry>,
+    lost: Vec<Entry>,
+    reset: Vec<Entry>,
+}
+
+fn callbacks() -> MutexGuard<'static, Callbacks> {
+    CALLBACKS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Find `EndScene` and `Reset` by reading the vtable of a throwaway device.
+/// Every device shares the vtable, so hooking these entries' targets hooks the
+/// game's device too.
+pub unsafe fn find() -> Result<(), Error> {
+    let window = CreateWindowExA(
+        0,
+        "STATIC\0".as_ptr().cast(),
+        "blps\0".as_ptr().cast(),
+        WS_OVERLAPPEDWINDOW,
+        0,
+        0,
+        100,
+        100,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+    );
+
+    if window.is_null() {
+        return Err(Error::Window);
+    }
+
+    let result = find_with_window(window);
+    DestroyWindow(window);
+    result
+}
+
+unsafe fn find_with_window(window: HWND) -> Result<(), Error> {
+    let d3d: *mut IDirect3D9 = Direct3DCreate9(D3D_SDK_VERSION);
+
+    if d3d.is_null() {
+        return Err(Error::Direct3D);
+    }
+
+    let mut parameters: D3DPRESENT_PARAMETERS = mem::zeroed();
+    parameters.Windowed = TRUE;
+    parameters.SwapEffect = D3DSWAPEFFECT_DISCARD;
+    parameters.BackBufferFormat = D3DFMT_UNKNOWN;
+    parameters.hDeviceWindow = window;
+
+    let mut device: *mut IDirect3DDevice9 = ptr::null_mut();
+
+    let result = (*d3d).CreateDevice(
+        D3DADAPTER_DEFAULT,
+        D3DDEVTYPE_HAL,
+        window,
+        D3DCREATE_SOFTWARE_VERTEXPROCESSING | D3DCREATE_DISABLE_DRIVER_MANAGEMENT,
+        &mut parameters,
+        &mut device,
+    );
+
+    if !SUCCEEDED(result) {
+        (*d3d).Release();
+        return Err(Error::Device(result));
+    }
+
+    let vtable = *device.cast::<*const *mut c_void>();
+    RESET.set(*vtable.add(RESET_INDEX));
+    END_SCENE.set(*vtable.add(END_SCENE_INDEX));
+
+    (*device).Release();
+    (*d3d).Release();
+
+    Ok(())
+}
+
+/// Call `callback` on the render thread at the end of every scene, to draw on
+/// top of the game.
+pub fn on_frame(callback: impl FnMut(*mut IDirect3DDevice9) + Send + 'static) {
+    callbacks().frame.push((Box::new(callback), false));
+}
+
+/// Call `callback` just before the device resets, to release resources in
+/// `D3DPOOL_DEFAULT`, such as fonts and render targets.
+pub fn on_lost(callback: impl FnMut(*mut IDirect3DDevice9) + Send + 'static) {
+    callbacks().lost.push((Box::new(callback), false));
+}
+
+/// Call `callback` after the device resets successfully, to recreate what
+/// `on_lost` released.
+pub fn on_reset(callback: impl FnMut(*mut IDirect3DDevice9) + Send + 'static) {
+    callbacks().reset.push((Box::new(callback), false));
+}
+
+/// Drop every callback.
+pub fn clear() {
+    let mut callbacks = callbacks();
+    callbacks.frame.clear();
+    callbacks.lost.clear();
+    callbacks.reset.clear();
+}
+
+/// Run each callback in `entries` that hasn't crashed.
+fn run(entries: &mut [Entry], device: *mut IDirect3DDevice9) {
+    for (callback, crashed) in entries.iter_mut().filter(|(_, crashed)| !crashed) {
+        if contain("a render callback", || callback(device)).is_none() {
+            *crashed = true;
+        }
+    }
+}
+
+pub unsafe extern "system" fn my_end_scene(device: *mut IDirect3DDevice9) -> HRESULT {
+    type EndScene = unsafe extern "system" fn(device: *mut IDirect3DDevice9) -> HRESULT;
+
+    let _in_flight = InFlight::enter();
+    END_SCENE_CALLS.fetch_add(1, Ordering::Relaxed);
+
+    run(&mut callbacks().frame, device);
+
+    let original = mem::transmute::<*mut c_void, EndScene>(END_SCENE.ptr());
+    original(device)
+}
+
+pub unsafe extern "system" fn my_reset(
+    device: *mut IDirect3DDevice9,
+    parameters: *mut D3DPRESENT_PARAMETERS,
+) -> HRESULT {
+    type Reset = unsafe extern "system" fn(
+        device: *mut IDirect3DDevice9,
+        parameters: *mut D3DPRESENT_PARAMETERS,
+    ) -> HRESULT;
+
+    let _in_flight = InFlight::enter();
+    RESET_CALLS.fetch_add(1, Ordering::Relaxed);
+
+    run(&mut callbacks().lost, device);
+
+    let original = mem::transmute::<*mut c_void, Reset>(RESET.ptr());
+    let result = original(device, parameters);
+
+    if SUCCEEDED(result) {
+        run(&mut callbacks().reset, device);
+    }
+
+    result
+}
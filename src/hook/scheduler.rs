@@ -0,0 +1,175 @@
+use crate::hook::contain::contain;
+use crate::hook::events;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+// Scheduled from any thread, run on the game thread.
+static SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler {
+    tasks: Vec::new(),
+    cancelled: Vec::new(),
+    frame: 0,
+    next_id: 0,
+});
+
+/// Identifies a scheduled task, to cancel it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TaskId(u64);
+
+#[derive(Clone, Copy)]
+enum Due {
+    At(Instant),
+    Frame(u64),
+}
+
+struct Task {
+    id: TaskId,
+    due: Due,
+    period: Option<Duration>,
+    callback: Box<dyn FnMut() + Send>,
+}
+
+struct Scheduler {
+    tasks: Vec<Task>,
+
+    /// Tasks cancelled while they were running, so they aren't rescheduled.
+    cancelled: Vec<TaskId>,
+
+    frame: u64,
+    next_id: u64,
+}
+
+impl Scheduler {
+    fn add(
+        &mut self,
+        due: Due,
+        period: Option<Duration>,
+        callback: Box<dyn FnMut() + Send>,
+    ) -> TaskId {
+        let id = TaskId(self.next_id);
+        self.next_id += 1;
+
+        self.tasks.push(Task {
+            id,
+            due,
+            period,
+            callback,
+        });
+
+        id
+    }
+}
+
+/// The longest delay or period, a day, so that adding it to now can't
+/// overflow.
+const MAX_SECONDS: f32 = 86_400.0;
+
+fn scheduler() -> MutexGuard<'static, Scheduler> {
+    SCHEDULER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn once(f: impl FnOnce() + Send + 'static) -> Box<dyn FnMut() + Send> {
+    let mut f = Some(f);
+
+    Box::new(move || {
+        if let Some(f) = f.take() {
+            f();
+        }
+    })
+}
+
+/// Run the due tasks once per frame.
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| tick());
+}
+
+/// Call `f` on the game thread once `seconds` have passed.
+pub fn after(seconds: f32, f: impl FnOnce() + Send + 'static) -> TaskId {
+    let due = Due::At(Instant::now() + duration(seconds));
+    scheduler().add(due, None, once(f))
+}
+
+/// Call `f` on the game thread every `seconds`, starting `seconds` from now.
+pub fn every(seconds: f32, f: impl FnMut() + Send + 'static) -> TaskId {
+    let period = duration(seconds);
+    scheduler().add(Due::At(Instant::now() + period), Some(period), Box::new(f))
+}
+
+/// `seconds` clamped to between zero and `MAX_SECONDS`, since
+/// `Duration::from_secs_f32` panics on negative, NaN, and huge values, e.g.,
+/// from a typo in a farming loop.
+fn duration(seconds: f32) -> Duration {
+    if seconds.is_nan() {
+        Duration::from_secs(0)
+    } else {
+        Duration::from_secs_f32(seconds.max(0.0).min(MAX_SECONDS))
+    }
+}
+
+/// Call `f` on the game thread once `frames` more frames have been drawn.
+pub fn after_frames(frames: u64, f: impl FnOnce() + Send + 'static) -> TaskId {
+    let mut scheduler = scheduler();
+    let due = Due::Frame(scheduler.frame + frames);
+    scheduler.add(due, None, once(f))
+}
+
+/// Stop `task` from running again. Does nothing if it already ran.
+pub fn cancel(task: TaskId) {
+    let mut scheduler = scheduler();
+    let count = scheduler.tasks.len();
+    scheduler.tasks.retain(|t| t.id != task);
+
+    if scheduler.tasks.len() == count {
+        scheduler.cancelled.push(task);
+    }
+}
+
+/// Drop every task.
+pub fn clear() {
+    let mut scheduler = scheduler();
+    scheduler.tasks.clear();
+    scheduler.cancelled.clear();
+}
+
+fn tick() {
+    let now = Instant::now();
+
+    // Take the due tasks out so that they can schedule or cancel tasks.
+    let due: Vec<Task> = {
+        let mut scheduler = scheduler();
+        scheduler.frame += 1;
+        let frame = scheduler.frame;
+
+        let is_due = |task: &Task| match task.due {
+            Due::At(instant) => instant <= now,
+            Due::Frame(f) => f <= frame,
+        };
+
+        let (due, pending) = scheduler.tasks.drain(..).partition(is_due);
+        scheduler.tasks = pending;
+        due
+    };
+
+    if due.is_empty() {
+        return;
+    }
+
+    let mut again = Vec::new();
+
+    for mut task in due {
+        if contain("a scheduled task", &mut task.callback).is_none() {
+            continue;
+        }
+
+        if let Some(period) = task.period {
+            task.due = Due::At(now + period);
+            again.push(task);
+        }
+    }
+
+    let mut scheduler = scheduler();
+    let cancelled = std::mem::take(&mut scheduler.cancelled);
+    scheduler
+        .tasks
+        .extend(again.into_iter().filter(|t| !cancelled.contains(&t.id)));
+}
@@ -0,0 +1,340 @@
+use crate::game::{Function, Object, Property};
+use crate::hook::contain::contain;
+use crate::hook::events::{self, Context};
+use crate::hook::reflect::{self, Value};
+use crate::GLOBAL_OBJECTS;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use log::{error, info, warn};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+
+const SCRIPTS: &str = "scripts";
+
+// Only used on the game thread. The lock is there to catch re-entrance: a
+// script calling a UFunction runs ProcessEvent again from inside a handler.
+static SCRIPTS_STATE: Mutex<Option<Scripts>> = Mutex::new(None);
+
+static RELOAD: AtomicBool = AtomicBool::new(false);
+
+/// An object handed to scripts.
+#[derive(Clone)]
+struct ObjectRef(*mut Object);
+
+// Scripts only run on the game thread.
+unsafe impl Send for ObjectRef {}
+unsafe impl Sync for ObjectRef {}
+
+struct Script {
+    name: String,
+    ast: AST,
+    scope: Scope<'static>,
+}
+
+struct Handler {
+    script: usize,
+    function: String,
+}
+
+struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+
+    /// Maps a UFunction's object index to the script functions handling it.
+    handlers: HashMap<u32, Vec<Handler>>,
+}
+
+type Subscriptions = Arc<Mutex<Vec<(String, String)>>>;
+
+type Result<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+/// Load every `.rhai` file in `scripts` on the next frame. Call again to
+/// reload them after editing.
+pub fn request_reload() {
+    RELOAD.store(true, Ordering::SeqCst);
+}
+
+/// Load the scripts on the game thread once it's running.
+pub unsafe fn subscribe() {
+    request_reload();
+
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| {
+        if RELOAD.swap(false, Ordering::SeqCst) {
+            unsafe { reload() };
+        }
+    });
+}
+
+/// Drop every script.
+pub fn clear() {
+    *SCRIPTS_STATE.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Call the script functions that handle the function in `context`, as
+/// `handler(this, parameters)`.
+pub unsafe fn dispatch(context: &Context) {
+    // Don't dispatch events raised by the scripts themselves.
+    let mut state = match SCRIPTS_STATE.try_lock() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+
+    if let Some(scripts) = state.as_mut() {
+        if contain("scripting", || scripts.dispatch(context)).is_none() {
+            *state = None;
+        }
+    }
+}
+
+unsafe fn reload() {
+    let mut state = SCRIPTS_STATE.lock().unwrap_or_else(PoisonError::into_inner);
+    *state = None;
+
+    match Scripts::load(Path::new(SCRIPTS)) {
+        Ok(scripts) => {
+            info!(
+                "Loaded {} scripts with {} handled events.",
+                scripts.scripts.len(),
+                scripts.handlers.len()
+            );
+            *state = Some(scripts);
+        }
+
+        Err(e) => error!("Failed to load scripts from \"{}\": {}", SCRIPTS, e),
+    }
+}
+
+impl Scripts {
+    unsafe fn load(directory: &Path) -> io::Result<Self> {
+        let subscriptions = Subscriptions::default();
+
+        let mut scripts = Self {
+            engine: engine(Arc::clone(&subscriptions)),
+            scripts: Vec::new(),
+            handlers: HashMap::new(),
+        };
+
+        let mut paths: Vec<_> = fs::read_dir(directory)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |e| e == "rhai"))
+            .collect();
+
+        paths.sort();
+
+        for path in paths {
+            let name = path.display().to_string();
+
+            let ast = match scripts.engine.compile_file(path) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    error!("{}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let mut scope = Scope::new();
+
+            if let Err(e) = scripts.engine.run_ast_with_scope(&mut scope, &ast) {
+                error!("{}: {}", name, e);
+                subscriptions.lock().unwrap_or_else(PoisonError::into_inner).clear();
+                continue;
+            }
+
+            let index = scripts.scripts.len();
+            scripts.scripts.push(Script { name, ast, scope });
+
+            let pending = subscriptions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .split_off(0);
+
+            for (event, function) in pending {
+                scripts.subscribe(index, &event, function);
+            }
+        }
+
+        Ok(scripts)
+    }
+
+    unsafe fn subscribe(&mut self, script: usize, event: &str, function: String) {
+        let full_name = reflect::function_full_name(event);
+
+        match GLOBAL_OBJECTS.get().find(&full_name) {
+            Some(event) => {
+                self.handlers
+                    .entry((*event).index)
+                    .or_default()
+                    .push(Handler { script, function });
+            }
+
+            None => warn!(
+                "{}: unable to find \"{}\", so {} will never be called.",
+                self.scripts[script].name, full_name, function
+            ),
+        }
+    }
+
+    unsafe fn dispatch(&mut self, context: &Context) {
+        let handlers = match self.handlers.get(&(*context.function).index) {
+            Some(handlers) => handlers,
+            None => return,
+        };
+
+        let this = ObjectRef(context.this);
+        let parameters = parameters(&*context.function, context.parameters.cast());
+
+        for handler in handlers {
+            let script = &mut self.scripts[handler.script];
+
+            let result: Result<Dynamic> = self.engine.call_fn(
+                &mut script.scope,
+                &script.ast,
+                &handler.function,
+                (this.clone(), parameters.clone()),
+            );
+
+            if let Err(e) = result {
+                error!("{}: {}: {}", script.name, handler.function, e);
+            }
+        }
+    }
+}
+
+unsafe fn parameters(function: &Function, parameters: *const u8) -> Map {
+    let mut map = Map::new();
+
+    if parameters.is_null() {
+        return map;
+    }
+
+    for (name, entry) in function.property_map().iter() {
+        if !(*entry.property).is_param() {
+            continue;
+        }
+
+        if let Some(value) = entry.read(parameters) {
+            map.insert(name.as_str().into(), to_dynamic(value));
+        }
+    }
+
+    map
+}
+
+fn to_dynamic(value: Value) -> Dynamic {
+    match value {
+        Value::Bool(b) => b.into(),
+        Value::Byte(b) => Dynamic::from_int(b.into()),
+        Value::Float(x) => Dynamic::from_float(x.into()),
+        Value::Int(i) => Dynamic::from_int(i.into()),
+        Value::Name(n) => unsafe { n.name().as_deref().unwrap_or("None").into() },
+        Value::Object(o) if o.is_null() => Dynamic::UNIT,
+        Value::Object(o) => Dynamic::from(ObjectRef(o)),
+        Value::Str(s) => s.into(),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn to_value(dynamic: Dynamic) -> Result<Value> {
+    if dynamic.is::<ObjectRef>() {
+        return Ok(Value::Object(dynamic.cast::<ObjectRef>().0));
+    }
+
+    if dynamic.is::<()>() {
+        return Ok(Value::Object(ptr::null_mut()));
+    }
+
+    if let Ok(b) = dynamic.as_bool() {
+        Ok(Value::Bool(b))
+    } else if let Ok(i) = dynamic.as_int() {
+        Ok(Value::Int(i as i32))
+    } else if let Ok(x) = dynamic.as_float() {
+        Ok(Value::Float(x as f32))
+    } else {
+        match dynamic.into_string() {
+            Ok(s) => Ok(Value::Str(s)),
+            Err(typ) => Err(format!("cannot pass a {} to the game", typ).into()),
+        }
+    }
+}
+
+unsafe fn find_property<'a>(object: &'a ObjectRef, name: &str) -> Result<&'a Property> {
+    let object = object.0.as_ref().ok_or("null object")?;
+    reflect::find_property(object, name)
+        .ok_or_else(|| format!("no property named {}", name).into())
+}
+
+fn engine(subscriptions: Subscriptions) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.on_print(|s| info!("{}", s));
+    engine.register_type_with_name::<ObjectRef>("Object");
+    // find("WillowPlayerController TheWorld.PersistentLevel.WillowPlayerController_0")
+    // find("WillowGame.WillowPlayerController Loader.TheWorld:PersistentLevel.WillowPlayerController_0")
+    engine.register_fn("find", |full_name: &str| unsafe {
+        GLOBAL_OBJECTS
+            .get()
+            .find_mut(full_name)
+            .map_or(Dynamic::UNIT, |o| Dynamic::from(ObjectRef(o)))
+    });
+
+    engine.register_fn("full_name", |object: &mut ObjectRef| unsafe {
+        object
+            .0
+            .as_ref()
+            .and_then(|o| o.full_name())
+            .unwrap_or_else(|| String::from("None"))
+    });
+
+    engine.register_fn("get", |object: &mut ObjectRef, name: &str| -> Result<Dynamic> {
+        unsafe {
+            let property = find_property(object, name)?;
+            Ok(reflect::read(property, object.0.cast())
+                .map_or(Dynamic::UNIT, to_dynamic))
+        }
+    });
+
+    engine.register_fn(
+        "set",
+        |object: &mut ObjectRef, name: &str, value: Dynamic| -> Result<()> {
+            unsafe {
+                let property = find_property(object, name)?;
+                let value = to_value(value)?;
+                reflect::write(property, object.0.cast(), &value).map_err(|e| e.to_string().into())
+            }
+        },
+    );
+
+    // call(this, "WillowGame.WillowPlayerController.ServerSay", ["hello"])
+    engine.register_fn(
+        "call",
+        |object: &mut ObjectRef, function: &str, arguments: Array| -> Result<Dynamic> {
+            unsafe {
+                let arguments = arguments
+                    .into_iter()
+                    .map(to_value)
+                    .collect::<Result<Vec<Value>>>()?;
+
+                let value = reflect::call_by_name(function, object.0, &arguments)
+                    .map_err(|e| e.to_string())?;
+
+                Ok(value.map_or(Dynamic::UNIT, to_dynamic))
+            }
+        },
+    );
+
+    // on("WillowGame.WillowPlayerController.PlayerTick", "tick")
+    engine.register_fn("on", move |event: &str, function: &str| {
+        subscriptions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((event.to_owned(), function.to_owned()));
+    });
+
+    engine
+}
@@ -0,0 +1,147 @@
+use crate::game::{Class, Object};
+use crate::hook::events;
+use crate::hook::Handle;
+use crate::GLOBAL_OBJECTS;
+
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+/// How often to rescan the global objects for spawned and destroyed objects.
+const SCAN_INTERVAL: Duration = Duration::from_millis(250);
+
+// Tracked from any thread, scanned on the game thread.
+static TRACKER: Mutex<Tracker> = Mutex::new(Tracker {
+    classes: Vec::new(),
+    last_scan: None,
+});
+
+struct Tracked {
+    /// e.g., "WillowGame.WillowAIPawn".
+    name: &'static str,
+
+    /// Null if it wasn't found.
+    class: *const Class,
+
+    /// Whether `class` was looked up yet.
+    resolved: bool,
+
+    /// Live instances, keyed by object index.
+    instances: HashMap<u32, Handle<Object>>,
+}
+
+struct Tracker {
+    classes: Vec<Tracked>,
+    last_scan: Option<Instant>,
+}
+
+// Objects are only dereferenced on the game thread.
+unsafe impl Send for Tracker {}
+
+fn tracker() -> MutexGuard<'static, Tracker> {
+    TRACKER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Keep the registry up to date on the game thread.
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |_| unsafe {
+        let mut tracker = tracker();
+
+        let now = Instant::now();
+
+        if tracker.last_scan.map_or(true, |t| now - t >= SCAN_INTERVAL) {
+            tracker.last_scan = Some(now);
+            tracker.scan();
+        }
+    });
+}
+
+/// Keep a registry of the live instances of `class` and its subclasses, e.g.,
+/// "WillowGame.WillowAIPawn", so that features don't each scan every object.
+pub fn track(class: &'static str) {
+    let mut tracker = tracker();
+
+    if tracker.classes.iter().all(|t| t.name != class) {
+        tracker.classes.push(Tracked {
+            name: class,
+            class: ptr::null(),
+            resolved: false,
+            instances: HashMap::new(),
+        });
+    }
+}
+
+/// The instances of `class` seen by the last scan. Call `track` first.
+///
+/// Objects destroyed since the last scan are still listed, but their handles
+/// no longer resolve.
+pub fn instances(class: &str) -> Vec<Handle<Object>> {
+    tracker()
+        .classes
+        .iter()
+        .find(|t| t.name == class)
+        .map_or_else(Vec::new, |t| t.instances.values().copied().collect())
+}
+
+/// Stop tracking every class.
+pub fn clear() {
+    let mut tracker = tracker();
+    tracker.classes.clear();
+    tracker.last_scan = None;
+}
+
+impl Tracker {
+    unsafe fn scan(&mut self) {
+        if self.classes.iter().any(|t| !t.resolved) {
+            self.resolve();
+        }
+
+        let mut seen = vec![HashMap::new(); self.classes.len()];
+
+        for object in GLOBAL_OBJECTS.get().iter() {
+            if (*object).is_default_object() || (*object).is_pending_kill() {
+                continue;
+            }
+
+            for (tracked, seen) in self.classes.iter().zip(&mut seen) {
+                if !tracked.class.is_null() && (*object).is(tracked.class) {
+                    seen.insert((*object).index, Handle::new(object));
+                }
+            }
+        }
+
+        for (tracked, seen) in self.classes.iter_mut().zip(seen) {
+            tracked.instances = seen;
+        }
+    }
+
+    /// Look up the classes not found yet, in one pass over the global objects.
+    unsafe fn resolve(&mut self) {
+        let mut wanted: HashMap<String, &mut Tracked> = self
+            .classes
+            .iter_mut()
+            .filter(|t| !t.resolved)
+            .map(|t| {
+                t.resolved = true;
+                (format!("Class {}", t.name), t)
+            })
+            .collect();
+
+        for object in GLOBAL_OBJECTS.get().iter() {
+            if wanted.is_empty() {
+                break;
+            }
+
+            if let Some(tracked) = (*object).full_name().and_then(|n| wanted.remove(&n)) {
+                tracked.class = object.cast();
+            }
+        }
+
+        for (name, _) in wanted {
+            warn!("Unable to find \"{}\" to track its instances.", name);
+        }
+    }
+}
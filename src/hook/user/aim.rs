@@ -0,0 +1,86 @@
+use crate::game::{Object, Vector};
+use crate::hook::events::Context;
+use crate::hook::reflect::{self, Value};
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, kill, PlayerContext};
+
+use std::sync::{Mutex, PoisonError};
+
+use log::info;
+use winapi::um::winuser::VK_F3;
+
+/// Only enemies within this many degrees of the crosshair are aimed at. Set
+/// from the console thread.
+static CONE: Mutex<f32> = Mutex::new(15.0);
+
+/// Turn toward the enemy closest to the crosshair whenever the player starts
+/// firing, so that the first shot lands.
+pub unsafe fn subscribe() {
+    feature::register("aim", VK_F3, Aim);
+}
+
+struct Aim;
+
+impl Feature for Aim {
+    fn name(&self) -> &'static str {
+        "Aim assist"
+    }
+
+    unsafe fn on_event(&mut self, name: &str, _: &mut Context) -> Result<(), reflect::Error> {
+        match user::context() {
+            Some(context) if name == "StartFire" => aim(context),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn set_cone(degrees: f32) {
+    *CONE.lock().unwrap_or_else(PoisonError::into_inner) = degrees;
+    info!("Aim assist reaches {} degrees from the crosshair.", degrees);
+}
+
+unsafe fn aim(context: PlayerContext) -> Result<(), reflect::Error> {
+    let pawn = match context.pawn {
+        Some(pawn) => pawn.cast::<Object>(),
+        None => return Ok(()),
+    };
+
+    let controller = context.controller.cast::<Object>();
+    let eye_height = (*pawn).get_property("BaseEyeHeight")?;
+    let eye = user::location(pawn)? + Vector::new(0.0, 0.0, eye_height);
+    let forward = user::rotation(controller)?.to_direction();
+    let cone = *CONE.lock().unwrap_or_else(PoisonError::into_inner);
+    let cone = cone.to_radians().cos();
+
+    let mut best: Option<(f32, Vector)> = None;
+
+    for enemy in kill::hostiles(pawn)? {
+        let direction = (user::location(enemy)? - eye).normalize();
+        let alignment = direction.dot(forward);
+
+        if alignment < cone
+            || best.map_or(false, |(b, _)| alignment <= b)
+            || !can_see(controller, enemy)?
+        {
+            continue;
+        }
+
+        best = Some((alignment, direction));
+    }
+
+    match best {
+        Some((_, direction)) => user::set_rotation(controller, direction.to_rotator()),
+        None => Ok(()),
+    }
+}
+
+/// Whether `controller` has a line of sight to `pawn`.
+unsafe fn can_see(controller: *mut Object, pawn: *mut Object) -> Result<bool, reflect::Error> {
+    let seen = reflect::call_by_name(
+        "Engine.Controller.CanSee",
+        controller,
+        &[Value::Object(pawn)],
+    )?;
+
+    Ok(matches!(seen, Some(Value::Bool(true))))
+}
@@ -0,0 +1,57 @@
+use crate::game::Object;
+use crate::hook::reflect::{self, Value};
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+
+use winapi::um::winuser::VK_NUMPAD1;
+
+/// Every ammo type has a pool, grenades included.
+const AMMO_POOL: &str = "WillowGame.AmmoResourcePool";
+
+/// Keep the equipped weapon's magazine and every ammo pool full, so that the
+/// player never reloads or runs out of grenades. As the host of a co-op game,
+/// this fills the other players' pools too.
+pub unsafe fn subscribe() {
+    tracker::track(AMMO_POOL);
+    feature::register("ammo", VK_NUMPAD1, Ammo);
+}
+
+struct Ammo;
+
+impl Feature for Ammo {
+    fn name(&self) -> &'static str {
+        "Infinite ammo"
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        refill(context)
+    }
+}
+
+unsafe fn refill(context: PlayerContext) -> Result<(), reflect::Error> {
+    if let Some(pawn) = context.pawn {
+        let weapon: *mut Object = (*pawn.cast::<Object>()).get_property("Weapon")?;
+
+        if !weapon.is_null() {
+            reflect::call_by_name("WillowGame.WillowWeapon.RefillClip", weapon, &[])?;
+        }
+    }
+
+    for pool in tracker::instances(AMMO_POOL) {
+        if let Some(pool) = pool.get() {
+            let max = reflect::call_by_name("Engine.ResourcePool.GetMaxValue", pool, &[])?;
+
+            if let Some(Value::Float(max)) = max {
+                let current: f32 = (*pool).get_property("CurrentValue")?;
+
+                if current < max {
+                    let value = [Value::Float(max)];
+                    reflect::call_by_name("Engine.ResourcePool.SetCurrentValue", pool, &value)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
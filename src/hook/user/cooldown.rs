@@ -0,0 +1,33 @@
+use crate::hook::reflect;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+
+use winapi::um::winuser::VK_NUMPAD0;
+
+/// Keep the action skill off cooldown, so that it's ready again as soon as it
+/// ends.
+pub unsafe fn subscribe() {
+    feature::register("cooldown", VK_NUMPAD0, Cooldown);
+}
+
+struct Cooldown;
+
+impl Feature for Cooldown {
+    fn name(&self) -> &'static str {
+        "No skill cooldown"
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        reset(context)
+    }
+}
+
+unsafe fn reset(context: PlayerContext) -> Result<(), reflect::Error> {
+    let controller = context.controller.cast();
+    reflect::call_by_name(
+        "WillowGame.WillowPlayerController.ResetSkillCooldown",
+        controller,
+        &[],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use crate::game::{Color, Object};
+use crate::hook::reflect;
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, kill::AI_PAWN};
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::info;
+use winapi::um::winuser::VK_DIVIDE;
+
+/// How long a hit marker stays up.
+const HIT_DURATION: Duration = Duration::from_millis(200);
+
+/// Hit markers are the crosshair's arms turned 45 degrees, in this color.
+const HIT_COLOR: Color = Color::rgb(255, 64, 64);
+
+/// Set from the console thread.
+static STYLE: Mutex<Style> = Mutex::new(Style {
+    size: 8.0,
+    gap: 3.0,
+    color: Color::WHITE,
+});
+
+#[derive(Clone, Copy)]
+struct Style {
+    /// How long each arm is, in pixels.
+    size: f32,
+
+    /// How far the arms start from the center, in pixels.
+    gap: f32,
+
+    color: Color,
+}
+
+struct Hits {
+    /// The health of each AI pawn last frame, by object index.
+    health: Option<HashMap<u32, i32>>,
+
+    /// When an AI pawn last lost health.
+    last: Option<Instant>,
+}
+
+fn style() -> MutexGuard<'static, Style> {
+    STYLE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Draw a crosshair in the middle of the screen, with a marker for a moment
+/// after each hit.
+pub unsafe fn subscribe() {
+    tracker::track(AI_PAWN);
+    let hits = Hits {
+        health: None,
+        last: None,
+    };
+
+    feature::register("crosshair", VK_DIVIDE, Crosshair { hits });
+}
+
+struct Crosshair {
+    hits: Hits,
+}
+
+impl Feature for Crosshair {
+    fn name(&self) -> &'static str {
+        "Crosshair"
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        // Health lost while off isn't a hit.
+        self.hits.health = None;
+        Ok(())
+    }
+
+    unsafe fn on_render(&mut self, canvas: *mut Object) -> Result<(), reflect::Error> {
+        draw(canvas, &mut self.hits)
+    }
+}
+
+pub fn set_size(size: f32) {
+    style().size = size;
+    info!("The crosshair's arms are {} pixels.", size);
+}
+
+pub fn set_gap(gap: f32) {
+    style().gap = gap;
+    info!("The crosshair's gap is {} pixels.", gap);
+}
+
+pub fn set_color(color: Color) {
+    style().color = color;
+    info!("The crosshair is {:?}.", color);
+}
+
+unsafe fn draw(canvas: *mut Object, hits: &mut Hits) -> Result<(), reflect::Error> {
+    if canvas.is_null() {
+        return Err(reflect::Error::NullObject);
+    }
+
+    let width: f32 = (*canvas).get_property("ClipX")?;
+    let height: f32 = (*canvas).get_property("ClipY")?;
+    let center = (width / 2.0, height / 2.0);
+    let Style { size, gap, color } = *style();
+
+    let arms = [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)];
+    draw_arms(canvas, center, &arms, gap, size, color)?;
+
+    if hits.update()? {
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        let arms = [
+            (diagonal, diagonal),
+            (-diagonal, diagonal),
+            (diagonal, -diagonal),
+            (-diagonal, -diagonal),
+        ];
+
+        draw_arms(canvas, center, &arms, gap + size, size, HIT_COLOR)?;
+    }
+
+    Ok(())
+}
+
+/// Draw a line along each direction in `arms`, from `gap` to `gap + size`
+/// pixels away from `center`.
+unsafe fn draw_arms(
+    canvas: *mut Object,
+    (x, y): (f32, f32),
+    arms: &[(f32, f32)],
+    gap: f32,
+    size: f32,
+    color: Color,
+) -> Result<(), reflect::Error> {
+    for &(dx, dy) in arms {
+        let start = (x + dx * gap, y + dy * gap);
+        let end = (x + dx * (gap + size), y + dy * (gap + size));
+
+        user::line(canvas, start, end, color)?;
+    }
+
+    Ok(())
+}
+
+impl Hits {
+    /// Whether an AI pawn lost health recently. The game deals damage through
+    /// script-to-script calls, which `ProcessEvent` doesn't see, so hits are
+    /// found by comparing each pawn's health with the last frame's.
+    unsafe fn update(&mut self) -> Result<bool, reflect::Error> {
+        let mut health = HashMap::new();
+        let mut hit = false;
+
+        for pawn in tracker::instances(AI_PAWN) {
+            if let Some(object) = pawn.get() {
+                let now: i32 = (*object).get_property("Health")?;
+                let before = self.health.as_ref().and_then(|h| h.get(&pawn.index()));
+
+                if before.map_or(false, |&before| now < before) {
+                    hit = true;
+                }
+
+                health.insert(pawn.index(), now);
+            }
+        }
+
+        if hit {
+            self.last = Some(Instant::now());
+        }
+
+        self.health = Some(health);
+        Ok(self.last.map_or(false, |t| t.elapsed() < HIT_DURATION))
+    }
+}
@@ -0,0 +1,108 @@
+use crate::game::Object;
+use crate::hook::reflect::{self, Value};
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+
+use log::info;
+use winapi::um::winuser::VK_F4;
+
+/// The Kismet action that plays a matinee, which is what cutscenes are.
+const INTERP: &str = "Engine.SeqAct_Interp";
+
+/// What plays a speaker's lines outside of cutscenes, e.g., quest givers and
+/// ECHO recordings.
+const DIALOG: &str = "GearboxFramework.GearboxDialogComponent";
+const IS_TALKING: &str = "GearboxFramework.GearboxDialogComponent.IsTalking";
+const STOP_TALKING: &str = "GearboxFramework.GearboxDialogComponent.StopTalking";
+
+/// Run each skippable cutscene to its end as soon as it plays, and cut off
+/// dialog as soon as it starts. The Kismet that waits for either carries on
+/// as if it was watched.
+pub unsafe fn subscribe() {
+    tracker::track(INTERP);
+    tracker::track(DIALOG);
+    feature::register("cutscenes", VK_F4, Cutscene);
+}
+
+struct Cutscene;
+
+impl Feature for Cutscene {
+    fn name(&self) -> &'static str {
+        "Cutscene and dialog skipper"
+    }
+
+    unsafe fn on_tick(&mut self, _context: PlayerContext) -> Result<(), reflect::Error> {
+        skip()?;
+        stop_dialog()
+    }
+}
+
+unsafe fn skip() -> Result<(), reflect::Error> {
+    for interp in tracker::instances(INTERP) {
+        if let Some(interp) = interp.get() {
+            if (*interp).get_property("bIsPlaying")? && (*interp).get_property("bIsSkippable")? {
+                skip_to_end(interp)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+unsafe fn skip_to_end(interp: *mut Object) -> Result<(), reflect::Error> {
+    let data = reflect::call_by_name(
+        "Engine.SeqAct_Interp.FindInterpDataFromVariable",
+        interp,
+        &[],
+    )?;
+
+    let data = match data {
+        Some(Value::Object(data)) if !data.is_null() => data,
+        _ => return Ok(()),
+    };
+
+    let length: f32 = (*data).get_property("InterpLength")?;
+
+    // It stops on its next update once it's at the end.
+    if (*interp).get_property::<f32>("Position")? >= length {
+        return Ok(());
+    }
+
+    // Not jumping, so that the event tracks in between fire their events, which
+    // quest Kismet waits for.
+    let arguments = [Value::Float(length), Value::Bool(false)];
+    reflect::call_by_name("Engine.SeqAct_Interp.SetPosition", interp, &arguments)?;
+
+    info!("Skipped a {:.1} second cutscene.", length);
+    Ok(())
+}
+
+unsafe fn stop_dialog() -> Result<(), reflect::Error> {
+    let is_talking = reflect::find_function(IS_TALKING)
+        .ok_or_else(|| reflect::Error::FunctionNotFound(IS_TALKING.to_owned()))?;
+
+    let stop_talking = reflect::find_function(STOP_TALKING)
+        .ok_or_else(|| reflect::Error::FunctionNotFound(STOP_TALKING.to_owned()))?;
+
+    let return_value = (*is_talking)
+        .property_map()
+        .get("ReturnValue")
+        .copied()
+        .ok_or_else(|| reflect::Error::PropertyNotFound(String::from("ReturnValue")))?;
+
+    for dialog in tracker::instances(DIALOG) {
+        if let Some(dialog) = dialog.get() {
+            // Leaving the optional parameters out asks about, and stops, any
+            // line rather than a particular one.
+            let parameters = reflect::call_named(dialog, is_talking, &[])?;
+
+            if let Some(Value::Bool(true)) = return_value.read(parameters.as_ptr()) {
+                reflect::call_named(dialog, stop_talking, &[])?;
+                info!("Skipped a line of dialog.");
+            }
+        }
+    }
+
+    Ok(())
+}
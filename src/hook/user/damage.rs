@@ -0,0 +1,117 @@
+use crate::game::{self, Array, Object};
+use crate::hook::reflect;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+use crate::hook::Handle;
+
+use std::sync::{Mutex, PoisonError};
+
+use log::info;
+use winapi::um::winuser::VK_NUMPAD6;
+
+/// The weapon property with the damage of each fire mode.
+const DAMAGE: &str = "InstantHitDamage";
+
+/// How much to multiply the player's damage by. Set from the console thread.
+static FACTOR: Mutex<f32> = Mutex::new(10.0);
+
+/// A weapon whose damage was multiplied, with its damage before.
+struct Scaled {
+    weapon: Handle<Object>,
+    damage: Vec<f32>,
+}
+
+fn factor() -> f32 {
+    *FACTOR.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Multiply the damage of each weapon the player equips. A large factor kills
+/// in one hit.
+pub unsafe fn subscribe() {
+    feature::register("damage", VK_NUMPAD6, Damage { scaled: Vec::new() });
+}
+
+struct Damage {
+    /// The weapons multiplied since turning on, to restore when turned off.
+    scaled: Vec<Scaled>,
+}
+
+impl Feature for Damage {
+    fn name(&self) -> &'static str {
+        "Damage multiplier"
+    }
+
+    unsafe fn on_disable(&mut self) {
+        restore(&mut self.scaled);
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        scale(context, &mut self.scaled)
+    }
+}
+
+/// Multiply damage by `factor`, including the weapons already multiplied.
+pub fn set_factor(factor: f32) {
+    *FACTOR.lock().unwrap_or_else(PoisonError::into_inner) = factor;
+    info!("Damage is multiplied by {}.", factor);
+}
+
+unsafe fn damage(weapon: *mut Object) -> Result<&'static mut Array<f32>, reflect::Error> {
+    let damage = (*weapon)
+        .property(DAMAGE)
+        .ok_or_else(|| reflect::Error::PropertyNotFound(DAMAGE.to_owned()))?;
+
+    Ok(&mut *damage.cast())
+}
+
+/// Set the equipped weapon's damage to its original damage times the factor,
+/// so that changing the factor doesn't compound.
+unsafe fn scale(context: PlayerContext, scaled: &mut Vec<Scaled>) -> Result<(), reflect::Error> {
+    let pawn = match context.pawn {
+        Some(pawn) => &*pawn.cast::<Object>(),
+        None => return Ok(()),
+    };
+
+    let weapon: *mut Object = pawn.get_property("Weapon")?;
+
+    if weapon.is_null() {
+        return Ok(());
+    }
+
+    let damage = damage(weapon)?;
+    let handle = Handle::new(weapon);
+
+    let original = match scaled.iter().position(|s| s.weapon == handle) {
+        Some(i) => &scaled[i].damage,
+        None => {
+            scaled.push(Scaled {
+                weapon: handle,
+                damage: damage.to_vec(),
+            });
+
+            &scaled[scaled.len() - 1].damage
+        }
+    };
+
+    let factor = factor();
+
+    for (damage, original) in damage.iter_mut().zip(original) {
+        *damage = original * factor;
+    }
+
+    Ok(())
+}
+
+/// Put back the damage of every weapon still around. The damage array may
+/// have been freed with its weapon, so it's only written where writable.
+unsafe fn restore(scaled: &mut Vec<Scaled>) {
+    for scaled in scaled.drain(..) {
+        if let Some(weapon) = scaled.weapon.get() {
+            if let Ok(damage) = damage(weapon) {
+                for (damage, &original) in damage.iter_mut().zip(&scaled.damage) {
+                    game::try_write(damage, original);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::game;
+use crate::hook::reflect::Value;
+use crate::hook::scheduler::{self, TaskId};
+use crate::hook::{exec, input, passive, user};
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use thiserror::Error;
+use winapi::um::winuser::VK_F6;
+
+/// One file per sequence, e.g., "farm/chest.txt".
+const SEQUENCES: &str = "farm";
+
+/// The most times a sequence can repeat, so that a typo can't farm forever.
+const MAX_REPEATS: u32 = 1000;
+
+/// How long to wait after each step that doesn't wait itself, so that a
+/// sequence without waits can't run a step every frame.
+const STEP_DELAY: f32 = 0.1;
+
+/// How long a step may wait for a player controller, e.g., while a map
+/// loads, before the loop gives up.
+const CONTROLLER_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Started and stopped from any thread, stepped on the game thread.
+static FARM: Mutex<Option<Farm>> = Mutex::new(None);
+
+/// Numbers each started sequence, so that a step scheduled for an earlier one
+/// doesn't advance the current one.
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("line {line}: {reason}")]
+    Parse { line: usize, reason: &'static str },
+
+    #[error("the sequence has no steps")]
+    Empty,
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+enum Step {
+    /// Run an exec function as if its key was pressed, e.g.,
+    /// "exec WillowGame.WillowPlayerController.Use".
+    Exec(String, Vec<String>),
+
+    /// Run a console command as the player, e.g., "console disconnect".
+    Console(String),
+
+    /// Wait before the next step, e.g., "wait 5".
+    Wait(f32),
+}
+
+struct Farm {
+    generation: u32,
+    name: String,
+    steps: Vec<Step>,
+    repeats: u32,
+
+    /// The step to run next, and how many times the sequence finished.
+    step: usize,
+    done: u32,
+
+    /// When the current step started waiting for a controller.
+    waiting_since: Option<Instant>,
+
+    /// The scheduled next step, to cancel when stopping.
+    task: Option<TaskId>,
+}
+
+fn farm() -> MutexGuard<'static, Option<Farm>> {
+    FARM.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Bind the hard stop.
+pub fn subscribe() {
+    input::bind("stop farming", VK_F6, stop);
+}
+
+/// Run the sequence in "farm/<name>.txt" `repeats` times. Each line is a step:
+/// "exec <function> [arguments]", "console <command>", or "wait <seconds>".
+/// Lines starting with # are comments.
+pub fn start(name: &str, repeats: u32) {
+    if passive::refuses("Farming") {
+        return;
+    }
+
+    let steps = match load(name) {
+        Ok(steps) => steps,
+        Err(e) => {
+            warn!("Failed to load the farming sequence {}: {}", name, e);
+            return;
+        }
+    };
+
+    let mut guard = farm();
+    stop_farm(&mut guard);
+
+    let repeats = repeats.min(MAX_REPEATS);
+    info!(
+        "Farming {} ({} steps) {} times. The stop hotkey ends it.",
+        name,
+        steps.len(),
+        repeats
+    );
+
+    let farm = guard.insert(Farm {
+        generation: GENERATION.fetch_add(1, Ordering::SeqCst).wrapping_add(1),
+        name: name.to_owned(),
+        steps,
+        repeats,
+        step: 0,
+        done: 0,
+        waiting_since: None,
+        task: None,
+    });
+
+    schedule(farm, 0.0);
+}
+
+/// Stop the running sequence, if any.
+pub fn stop() {
+    stop_farm(&mut farm());
+}
+
+fn stop_farm(farm: &mut Option<Farm>) {
+    if let Some(farm) = farm.take() {
+        if let Some(task) = farm.task {
+            scheduler::cancel(task);
+        }
+
+        info!(
+            "Stopped farming {} after {} of {} runs.",
+            farm.name, farm.done, farm.repeats
+        );
+    }
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(SEQUENCES).join(format!("{}.txt", name))
+}
+
+fn load(name: &str) -> Result<Vec<Step>, Error> {
+    let text = fs::read_to_string(path(name))?;
+    let mut steps = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parse_error = |reason| Error::Parse {
+            line: i + 1,
+            reason,
+        };
+
+        let (command, rest) = match line.find(char::is_whitespace) {
+            Some(space) => (&line[..space], line[space..].trim()),
+            None => (line, ""),
+        };
+
+        let step = match command {
+            "exec" => {
+                let mut words = rest.split_whitespace().map(str::to_owned);
+                let function = words
+                    .next()
+                    .ok_or_else(|| parse_error("expected a function"))?;
+                Step::Exec(function, words.collect())
+            }
+
+            "console" if !rest.is_empty() => Step::Console(rest.to_owned()),
+            "console" => return Err(parse_error("expected a command")),
+
+            "wait" => match rest.parse::<f32>() {
+                Ok(seconds) if seconds >= 0.0 => Step::Wait(seconds),
+                _ => return Err(parse_error("expected seconds")),
+            },
+
+            _ => return Err(parse_error("expected exec, console, or wait")),
+        };
+
+        steps.push(step);
+    }
+
+    if steps.is_empty() {
+        Err(Error::Empty)
+    } else {
+        Ok(steps)
+    }
+}
+
+/// Schedule `farm`'s next step. Call with `FARM` locked, so that the sequence
+/// can't be replaced before it knows its task.
+fn schedule(farm: &mut Farm, seconds: f32) {
+    let generation = farm.generation;
+    let task = scheduler::after(seconds, move || unsafe { advance(generation) });
+    farm.task = Some(task);
+}
+
+/// Run the current step of sequence `generation`, then schedule the next.
+unsafe fn advance(generation: u32) {
+    let mut guard = farm();
+
+    let farm = match guard.as_mut() {
+        Some(farm) if farm.generation == generation => farm,
+        _ => return,
+    };
+
+    farm.task = None;
+
+    if farm.done >= farm.repeats {
+        info!("Finished farming {}.", farm.name);
+        *guard = None;
+        return;
+    }
+
+    let delay = match run(farm) {
+        Ok(Some(delay)) => delay,
+
+        // Try the same step again.
+        Ok(None) => 1.0,
+
+        Err(reason) => {
+            warn!("Stopped farming {}: {}", farm.name, reason);
+            *guard = None;
+            return;
+        }
+    };
+
+    schedule(farm, delay);
+}
+
+/// Run `farm`'s current step. Returns how long to wait before the next, or
+/// `None` to retry this step.
+unsafe fn run(farm: &mut Farm) -> Result<Option<f32>, String> {
+    let delay = match &farm.steps[farm.step] {
+        Step::Exec(function, arguments) => {
+            exec::synthesize(function, arguments.iter().map(|a| argument(a)).collect());
+
+            // Synthesized calls run on the next frame.
+            STEP_DELAY
+        }
+
+        Step::Console(command) => {
+            let controller = match user::controller() {
+                Some(controller) => controller,
+                None => return wait_for_controller(farm),
+            };
+
+            game::console_command(controller.cast(), command);
+            STEP_DELAY
+        }
+
+        Step::Wait(seconds) => *seconds,
+    };
+
+    farm.waiting_since = None;
+    farm.step += 1;
+
+    if farm.step == farm.steps.len() {
+        farm.step = 0;
+        farm.done += 1;
+        info!("Finished farming run {} of {}.", farm.done, farm.repeats);
+    }
+
+    Ok(Some(delay))
+}
+
+fn wait_for_controller(farm: &mut Farm) -> Result<Option<f32>, String> {
+    let since = *farm.waiting_since.get_or_insert_with(Instant::now);
+
+    if since.elapsed() > CONTROLLER_TIMEOUT {
+        Err(format!("no player controller for {:?}", CONTROLLER_TIMEOUT))
+    } else {
+        Ok(None)
+    }
+}
+
+/// An exec argument as an int or float if it's a number, or else a string.
+fn argument(text: &str) -> Value {
+    if let Ok(i) = text.parse() {
+        Value::Int(i)
+    } else if let Ok(x) = text.parse() {
+        Value::Float(x)
+    } else {
+        Value::Str(text.to_owned())
+    }
+}
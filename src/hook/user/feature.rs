@@ -0,0 +1,305 @@
+use crate::game::Object;
+use crate::hook::contain::contain;
+use crate::hook::events::{self, Action, Context};
+use crate::hook::user::{self, PlayerContext};
+use crate::hook::{exec, input, passive, reflect, scheduler};
+
+use std::fs;
+use std::io;
+use std::sync::mpsc;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use log::{info, warn};
+
+/// Features to turn on once the player is in the game, one action per line,
+/// e.g., "ammo". Lines starting with # are comments.
+const CONFIG: &str = "features.txt";
+
+/// How long `clear` waits for the game thread to turn the features off.
+const CLEAR_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Registered and run on the game thread, listed from the console thread.
+static FEATURES: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+/// A gameplay feature that its hotkey, the console, and the configuration
+/// turn on and off. Its callbacks only run while it's on, on the game thread.
+/// A callback that fails turns it off.
+pub trait Feature {
+    /// What the log calls it, e.g., "Infinite ammo".
+    fn name(&self) -> &'static str;
+
+    /// Whether it only looks at the game, so that passive mode allows it.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// The functions to call `on_event` after, e.g.,
+    /// "Engine.Camera.UpdateCamera".
+    fn events(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Called when turned on. An error leaves it off, so undo whatever part
+    /// worked.
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        Ok(())
+    }
+
+    /// Called when turned off, including by an error, to put back what it
+    /// changed.
+    unsafe fn on_disable(&mut self) {}
+
+    /// Called each time the local player's controller ticks.
+    unsafe fn on_tick(&mut self, _context: PlayerContext) -> Result<(), reflect::Error> {
+        Ok(())
+    }
+
+    /// Called with the name and context of each exec command the player runs,
+    /// e.g., "StartFire", before it runs, and of each function in `events`,
+    /// after it runs.
+    unsafe fn on_event(
+        &mut self,
+        _name: &str,
+        _context: &mut Context,
+    ) -> Result<(), reflect::Error> {
+        Ok(())
+    }
+
+    /// Called each frame with the canvas to draw on.
+    unsafe fn on_render(&mut self, _canvas: *mut Object) -> Result<(), reflect::Error> {
+        Ok(())
+    }
+}
+
+struct Entry {
+    /// Names the feature's hotkey, and the feature in the console and the
+    /// configuration, e.g., "ammo".
+    action: &'static str,
+
+    feature: Box<dyn Feature>,
+    enabled: bool,
+
+    /// Set by the configuration, to turn on once there's a player.
+    pending: bool,
+
+    /// Set once a callback crashes, so that it's never turned on again.
+    crashed: bool,
+}
+
+// Features are only run on the game thread.
+unsafe impl Send for Entry {}
+
+fn features() -> MutexGuard<'static, Vec<Entry>> {
+    FEATURES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run the features' callbacks.
+pub unsafe fn subscribe() {
+    user::on_player_tick(|context| {
+        for entry in features().iter_mut() {
+            if entry.pending {
+                entry.pending = false;
+                entry.enable();
+            }
+
+            if entry.enabled {
+                entry.run(|feature| unsafe { feature.on_tick(context) });
+            }
+        }
+    });
+
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |e| {
+        let canvas: *mut Object = *e.parameters.cast();
+
+        for entry in features().iter_mut().filter(|e| e.enabled) {
+            entry.run(|feature| unsafe { feature.on_render(canvas) });
+        }
+    });
+
+    exec::on_exec(|name, context| {
+        for entry in features().iter_mut().filter(|e| e.enabled) {
+            entry.run(|feature| unsafe { feature.on_event(name, context) });
+        }
+
+        Action::Continue
+    });
+}
+
+/// Add `feature`, off, with a hotkey on `key` called `action`.
+pub unsafe fn register(action: &'static str, key: i32, feature: impl Feature + 'static) {
+    let mut registry = features();
+    let index = registry.len();
+
+    for &event in feature.events() {
+        events::after(event, move |context| {
+            let mut features = features();
+
+            // `clear` empties the registry before the detours come off.
+            let entry = match features.get_mut(index) {
+                Some(entry) => entry,
+                None => return,
+            };
+
+            if entry.enabled {
+                entry.run(|feature| unsafe { feature.on_event(event, context) });
+            }
+        });
+    }
+
+    registry.push(Entry {
+        action,
+        feature: Box::new(feature),
+        enabled: false,
+        pending: false,
+        crashed: false,
+    });
+
+    input::bind(action, key, move || unsafe { toggle(action) });
+}
+
+/// Turn the feature called `action` on or off. Call on the game thread.
+pub unsafe fn toggle(action: &str) {
+    let mut features = features();
+
+    match features.iter_mut().find(|e| e.action == action) {
+        Some(entry) if entry.enabled => entry.disable(),
+        Some(entry) => entry.enable(),
+        None => warn!("There's no feature called \"{}\".", action),
+    }
+}
+
+/// Turn the feature called `action` on or off unless it already is. Call on
+/// the game thread.
+pub unsafe fn set(action: &str, enabled: bool) {
+    let mut features = features();
+
+    match features.iter_mut().find(|e| e.action == action) {
+        Some(entry) if entry.enabled == enabled => {}
+        Some(entry) if enabled => entry.enable(),
+        Some(entry) => entry.disable(),
+        None => warn!("There's no feature called \"{}\".", action),
+    }
+}
+
+/// Turn off every feature that's on, on the game thread, so that unloading
+/// leaves the game as it was, then forget them. Call from another thread
+/// while the detours are still attached.
+pub fn clear() {
+    let (done, finished) = mpsc::channel();
+
+    scheduler::after_frames(0, move || {
+        for entry in features().iter_mut().filter(|e| e.enabled) {
+            unsafe { entry.disable() };
+        }
+
+        let _ = done.send(());
+    });
+
+    if finished.recv_timeout(CLEAR_TIMEOUT).is_err() {
+        warn!(
+            "The game thread didn't turn off the features within {:?}.",
+            CLEAR_TIMEOUT
+        );
+    }
+
+    features().clear();
+}
+
+/// Turn on the features that the configuration lists, once there's a player.
+pub fn load() {
+    let config = match fs::read_to_string(CONFIG) {
+        Ok(config) => config,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return,
+        Err(e) => {
+            warn!("Failed to read {}: {}", CONFIG, e);
+            return;
+        }
+    };
+
+    let mut features = features();
+
+    for action in config.lines().map(str::trim) {
+        if action.is_empty() || action.starts_with('#') {
+            continue;
+        }
+
+        match features.iter_mut().find(|e| e.action == action) {
+            Some(entry) => entry.pending = true,
+            None => warn!("{} lists \"{}\", which isn't a feature.", CONFIG, action),
+        }
+    }
+}
+
+pub fn show() {
+    for entry in features().iter() {
+        let state = if entry.enabled { "on" } else { "off" };
+        info!(
+            "{:>12} -> {} is {}.",
+            entry.action,
+            entry.feature.name(),
+            state
+        );
+    }
+}
+
+impl Entry {
+    unsafe fn enable(&mut self) {
+        let name = self.feature.name();
+
+        if self.crashed {
+            warn!("{} crashed, so it stays off.", name);
+            return;
+        }
+
+        if !self.feature.is_read_only() && passive::refuses(name) {
+            return;
+        }
+
+        let feature = &mut *self.feature;
+
+        match contain(name, || unsafe { feature.on_enable() }) {
+            Some(Ok(())) => {
+                self.enabled = true;
+                info!("{} is on.", name);
+            }
+            Some(Err(e)) => warn!("Failed to turn on {}: {}", name, e),
+            None => self.crashed = true,
+        }
+    }
+
+    unsafe fn disable(&mut self) {
+        let name = self.feature.name();
+        let feature = &mut *self.feature;
+        self.enabled = false;
+
+        if contain(name, || unsafe { feature.on_disable() }).is_none() {
+            self.crashed = true;
+        }
+
+        info!("{} is off.", name);
+    }
+
+    /// Run one of the feature's callbacks, and turn the feature off if it
+    /// fails, e.g., because this game doesn't have a property it writes,
+    /// rather than logging the same error every frame.
+    unsafe fn run(
+        &mut self,
+        callback: impl FnOnce(&mut dyn Feature) -> Result<(), reflect::Error>,
+    ) {
+        let name = self.feature.name();
+        let feature = &mut *self.feature;
+
+        match contain(name, || callback(feature)) {
+            Some(Ok(())) => {}
+            Some(Err(e)) => {
+                warn!("{} failed, so it's off: {}", name, e);
+                self.disable();
+            }
+            None => {
+                self.enabled = false;
+                self.crashed = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::game::Object;
+use crate::hook::reflect::{self, Value};
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, PlayerContext};
+
+use std::sync::{Mutex, PoisonError};
+
+use log::{info, warn};
+use winapi::um::winuser::VK_NUMPAD3;
+
+/// `EPhysics` values.
+const PHYS_FALLING: u8 = 2;
+pub(super) const PHYS_FLYING: u8 = 4;
+
+/// How fast to fly, in unreal units per second. Set from the console thread.
+static SPEED: Mutex<f32> = Mutex::new(2000.0);
+
+/// Fly through walls. Flying survives respawns, since each tick puts a new
+/// pawn back in the air.
+pub unsafe fn subscribe() {
+    feature::register("fly", VK_NUMPAD3, Fly { air_speed: None });
+}
+
+struct Fly {
+    /// The pawn's air speed before flying, to restore when landing.
+    air_speed: Option<f32>,
+}
+
+impl Feature for Fly {
+    fn name(&self) -> &'static str {
+        "Fly"
+    }
+
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        match user::context() {
+            Some(context) => self.set_flying(context, true),
+            None => Ok(()),
+        }
+    }
+
+    unsafe fn on_disable(&mut self) {
+        if let Some(context) = user::context() {
+            if let Err(e) = self.set_flying(context, false) {
+                warn!("Failed to land: {}", e);
+            }
+        }
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        self.keep_flying(context)
+    }
+}
+
+pub fn set_speed(speed: f32) {
+    *SPEED.lock().unwrap_or_else(PoisonError::into_inner) = speed;
+    info!("Fly speed is {}.", speed);
+}
+
+impl Fly {
+    unsafe fn keep_flying(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        let pawn = match context.pawn {
+            Some(pawn) => &mut *pawn.cast::<Object>(),
+            None => return Ok(()),
+        };
+
+        if pawn.get_property::<u8>("Physics")? != PHYS_FLYING {
+            self.set_flying(context, true)?;
+        }
+
+        let speed = *SPEED.lock().unwrap_or_else(PoisonError::into_inner);
+        pawn.set_property("AirSpeed", speed)
+    }
+
+    unsafe fn set_flying(
+        &mut self,
+        context: PlayerContext,
+        flying: bool,
+    ) -> Result<(), reflect::Error> {
+        let pawn: *mut Object = match context.pawn {
+            Some(pawn) => pawn.cast(),
+            None => return Ok(()),
+        };
+
+        if flying {
+            self.air_speed
+                .get_or_insert((*pawn).get_property("AirSpeed")?);
+        } else if let Some(speed) = self.air_speed.take() {
+            (*pawn).set_property("AirSpeed", speed)?;
+        }
+
+        let arguments = [
+            Value::Bool(!flying),
+            Value::Bool(!flying),
+            Value::Bool(false),
+        ];
+
+        reflect::call_by_name("Engine.Actor.SetCollision", pawn, &arguments)?;
+        (*pawn).set_property("bCollideWorld", !flying)?;
+
+        let physics = if flying { PHYS_FLYING } else { PHYS_FALLING };
+        reflect::call_by_name("Engine.Actor.SetPhysics", pawn, &[Value::Byte(physics)])?;
+
+        let state = if flying {
+            "PlayerFlying"
+        } else {
+            "PlayerWalking"
+        };
+
+        user::goto_state(context.controller.cast(), state)
+    }
+}
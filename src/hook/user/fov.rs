@@ -0,0 +1,126 @@
+use crate::game::Object;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, PlayerContext};
+use crate::hook::{passive, reflect, scheduler};
+
+use std::sync::{Mutex, PoisonError};
+
+use log::{info, warn};
+use winapi::um::winuser::VK_F9;
+
+/// The field of view to keep, in degrees, or `None` to leave the game's. Set
+/// from the console and overlay threads.
+static FOV: Mutex<Option<f32>> = Mutex::new(None);
+
+/// The game's own field of view, for the overlay to start from.
+pub const DEFAULT: f32 = 90.0;
+
+/// Keep the field of view at the override. The game resets it when zooming
+/// out and respawning, so it's reapplied every tick.
+pub unsafe fn subscribe() {
+    feature::register("fov", VK_F9, Fov { original: None });
+}
+
+struct Fov {
+    /// The game's own fields of view, saved when the override starts so that
+    /// stopping it can put them back.
+    original: Option<Original>,
+}
+
+struct Original {
+    controller: f32,
+
+    /// `None` if there was no camera yet.
+    camera: Option<f32>,
+}
+
+impl Feature for Fov {
+    fn name(&self) -> &'static str {
+        "The field of view override"
+    }
+
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        FOV.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(DEFAULT);
+        Ok(())
+    }
+
+    unsafe fn on_disable(&mut self) {
+        *FOV.lock().unwrap_or_else(PoisonError::into_inner) = None;
+
+        if let (Some(original), Some(context)) = (self.original.take(), user::context()) {
+            if let Err(e) = restore(context, &original) {
+                warn!("Failed to put back the game's field of view: {}", e);
+            }
+        }
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        if self.original.is_none() {
+            self.original = Some(save(context)?);
+        }
+
+        apply(context, get().unwrap_or(DEFAULT))
+    }
+}
+
+pub fn get() -> Option<f32> {
+    *FOV.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Override the field of view with `fov` degrees, or stop with `None`.
+pub fn set(fov: Option<f32>) {
+    if fov.is_some() && passive::refuses("Overriding the field of view") {
+        return;
+    }
+
+    *FOV.lock().unwrap_or_else(PoisonError::into_inner) = fov;
+
+    if let Some(fov) = fov {
+        info!("The field of view override is {} degrees.", fov);
+    }
+
+    scheduler::after_frames(0, move || unsafe { feature::set("fov", fov.is_some()) });
+}
+
+/// Zooming scopes from the default, so overriding it keeps weapon zoom
+/// working.
+unsafe fn apply(context: PlayerContext, fov: f32) -> Result<(), reflect::Error> {
+    let controller = &mut *context.controller.cast::<Object>();
+    controller.set_property("DefaultFOV", fov)?;
+
+    let camera: *mut Object = controller.get_property("PlayerCamera")?;
+
+    if let Some(camera) = camera.as_mut() {
+        camera.set_property("DefaultFOV", fov)?;
+    }
+
+    Ok(())
+}
+
+unsafe fn save(context: PlayerContext) -> Result<Original, reflect::Error> {
+    let controller = &*context.controller.cast::<Object>();
+    let camera: *mut Object = controller.get_property("PlayerCamera")?;
+
+    Ok(Original {
+        controller: controller.get_property("DefaultFOV")?,
+        camera: match camera.as_ref() {
+            Some(camera) => Some(camera.get_property("DefaultFOV")?),
+            None => None,
+        },
+    })
+}
+
+unsafe fn restore(context: PlayerContext, original: &Original) -> Result<(), reflect::Error> {
+    let controller = &mut *context.controller.cast::<Object>();
+    controller.set_property("DefaultFOV", original.controller)?;
+
+    let camera: *mut Object = controller.get_property("PlayerCamera")?;
+
+    if let (Some(camera), Some(fov)) = (camera.as_mut(), original.camera) {
+        camera.set_property("DefaultFOV", fov)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use crate::game::{Object, Vector};
+use crate::hook::events::Context;
+use crate::hook::input;
+use crate::hook::reflect::{self, Value};
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, PlayerContext};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use log::{info, warn};
+use winapi::um::winuser::{VK_CONTROL, VK_DECIMAL, VK_SHIFT, VK_SPACE};
+
+const UPDATE_CAMERA: &str = "Engine.Camera.UpdateCamera";
+
+/// `CameraCache` is a `TCameraCacheEntry`, whose `POV` follows a float
+/// timestamp. The `POV` starts with its location.
+const POV_LOCATION_OFFSET: usize = 4;
+
+/// How much faster the camera moves while shift is held.
+const FAST: f32 = 4.0;
+
+/// How fast the camera flies, in unreal units per second. Set from the
+/// console thread.
+static SPEED: Mutex<f32> = Mutex::new(1000.0);
+
+/// Whether to hide the HUD while the camera is free. Set from the console
+/// thread.
+static HIDE_HUD: AtomicBool = AtomicBool::new(true);
+
+/// Fly the view away from the pawn, which stays put, e.g., to take pictures.
+/// The mouse still turns the view, WASD moves it, and space and control raise
+/// and lower it.
+pub unsafe fn subscribe() {
+    feature::register("free camera", VK_DECIMAL, Freecam { location: None });
+}
+
+struct Freecam {
+    /// Where the free camera is, once it's started.
+    location: Option<Vector>,
+}
+
+impl Feature for Freecam {
+    fn name(&self) -> &'static str {
+        "Free camera"
+    }
+
+    fn events(&self) -> &'static [&'static str] {
+        &[UPDATE_CAMERA]
+    }
+
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        let context = user::context().ok_or(reflect::Error::NullObject)?;
+        let result = self.start(context);
+
+        if result.is_err() {
+            self.stop(context);
+        }
+
+        result
+    }
+
+    unsafe fn on_disable(&mut self) {
+        if let Some(context) = user::context() {
+            self.stop(context);
+        }
+    }
+
+    unsafe fn on_event(&mut self, name: &str, e: &mut Context) -> Result<(), reflect::Error> {
+        match user::context() {
+            Some(context) if name == UPDATE_CAMERA => {
+                let delta: f32 = *e.parameters.cast();
+                self.fly(context, e.this, delta)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn set_speed(speed: f32) {
+    *SPEED.lock().unwrap_or_else(PoisonError::into_inner) = speed;
+    info!("The free camera flies at {}.", speed);
+}
+
+pub fn set_hide_hud(hide: bool) {
+    HIDE_HUD.store(hide, Ordering::SeqCst);
+    info!("Hide the HUD in the free camera: {}.", hide);
+}
+
+impl Freecam {
+    unsafe fn start(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        let location = *pov_location(camera(context)?)?;
+
+        ignore_move_input(context, true)?;
+        self.location = Some(location);
+
+        if HIDE_HUD.load(Ordering::SeqCst) {
+            show_hud(context, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Put back what `start` changed, even if it only partly worked.
+    unsafe fn stop(&mut self, context: PlayerContext) {
+        if self.location.take().is_some() {
+            if let Err(e) = ignore_move_input(context, false).and_then(|_| show_hud(context, true))
+            {
+                warn!("Failed to put the camera back: {}", e);
+            }
+        }
+    }
+
+    /// Move the camera by the held keys, then show the view from there.
+    unsafe fn fly(
+        &mut self,
+        context: PlayerContext,
+        camera: *mut Object,
+        delta: f32,
+    ) -> Result<(), reflect::Error> {
+        let controller = &*context.controller.cast::<Object>();
+
+        let player_camera: *mut Object = controller.get_property("PlayerCamera")?;
+
+        if camera != player_camera {
+            return Ok(());
+        }
+
+        let (forward, right, _) = user::rotation(context.controller.cast())?.axes();
+        let up = Vector::new(0.0, 0.0, 1.0);
+
+        let axis = |positive, negative| match (input::is_down(positive), input::is_down(negative)) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+
+        let direction = forward * axis(i32::from(b'W'), i32::from(b'S'))
+            + right * axis(i32::from(b'D'), i32::from(b'A'))
+            + up * axis(VK_SPACE, VK_CONTROL);
+
+        let mut speed = *SPEED.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if input::is_down(VK_SHIFT) {
+            speed *= FAST;
+        }
+
+        let location = self.location.get_or_insert(*pov_location(camera)?);
+        *location = *location + direction.normalize() * speed * delta;
+
+        *pov_location(camera)? = *location;
+        Ok(())
+    }
+}
+
+unsafe fn camera(context: PlayerContext) -> Result<*mut Object, reflect::Error> {
+    let camera: *mut Object =
+        (*context.controller.cast::<Object>()).get_property("PlayerCamera")?;
+
+    if camera.is_null() {
+        Err(reflect::Error::NullObject)
+    } else {
+        Ok(camera)
+    }
+}
+
+/// The location of the view that the camera last computed, which is what the
+/// game renders from.
+unsafe fn pov_location(camera: *mut Object) -> Result<&'static mut Vector, reflect::Error> {
+    let cache = (*camera)
+        .property("CameraCache")
+        .ok_or_else(|| reflect::Error::PropertyNotFound("CameraCache".to_owned()))?;
+
+    Ok(&mut *cache.add(POV_LOCATION_OFFSET).cast())
+}
+
+/// Keep the pawn still while the camera flies. The engine counts these, so
+/// every `true` needs a `false`.
+unsafe fn ignore_move_input(context: PlayerContext, ignore: bool) -> Result<(), reflect::Error> {
+    let controller = context.controller.cast();
+    let arguments = [Value::Bool(ignore)];
+    reflect::call_by_name("Engine.Controller.IgnoreMoveInput", controller, &arguments)?;
+    Ok(())
+}
+
+unsafe fn show_hud(context: PlayerContext, show: bool) -> Result<(), reflect::Error> {
+    match context.hud {
+        Some(hud) => (*hud.cast::<Object>()).set_property("bShowHUD", show),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::game::{Array, Object};
+use crate::hook::reflect::{self, Value};
+use crate::hook::user;
+use crate::hook::{gmalloc, passive};
+use crate::GLOBAL_OBJECTS;
+
+use std::ptr;
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// A static function, so it's called on the class default object.
+const ITEM_POOL: &str = "ItemPool WillowGame.Default__ItemPool";
+const SPAWN: &str = "WillowGame.ItemPool.SpawnBalancedInventoryFromInventoryBalanceDefinition";
+const ADD_TO_BACKPACK: &str = "WillowGame.WillowInventoryManager.AddInventoryToBackpack";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("reflect error: {0}")]
+    Reflect(#[from] reflect::Error),
+
+    #[error("there's no pawn to give items to")]
+    NoPawn,
+
+    #[error("no object named \"{0}\"")]
+    NotFound(String),
+}
+
+/// Spawn an item from `definition`, the path name of a balance definition
+/// like "GD_Weap_Pistol.A_Weapons.Pistol_Maliwan_5_Infection", at `level`,
+/// and put it in the player's backpack. Call on the game thread.
+pub unsafe fn give(definition: &str, level: i32) {
+    if passive::refuses("Giving items") {
+        return;
+    }
+
+    match try_give(definition, level) {
+        Ok(count) => info!("Gave {} {} at level {}.", count, definition, level),
+        Err(e) => warn!("Failed to give {}: {}", definition, e),
+    }
+}
+
+unsafe fn try_give(definition: &str, level: i32) -> Result<usize, Error> {
+    let context = user::context().ok_or(Error::NoPawn)?;
+    let pawn: *mut Object = context.pawn.ok_or(Error::NoPawn)?.cast();
+    let objects = GLOBAL_OBJECTS.get();
+
+    let balance = objects
+        .find_matching(&format!("* {}", definition))
+        .find(|&o| !(*o).is_default_object())
+        .ok_or_else(|| Error::NotFound(definition.to_owned()))?;
+
+    let item_pool = objects
+        .find_mut(ITEM_POOL)
+        .ok_or_else(|| Error::NotFound(ITEM_POOL.to_owned()))?;
+
+    let spawn = reflect::find_function(SPAWN)
+        .ok_or_else(|| reflect::Error::FunctionNotFound(SPAWN.to_owned()))?;
+
+    let arguments = [
+        ("InvBalanceDefinition", Value::Object(balance)),
+        ("Quantity", Value::Int(1)),
+        ("GameStage", Value::Int(level)),
+        ("AwesomeLevel", Value::Int(0)),
+        ("ContextSource", Value::Object(context.controller.cast())),
+    ];
+
+    let parameters = reflect::call_named(item_pool, spawn, &arguments)?;
+
+    let spawned = (*spawn)
+        .property_map()
+        .get("SpawnedInventory")
+        .copied()
+        .ok_or_else(|| reflect::Error::PropertyNotFound(String::from("SpawnedInventory")))?;
+
+    let items: Array<*mut Object> =
+        ptr::read_unaligned(parameters.as_ptr().add(spawned.offset as usize).cast());
+
+    let manager: *mut Object = (*pawn).get_property("InvManager")?;
+    let mut count = 0;
+
+    for item in items.iter() {
+        reflect::call_by_name(ADD_TO_BACKPACK, manager, &[Value::Object(item)])?;
+        count += 1;
+    }
+
+    // The game allocated the array of spawned items.
+    let _ = gmalloc::free(items.data.cast());
+
+    Ok(count)
+}
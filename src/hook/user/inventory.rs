@@ -0,0 +1,142 @@
+use crate::game::{cast, Array, Object, StructProperty};
+use crate::hook::reflect::{self, Kind, PropertyEntry, Value};
+use crate::hook::user;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// Where the report goes if the command doesn't say.
+pub const DEFAULT_PATH: &str = "inventory.txt";
+
+/// The struct on each weapon and item that lists its balance, manufacturer,
+/// level and parts.
+const DEFINITION_DATA: &str = "DefinitionData";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("there's no pawn")]
+    NoPawn,
+
+    #[error("the pawn has no inventory manager")]
+    NoInventory,
+
+    #[error("{0}")]
+    Reflect(#[from] reflect::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Write the name, rarity and parts of each item the player has equipped or in
+/// their backpack to `path`. Call on the game thread.
+pub unsafe fn dump(path: &str) {
+    match try_dump(path) {
+        Ok(count) => info!("Wrote {} items to {}.", count, path),
+        Err(e) => warn!("Failed to write the inventory to {}: {}", path, e),
+    }
+}
+
+unsafe fn try_dump(path: &str) -> Result<usize, Error> {
+    let pawn: *mut Object = user::context()
+        .and_then(|c| c.pawn)
+        .ok_or(Error::NoPawn)?
+        .cast();
+
+    let manager: *mut Object = (*pawn).get_property("InvManager")?;
+
+    if manager.is_null() {
+        return Err(Error::NoInventory);
+    }
+
+    let mut report = String::new();
+    let mut count = 0;
+
+    report.push_str("Equipped\n\n");
+
+    // Equipped items are a linked list, each pointing to the next.
+    let mut item: *mut Object = (*manager).get_property("InventoryChain")?;
+
+    while !item.is_null() {
+        describe(&mut report, item)?;
+        count += 1;
+        item = (*item).get_property("Inventory")?;
+    }
+
+    report.push_str("Backpack\n\n");
+
+    let backpack: *const Array<*mut Object> = (*manager)
+        .property("Backpack")
+        .ok_or_else(|| reflect::Error::PropertyNotFound("Backpack".to_owned()))?
+        .cast();
+
+    for item in (*backpack).iter() {
+        describe(&mut report, item)?;
+        count += 1;
+    }
+
+    fs::write(path, report)?;
+    Ok(count)
+}
+
+/// Append a paragraph about `item` to `report`.
+unsafe fn describe(report: &mut String, item: *mut Object) -> Result<(), reflect::Error> {
+    let name = match reflect::call_by_name("Engine.Actor.GetHumanReadableName", item, &[])? {
+        Some(Value::Str(name)) => name,
+        _ => String::from("?"),
+    };
+
+    let object = (*item).full_name().unwrap_or_default();
+    let rarity: i32 = (*item).get_property("RarityLevel")?;
+
+    let _ = writeln!(report, "{}", name);
+    let _ = writeln!(report, "    Object: {}", object);
+    let _ = writeln!(report, "    Rarity: {}", rarity);
+
+    for (field, value) in definition_data(item)? {
+        let _ = writeln!(report, "    {}: {}", field, value);
+    }
+
+    report.push('\n');
+    Ok(())
+}
+
+/// The fields of `item`'s definition data that are set, in declaration order,
+/// e.g., its balance, its level as `ManufacturerGradeIndex`, and each part.
+unsafe fn definition_data(item: *mut Object) -> Result<Vec<(String, Value)>, reflect::Error> {
+    let property = reflect::find_property(&*item, DEFINITION_DATA)
+        .ok_or_else(|| reflect::Error::PropertyNotFound(DEFINITION_DATA.to_owned()))?;
+
+    let is_struct = property
+        .full_name()
+        .map_or(false, |n| n.starts_with("StructProperty "));
+
+    if !is_struct {
+        return Err(reflect::Error::WrongType {
+            property: DEFINITION_DATA.to_owned(),
+            expected: "struct",
+        });
+    }
+
+    let entry = PropertyEntry::new(property).ok_or(reflect::Error::ClassesNotFound)?;
+    let inner = cast::<StructProperty>(property).inner_struct;
+    let container = item.cast::<u8>().add(entry.offset as usize);
+
+    let fields = (*inner).property_map();
+    let mut fields: Vec<_> = fields.iter().collect();
+    fields.sort_by_key(|(_, e)| e.offset);
+
+    Ok(fields
+        .into_iter()
+        .filter(|(_, e)| matches!(e.kind, Kind::Object | Kind::Int | Kind::Name))
+        .filter_map(|(name, e)| Some((name.clone(), e.read(container)?)))
+        .filter(|(_, value)| match value {
+            Value::Object(o) => !o.is_null(),
+            Value::Name(n) => n.index != 0,
+            _ => true,
+        })
+        .collect())
+}
@@ -0,0 +1,77 @@
+use crate::game::Object;
+use crate::hook::reflect::{self, Value};
+use crate::hook::{passive, tracker, user};
+
+use log::{info, warn};
+
+/// Enemies, but also allies like Claptrap, so hostility is by team.
+pub(super) const AI_PAWN: &str = "WillowGame.WillowAIPawn";
+
+/// Unreal units are two centimeters.
+const UNITS_PER_METER: f32 = 50.0;
+
+/// Keep the registry of AI pawns warm for `within`.
+pub unsafe fn subscribe() {
+    tracker::track(AI_PAWN);
+}
+
+/// Kill every hostile pawn within `meters` of the player. Call on the game
+/// thread.
+pub unsafe fn within(meters: f32) {
+    if passive::refuses("Killing") {
+        return;
+    }
+
+    match try_within(meters * UNITS_PER_METER) {
+        Ok(killed) => info!("Killed {} pawns within {} meters.", killed, meters),
+        Err(e) => warn!("Failed to kill pawns within {} meters: {}", meters, e),
+    }
+}
+
+unsafe fn try_within(radius: f32) -> Result<usize, reflect::Error> {
+    let player = user::context()
+        .and_then(|c| c.pawn)
+        .ok_or(reflect::Error::NullObject)?
+        .cast::<Object>();
+
+    let center = user::location(player)?;
+    let mut killed = 0;
+
+    for pawn in hostiles(player)? {
+        if user::location(pawn)?.distance(center) > radius {
+            continue;
+        }
+
+        // Dies as if by the player, so that kill skills and drops happen.
+        reflect::call_by_name("Engine.Pawn.KilledBy", pawn, &[Value::Object(player)])?;
+        killed += 1;
+    }
+
+    Ok(killed)
+}
+
+/// The living AI pawns that aren't on `player`'s team.
+pub(super) unsafe fn hostiles(player: *mut Object) -> Result<Vec<*mut Object>, reflect::Error> {
+    let player_team = team(player)?;
+    let mut hostiles = Vec::new();
+
+    for pawn in tracker::instances(AI_PAWN) {
+        let pawn = match pawn.get() {
+            Some(pawn) => pawn,
+            None => continue,
+        };
+
+        if (*pawn).get_property::<i32>("Health")? > 0 && team(pawn)? != player_team {
+            hostiles.push(pawn);
+        }
+    }
+
+    Ok(hostiles)
+}
+
+unsafe fn team(actor: *mut Object) -> Result<Option<u8>, reflect::Error> {
+    match reflect::call_by_name("Engine.Actor.GetTeamNum", actor, &[])? {
+        Some(Value::Byte(team)) => Ok(Some(team)),
+        _ => Ok(None),
+    }
+}
@@ -1,60 +1,361 @@
-use crate::game::{Function, Object};
-use crate::hook::sdk::{Canvas, WillowPlayerController};
-
-use super::CACHED_FUNCTION_INDEXES;
-
-use std::ffi::c_void;
-use std::ptr;
-
-use log::info;
-
-mod yank;
-use yank::Yank;
-
-pub static mut CONTROLLER: *mut WillowPlayerController = ptr::null_mut();
-
-pub unsafe fn process_event(
-    this: *mut Object,
-    method: *mut Function,
-    parameters: *mut c_void,
-    _return_value: *mut c_void,
-) {
-    let indexes = CACHED_FUNCTION_INDEXES.yank_ref();
-    let method_index = (*method).index;
-
-    if method_index == indexes.post_render {
-        my_post_render(parameters.cast());
-    } else if method_index == indexes.player_tick {
-        my_player_tick(this.cast());
-    } else if method_index == indexes.player_destroyed {
-        my_player_destroyed();
-    } else {
-        // print_event(this, method);
+use crate::game::{self, Color, Object, Rotator, Vector};
+use crate::hook::reflect::{self, Value};
+use crate::hook::sdk::{Canvas, Pawn, WillowPlayerController, HUD};
+use crate::hook::{events, input, overlay, profiler, recorder, Handle};
+use crate::GLOBAL_NAMES;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use imgui::{im_str, Window};
+use log::{error, info};
+use winapi::um::winuser::{VK_F7, VK_F8};
+
+pub mod aim;
+mod ammo;
+mod cooldown;
+pub mod crosshair;
+mod cutscene;
+pub mod damage;
+pub mod farm;
+pub mod feature;
+pub mod fly;
+pub mod fov;
+pub mod freecam;
+pub mod give;
+pub mod inventory;
+pub mod kill;
+pub mod movement;
+pub mod pickup;
+pub mod radar;
+mod recoil;
+pub mod route;
+pub mod speed;
+mod stats;
+pub mod teleport;
+pub mod travel;
+pub mod vacuum;
+pub mod vehicle;
+
+static HANDLES: Mutex<Handles> = Mutex::new(Handles {
+    controller: None,
+    pawn: None,
+    hud: None,
+});
+
+/// The local player's objects as of the last frame.
+#[derive(Clone, Copy)]
+struct Handles {
+    controller: Option<Handle<WillowPlayerController>>,
+    pawn: Option<Handle<Pawn>>,
+    hud: Option<Handle<HUD>>,
+}
+
+fn handles() -> MutexGuard<'static, Handles> {
+    HANDLES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The local player's controller, pawn and HUD. They're found again from the
+/// engine's `LocalPlayer` every frame, so they follow map changes and
+/// respawns. Only valid until the game next collects garbage, so use them on
+/// the game thread and don't keep them.
+#[derive(Clone, Copy)]
+pub struct PlayerContext {
+    pub controller: *mut WillowPlayerController,
+
+    /// `None` while dead, e.g., in Fight For Your Life's respawn screen.
+    pub pawn: Option<*mut Pawn>,
+
+    pub hud: Option<*mut HUD>,
+}
+
+/// The local player's objects, or `None` while there's no controller, e.g.,
+/// during a map change.
+pub unsafe fn context() -> Option<PlayerContext> {
+    let handles = *handles();
+
+    Some(PlayerContext {
+        controller: live(handles.controller)?,
+        pawn: live(handles.pawn),
+        hud: live(handles.hud),
+    })
+}
+
+/// The local player's controller, or `None` if it's gone, e.g., after the map
+/// changed and before the new controller exists.
+pub unsafe fn controller() -> Option<*mut WillowPlayerController> {
+    context().map(|c| c.controller)
+}
+
+unsafe fn live<T>(handle: Option<Handle<T>>) -> Option<*mut T> {
+    handle?
+        .get()
+        .filter(|&o| !(*o.cast::<Object>()).is_pending_kill())
+}
+
+/// The object that `object`'s property called `name` points to.
+unsafe fn object_property(object: *mut Object, name: &str) -> Option<*mut Object> {
+    let value: *const *mut Object = (*object).property(name)?.cast();
+    Some(*value).filter(|o| !o.is_null() && !(**o).is_pending_kill())
+}
+
+/// Look up the local player's objects again.
+unsafe fn refresh() {
+    let controller = game::player_controller().filter(|&c| !(*c).is_pending_kill());
+    let mut handles = handles();
+    let previous = handles.controller;
+
+    *handles = match controller {
+        Some(controller) => Handles {
+            controller: Some(Handle::new(controller.cast())),
+            pawn: object_property(controller, "Pawn").map(|p| Handle::new(p.cast())),
+            hud: object_property(controller, "myHUD").map(|h| Handle::new(h.cast())),
+        },
+
+        None => Handles {
+            controller: None,
+            pawn: None,
+            hud: None,
+        },
+    };
+
+    if handles.controller != previous {
+        match handles.controller {
+            Some(controller) => info!("The player controller is {:?}.", controller),
+            None => info!("The player controller is gone."),
+        }
     }
 }
 
-unsafe fn my_post_render(canvas: *mut *mut Canvas) {
-    let canvas = *canvas;
-    (*canvas).SetPos(200.0, 200.0, 0.0);
-    (*canvas).DrawBox(200.0, 200.0);
+/// Send `object`, e.g., a controller, to its state called `state`, like
+/// UnrealScript's `GotoState('PlayerFlying')`.
+unsafe fn goto_state(object: *mut Object, state: &str) -> Result<(), reflect::Error> {
+    let state = GLOBAL_NAMES
+        .get()
+        .find(state)
+        .ok_or_else(|| reflect::Error::NameNotFound(state.to_owned()))?;
+
+    let none = game::NameIndex {
+        index: 0,
+        number: 0,
+    };
+
+    let arguments = [
+        Value::Name(state),
+        Value::Name(none),
+        Value::Bool(false),
+        Value::Bool(false),
+    ];
+
+    reflect::call_by_name("Core.Object.GotoState", object, &arguments)?;
+    Ok(())
+}
+
+/// Move `actor` to `location`, e.g., a pawn or a pickup.
+unsafe fn set_location(actor: *mut Object, location: Vector) -> Result<(), reflect::Error> {
+    #[repr(C)]
+    struct SetLocation {
+        location: Vector,
+        return_value: u32,
+    }
+
+    let mut parameters = SetLocation {
+        location,
+        return_value: 0,
+    };
+
+    game::call_checked(actor, "Function Engine.Actor.SetLocation", &mut parameters)
+        .ok_or_else(|| reflect::Error::FunctionNotFound("Engine.Actor.SetLocation".to_owned()))
+}
+
+/// Turn `actor` to `rotation`, e.g., a controller to aim its pawn.
+unsafe fn set_rotation(actor: *mut Object, rotation: Rotator) -> Result<(), reflect::Error> {
+    #[repr(C)]
+    struct SetRotation {
+        rotation: Rotator,
+        return_value: u32,
+    }
+
+    let mut parameters = SetRotation {
+        rotation,
+        return_value: 0,
+    };
+
+    game::call_checked(actor, "Function Engine.Actor.SetRotation", &mut parameters)
+        .ok_or_else(|| reflect::Error::FunctionNotFound("Engine.Actor.SetRotation".to_owned()))
+}
+
+/// Where `actor` is.
+unsafe fn location(actor: *mut Object) -> Result<Vector, reflect::Error> {
+    let location = (*actor)
+        .property("Location")
+        .ok_or_else(|| reflect::Error::PropertyNotFound("Location".to_owned()))?;
+
+    Ok(location.cast::<Vector>().read_unaligned())
 }
 
-unsafe fn my_player_tick(my_controller: *mut WillowPlayerController) {
-    if CONTROLLER.is_null() {
-        CONTROLLER = my_controller;
-        info!("Set CONTROLLER.");
+/// Where `controller` sees from and which way, e.g., to tell whether a point
+/// is in front of the view before projecting it.
+unsafe fn view_point(controller: *mut Object) -> Result<(Vector, Rotator), reflect::Error> {
+    #[repr(C)]
+    struct GetPlayerViewPoint {
+        location: Vector,
+        rotation: Rotator,
     }
+
+    let mut parameters = GetPlayerViewPoint {
+        location: Vector::default(),
+        rotation: Rotator::default(),
+    };
+
+    game::call_checked(
+        controller,
+        "Function Engine.Controller.GetPlayerViewPoint",
+        &mut parameters,
+    )
+    .ok_or_else(|| {
+        reflect::Error::FunctionNotFound("Engine.Controller.GetPlayerViewPoint".to_owned())
+    })?;
+
+    Ok((parameters.location, parameters.rotation))
 }
 
-unsafe fn my_player_destroyed() {
-    CONTROLLER = ptr::null_mut();
-    info!("Destroyed CONTROLLER.");
+/// Which way `actor` faces, e.g., where a controller aims.
+unsafe fn rotation(actor: *mut Object) -> Result<Rotator, reflect::Error> {
+    let rotation = (*actor)
+        .property("Rotation")
+        .ok_or_else(|| reflect::Error::PropertyNotFound("Rotation".to_owned()))?;
+
+    Ok(rotation.cast::<Rotator>().read_unaligned())
+}
+
+/// Draw a line on `canvas` from `start` to `end`, in pixels.
+unsafe fn line(
+    canvas: *mut Object,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: Color,
+) -> Result<(), reflect::Error> {
+    game::draw_line(canvas, start, end, color)
+        .ok_or_else(|| reflect::Error::FunctionNotFound("Engine.Canvas.Draw2DLine".to_owned()))
+}
+
+/// Says when `period` has passed, for features that shouldn't run every tick.
+struct Interval {
+    period: Duration,
+    last: Option<Instant>,
 }
 
-fn _print_event(object: &Object, method: &Function) {
-    if let Some(object) = unsafe { object.full_name() } {
-        if let Some(method) = unsafe { method.full_name() } {
-            info!("{} called {}", object, method);
+impl Interval {
+    const fn new(period: Duration) -> Self {
+        Self { period, last: None }
+    }
+
+    /// True on the first call, and then at most once per period.
+    fn is_due(&mut self) -> bool {
+        let now = Instant::now();
+
+        if self.last.map_or(true, |t| now - t >= self.period) {
+            self.last = Some(now);
+            true
+        } else {
+            false
         }
     }
 }
+
+/// Call `callback` with the local player's objects each time their controller
+/// ticks, but not for other players' controllers.
+unsafe fn on_player_tick(mut callback: impl FnMut(PlayerContext) + 'static) {
+    events::on("WillowGame.WillowPlayerController.PlayerTick", move |e| {
+        if let Some(context) = context() {
+            if context.controller.cast() == e.this {
+                callback(context);
+            }
+        }
+    });
+}
+
+pub unsafe fn subscribe() {
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |e| {
+        refresh();
+        my_post_render(e.parameters.cast());
+    });
+
+    ammo::subscribe();
+    recoil::subscribe();
+    fly::subscribe();
+    teleport::subscribe();
+    speed::subscribe();
+    fov::subscribe();
+    damage::subscribe();
+    vacuum::subscribe();
+    pickup::subscribe();
+    kill::subscribe();
+    movement::subscribe();
+    cooldown::subscribe();
+    freecam::subscribe();
+    crosshair::subscribe();
+    stats::subscribe();
+    aim::subscribe();
+    vehicle::subscribe();
+    cutscene::subscribe();
+    radar::subscribe();
+    route::subscribe();
+    farm::subscribe();
+    feature::subscribe();
+    feature::load();
+
+    input::bind("record", VK_F7, || {
+        if recorder::is_recording() {
+            recorder::stop();
+        } else {
+            recorder::start();
+        }
+    });
+
+    input::bind("flush", VK_F8, || {
+        if let Err(e) = recorder::flush() {
+            error!("Failed to flush recorded events: {}", e);
+        }
+    });
+
+    overlay::on_draw(|ui| {
+        Window::new(im_str!("blps"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                let mut profiling = profiler::is_profiling();
+
+                if ui.checkbox(im_str!("Profile events"), &mut profiling) {
+                    if profiling {
+                        profiler::start();
+                    } else {
+                        profiler::stop();
+                    }
+                }
+
+                if ui.button(im_str!("Write profile"), [0.0, 0.0]) {
+                    if let Err(e) = unsafe { profiler::report() } {
+                        error!("Failed to write the profile: {}", e);
+                    }
+                }
+
+                let mut degrees = fov::get();
+                let mut overriding = degrees.is_some();
+
+                if ui.checkbox(im_str!("Override FOV"), &mut overriding) {
+                    fov::set(if overriding { Some(fov::DEFAULT) } else { None });
+                } else if let Some(degrees) = degrees.as_mut() {
+                    if ui.input_float(im_str!("FOV"), degrees).build() {
+                        fov::set(Some(*degrees));
+                    }
+                }
+            });
+    });
+}
+
+unsafe fn my_post_render(canvas: *mut *mut Canvas) {
+    let canvas = *canvas;
+    (*canvas).SetPos(200.0, 200.0, 0.0);
+    (*canvas).DrawBox(200.0, 200.0);
+}
@@ -0,0 +1,127 @@
+use crate::game::Object;
+use crate::hook::reflect;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{fly, PlayerContext};
+use crate::hook::Handle;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::{info, warn};
+use winapi::um::winuser::VK_NUMPAD9;
+
+/// Set from the console thread.
+static MULTIPLIERS: Mutex<Multipliers> = Mutex::new(Multipliers {
+    speed: 2.0,
+    jump: 2.0,
+});
+
+#[derive(Clone, Copy)]
+struct Multipliers {
+    /// Scales how fast the pawn runs and moves in the air.
+    speed: f32,
+
+    /// Scales how high the pawn jumps.
+    jump: f32,
+}
+
+/// The pawn's movement before scaling, so that scaling doesn't compound.
+struct Base {
+    pawn: Handle<Object>,
+    ground_speed: f32,
+    air_speed: f32,
+    jump_z: f32,
+}
+
+fn multipliers() -> MutexGuard<'static, Multipliers> {
+    MULTIPLIERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Scale the pawn's speed and jump height every tick, since respawning and
+/// map changes reset them.
+pub unsafe fn subscribe() {
+    feature::register("movement", VK_NUMPAD9, Movement { base: None });
+}
+
+struct Movement {
+    /// The scaled pawn's movement, to restore when turned off.
+    base: Option<Base>,
+}
+
+impl Feature for Movement {
+    fn name(&self) -> &'static str {
+        "Movement modifiers"
+    }
+
+    unsafe fn on_disable(&mut self) {
+        if let Some(base) = self.base.take() {
+            if let Err(e) = restore(&base) {
+                warn!("Failed to restore the pawn's movement: {}", e);
+            }
+        }
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        scale(context, &mut self.base)
+    }
+}
+
+pub fn set_speed(speed: f32) {
+    multipliers().speed = speed;
+    info!("Movement speed is multiplied by {}.", speed);
+}
+
+pub fn set_jump(jump: f32) {
+    multipliers().jump = jump;
+    info!("Jump height is multiplied by {}.", jump);
+}
+
+unsafe fn scale(context: PlayerContext, base: &mut Option<Base>) -> Result<(), reflect::Error> {
+    let pawn = match context.pawn {
+        Some(pawn) => pawn.cast::<Object>(),
+        None => return Ok(()),
+    };
+
+    let handle = Handle::new(pawn);
+
+    // A respawned pawn starts from its own movement.
+    if base.as_ref().map_or(true, |b| b.pawn != handle) {
+        *base = Some(Base {
+            pawn: handle,
+            ground_speed: (*pawn).get_property("GroundSpeed")?,
+            air_speed: (*pawn).get_property("AirSpeed")?,
+            jump_z: (*pawn).get_property("JumpZ")?,
+        });
+    }
+
+    let base = match base.as_ref() {
+        Some(base) => base,
+        None => return Ok(()),
+    };
+
+    let Multipliers { speed, jump } = *multipliers();
+    (*pawn).set_property("GroundSpeed", base.ground_speed * speed)?;
+
+    // Flying sets its own air speed.
+    if (*pawn).get_property::<u8>("Physics")? != fly::PHYS_FLYING {
+        (*pawn).set_property("AirSpeed", base.air_speed * speed)?;
+    }
+
+    // A jump reaches JumpZ² / 2g, so scaling the height by `jump` scales JumpZ
+    // by its square root whatever the gravity, e.g., on the low-gravity moon.
+    (*pawn).set_property("JumpZ", base.jump_z * jump.max(0.0).sqrt())
+}
+
+unsafe fn restore(base: &Base) -> Result<(), reflect::Error> {
+    let pawn = match base.pawn.get() {
+        Some(pawn) => pawn,
+        None => return Ok(()),
+    };
+
+    (*pawn).set_property("GroundSpeed", base.ground_speed)?;
+
+    if (*pawn).get_property::<u8>("Physics")? != fly::PHYS_FLYING {
+        (*pawn).set_property("AirSpeed", base.air_speed)?;
+    }
+
+    (*pawn).set_property("JumpZ", base.jump_z)
+}
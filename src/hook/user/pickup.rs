@@ -0,0 +1,72 @@
+use crate::game::Object;
+use crate::hook::reflect;
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{vacuum, Interval, PlayerContext};
+use crate::GLOBAL_OBJECTS;
+
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use log::info;
+use winapi::um::winuser::VK_NUMPAD8;
+
+/// Money, ammo, and health are used when touched, unlike gear, which derives
+/// from `WillowEquipAbleItem`.
+const CONSUMABLE: &str = "Class WillowGame.WillowUsableItem";
+
+/// How often to look for consumables.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// How far to reach, in unreal units. Set from the console thread.
+static RADIUS: Mutex<f32> = Mutex::new(1500.0);
+
+/// Claim the money, ammo, and health around the player, but leave gear for
+/// them to choose.
+pub unsafe fn subscribe() {
+    tracker::track(vacuum::PICKUP);
+    let claim = Interval::new(INTERVAL);
+    feature::register("auto-pickup", VK_NUMPAD8, Pickup { claim });
+}
+
+struct Pickup {
+    claim: Interval,
+}
+
+impl Feature for Pickup {
+    fn name(&self) -> &'static str {
+        "Auto-pickup"
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        if self.claim.is_due() {
+            claim(context)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn set_radius(radius: f32) {
+    *RADIUS.lock().unwrap_or_else(PoisonError::into_inner) = radius;
+    info!("Auto-pickup reaches {} units.", radius);
+}
+
+/// Touching a consumable claims it, so bringing it to the player is enough.
+unsafe fn claim(context: PlayerContext) -> Result<(), reflect::Error> {
+    let consumable = GLOBAL_OBJECTS
+        .get()
+        .find(CONSUMABLE)
+        .ok_or_else(|| reflect::Error::NameNotFound(CONSUMABLE.to_owned()))?;
+
+    let radius = *RADIUS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    vacuum::pull_matching(context, radius, |pickup| {
+        let inventory: *mut Object = (*pickup).get_property("Inventory")?;
+        Ok(inventory
+            .as_ref()
+            .map_or(false, |i| i.is(consumable.cast())))
+    })?;
+
+    Ok(())
+}
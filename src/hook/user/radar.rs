@@ -0,0 +1,147 @@
+use crate::game::{Color, Object, Rotator, Vector};
+use crate::hook::reflect;
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, kill, kill::AI_PAWN};
+
+use std::sync::{Mutex, PoisonError};
+
+use log::info;
+use winapi::um::winuser::VK_F5;
+
+/// The radar's width and height, in pixels.
+const SIZE: f32 = 160.0;
+
+/// How far the radar is from the top right corner, in pixels.
+const MARGIN: f32 = 16.0;
+
+/// How far each blip's arms reach from its center, in pixels.
+const BLIP: f32 = 3.0;
+
+const FRAME: Color = Color::rgba(255, 255, 255, 160);
+const HOSTILE: Color = Color::rgb(255, 64, 64);
+const FRIENDLY: Color = Color::rgb(64, 255, 64);
+
+/// How far the radar sees, in unreal units, from its center to its edge. Set
+/// from the console thread.
+static RANGE: Mutex<f32> = Mutex::new(4000.0);
+
+/// Draw a radar in the top right corner of the screen, with the player in the
+/// middle facing up, and hostile and friendly pawns around them.
+pub unsafe fn subscribe() {
+    tracker::track(AI_PAWN);
+    feature::register("radar", VK_F5, Radar);
+}
+
+/// Not read-only, since it shows what the player can't see.
+struct Radar;
+
+impl Feature for Radar {
+    fn name(&self) -> &'static str {
+        "Radar"
+    }
+
+    unsafe fn on_render(&mut self, canvas: *mut Object) -> Result<(), reflect::Error> {
+        draw(canvas)
+    }
+}
+
+pub fn set_range(range: f32) {
+    *RANGE.lock().unwrap_or_else(PoisonError::into_inner) = range;
+    info!("The radar sees {} units.", range);
+}
+
+unsafe fn draw(canvas: *mut Object) -> Result<(), reflect::Error> {
+    let context = match user::context() {
+        Some(context) => context,
+        None => return Ok(()),
+    };
+
+    let pawn: *mut Object = match context.pawn {
+        Some(pawn) => pawn.cast(),
+        None => return Ok(()),
+    };
+
+    if canvas.is_null() {
+        return Err(reflect::Error::NullObject);
+    }
+
+    let width: f32 = (*canvas).get_property("ClipX")?;
+    let left = width - MARGIN - SIZE;
+    let top = MARGIN;
+    let center = (left + SIZE / 2.0, top + SIZE / 2.0);
+
+    let corners = [
+        (left, top),
+        (left + SIZE, top),
+        (left + SIZE, top + SIZE),
+        (left, top + SIZE),
+    ];
+
+    for (i, &corner) in corners.iter().enumerate() {
+        user::line(canvas, corner, corners[(i + 1) % corners.len()], FRAME)?;
+    }
+
+    blip(canvas, center, FRAME)?;
+
+    // Only the yaw matters on a flat radar.
+    let mut rotation = user::rotation(context.controller.cast())?;
+    rotation.pitch = 0;
+    rotation.roll = 0;
+
+    let here = user::location(pawn)?;
+    let range = *RANGE.lock().unwrap_or_else(PoisonError::into_inner);
+    let hostiles = kill::hostiles(pawn)?;
+
+    for other in tracker::instances(AI_PAWN) {
+        let other = match other.get() {
+            Some(other) => other,
+            None => continue,
+        };
+
+        if (*other).get_property::<i32>("Health")? <= 0 {
+            continue;
+        }
+
+        if let Some(position) = to_radar(user::location(other)? - here, rotation, range) {
+            let color = if hostiles.contains(&other) {
+                HOSTILE
+            } else {
+                FRIENDLY
+            };
+
+            let point = (
+                center.0 + position.0 * SIZE / 2.0,
+                center.1 + position.1 * SIZE / 2.0,
+            );
+
+            blip(canvas, point, color)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Where `offset` from the player is on the radar, from -1 to 1 on each axis
+/// with up as ahead, or `None` if it's out of `range`.
+fn to_radar(offset: Vector, rotation: Rotator, range: f32) -> Option<(f32, f32)> {
+    let (forward, right, _) = rotation.axes();
+    let x = offset.dot(right) / range;
+    let y = -offset.dot(forward) / range;
+
+    if x.abs() <= 1.0 && y.abs() <= 1.0 {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+/// A small cross at `(x, y)`.
+unsafe fn blip(
+    canvas: *mut Object,
+    (x, y): (f32, f32),
+    color: Color,
+) -> Result<(), reflect::Error> {
+    user::line(canvas, (x - BLIP, y), (x + BLIP, y), color)?;
+    user::line(canvas, (x, y - BLIP), (x, y + BLIP), color)
+}
@@ -0,0 +1,53 @@
+use crate::game::{self, Object};
+use crate::hook::reflect::{self, Kind, PropertyEntry};
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+
+use winapi::um::winuser::VK_NUMPAD2;
+
+/// The weapon properties that widen the cone of fire or kick the view. Not
+/// every game has all of them, so missing ones and ones that aren't floats
+/// are skipped.
+const PROPERTIES: [&str; 4] = ["Spread", "AccuracyImpulse", "Recoil", "RecoilMultiplier"];
+
+/// Zero the spread and recoil of the equipped weapon every tick, since
+/// firing, zooming and swapping weapons recompute them.
+pub unsafe fn subscribe() {
+    feature::register("recoil", VK_NUMPAD2, Recoil);
+}
+
+struct Recoil;
+
+impl Feature for Recoil {
+    fn name(&self) -> &'static str {
+        "No recoil"
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        steady(context)
+    }
+}
+
+unsafe fn steady(context: PlayerContext) -> Result<(), reflect::Error> {
+    let pawn = match context.pawn {
+        Some(pawn) => &*pawn.cast::<Object>(),
+        None => return Ok(()),
+    };
+
+    let weapon: *mut Object = pawn.get_property("Weapon")?;
+
+    if weapon.is_null() {
+        return Ok(());
+    }
+
+    for &name in &PROPERTIES {
+        let entry = reflect::find_property(&*weapon, name).and_then(|p| PropertyEntry::new(p));
+
+        if let Some(entry) = entry.filter(|e| e.kind == Kind::Float) {
+            let address = weapon.cast::<u8>().add(entry.offset as usize);
+            game::try_write(address.cast::<f32>(), 0.0);
+        }
+    }
+
+    Ok(())
+}
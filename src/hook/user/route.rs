@@ -0,0 +1,250 @@
+use crate::game::{self, Color, Object, Vector};
+use crate::hook::events;
+use crate::hook::reflect;
+use crate::hook::user::{self, PlayerContext};
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// One file per route, e.g., "routes/any-percent.txt".
+const ROUTES: &str = "routes";
+
+/// Only positions this far from the last recorded one are kept, in unreal
+/// units, so that standing still doesn't grow the route.
+const MIN_STEP: f32 = 25.0;
+
+/// How far the ghost's arms reach from its center, in pixels.
+const GHOST_SIZE: f32 = 8.0;
+
+const LINE: Color = Color::rgba(64, 192, 255, 192);
+const GHOST: Color = Color::rgb(255, 255, 255);
+
+// Recorded and drawn on the game thread, started and stopped from the console
+// thread.
+static ROUTE: Mutex<Route> = Mutex::new(Route {
+    recording: None,
+    ghost: None,
+});
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("not recording")]
+    NotRecording,
+
+    #[error("line {0} isn't \"seconds x y z\"")]
+    Parse(usize),
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Where the player was how many seconds into the run.
+#[derive(Clone, Copy)]
+struct Point {
+    seconds: f32,
+    location: Vector,
+}
+
+struct Recording {
+    name: String,
+    start: Instant,
+    points: Vec<Point>,
+}
+
+struct Ghost {
+    start: Instant,
+    points: Vec<Point>,
+}
+
+struct Route {
+    recording: Option<Recording>,
+    ghost: Option<Ghost>,
+}
+
+fn route() -> MutexGuard<'static, Route> {
+    ROUTE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(ROUTES).join(format!("{}.txt", name))
+}
+
+/// Record the player's runs, and replay one as a line through the world with
+/// a ghost moving along it at the recorded pace.
+pub unsafe fn subscribe() {
+    user::on_player_tick(|context| {
+        if let Err(e) = record(context) {
+            warn!("Stopped recording the route: {}", e);
+            route().recording = None;
+        }
+    });
+
+    events::on("WillowGame.WillowGameViewportClient.PostRender", |e| {
+        let canvas: *mut Object = *e.parameters.cast();
+
+        if let Err(e) = draw(canvas) {
+            warn!("Stopped showing the route: {}", e);
+            route().ghost = None;
+        }
+    });
+}
+
+/// Start recording a route called `name`, replacing any recording not saved.
+pub fn start(name: &str) {
+    route().recording = Some(Recording {
+        name: name.to_owned(),
+        start: Instant::now(),
+        points: Vec::new(),
+    });
+
+    info!("Recording the route {}.", name);
+}
+
+/// Stop recording and save the route.
+pub fn stop() {
+    match try_stop() {
+        Ok((name, count)) => info!("Saved {} positions to the route {}.", count, name),
+        Err(e) => warn!("Failed to save the route: {}", e),
+    }
+}
+
+/// Show the route called `name`, with its ghost starting from the beginning.
+pub fn show(name: &str) {
+    match load(name) {
+        Ok(points) => {
+            info!("Showing the route {} of {} positions.", name, points.len());
+
+            route().ghost = Some(Ghost {
+                start: Instant::now(),
+                points,
+            });
+        }
+
+        Err(e) => warn!("Failed to load the route {}: {}", name, e),
+    }
+}
+
+pub fn hide() {
+    route().ghost = None;
+    info!("Hid the route.");
+}
+
+fn try_stop() -> Result<(String, usize), Error> {
+    let recording = route().recording.take().ok_or(Error::NotRecording)?;
+    let mut text = String::new();
+
+    for point in &recording.points {
+        let Vector { x, y, z } = point.location;
+        let _ = writeln!(text, "{} {} {} {}", point.seconds, x, y, z);
+    }
+
+    fs::create_dir_all(ROUTES)?;
+    fs::write(path(&recording.name), text)?;
+
+    Ok((recording.name, recording.points.len()))
+}
+
+fn load(name: &str) -> Result<Vec<Point>, Error> {
+    let text = fs::read_to_string(path(name))?;
+
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).ok_or(Error::Parse(i + 1)))
+        .collect()
+}
+
+/// "seconds x y z"
+fn parse_line(line: &str) -> Option<Point> {
+    let mut numbers = line.split_whitespace().map(|n| n.parse::<f32>().ok());
+    let mut next = || numbers.next().flatten();
+
+    Some(Point {
+        seconds: next()?,
+        location: Vector::new(next()?, next()?, next()?),
+    })
+}
+
+unsafe fn record(context: PlayerContext) -> Result<(), reflect::Error> {
+    let mut route = route();
+
+    let recording = match route.recording.as_mut() {
+        Some(recording) => recording,
+        None => return Ok(()),
+    };
+
+    let pawn = match context.pawn {
+        Some(pawn) => pawn.cast(),
+        None => return Ok(()),
+    };
+
+    let location = user::location(pawn)?;
+
+    let moved = recording
+        .points
+        .last()
+        .map_or(true, |p| p.location.distance(location) >= MIN_STEP);
+
+    if moved {
+        recording.points.push(Point {
+            seconds: recording.start.elapsed().as_secs_f32(),
+            location,
+        });
+    }
+
+    Ok(())
+}
+
+unsafe fn draw(canvas: *mut Object) -> Result<(), reflect::Error> {
+    let route = route();
+
+    let ghost = match route.ghost.as_ref() {
+        Some(ghost) => ghost,
+        None => return Ok(()),
+    };
+
+    let controller = match user::controller() {
+        Some(controller) => controller.cast(),
+        None => return Ok(()),
+    };
+
+    let (eye, rotation) = user::view_point(controller)?;
+    let forward = rotation.to_direction();
+
+    // Only points in front of the view project to the screen.
+    let screen = |location: Vector| {
+        if (location - eye).dot(forward) > 0.0 {
+            game::project(canvas, location)
+        } else {
+            None
+        }
+    };
+
+    for pair in ghost.points.windows(2) {
+        if let (Some(start), Some(end)) = (screen(pair[0].location), screen(pair[1].location)) {
+            user::line(canvas, start, end, LINE)?;
+        }
+    }
+
+    let elapsed = ghost.start.elapsed().as_secs_f32();
+
+    let position = ghost
+        .points
+        .iter()
+        .take_while(|p| p.seconds <= elapsed)
+        .last()
+        .or_else(|| ghost.points.first());
+
+    if let Some((x, y)) = position.and_then(|p| screen(p.location)) {
+        user::line(canvas, (x - GHOST_SIZE, y), (x + GHOST_SIZE, y), GHOST)?;
+        user::line(canvas, (x, y - GHOST_SIZE), (x, y + GHOST_SIZE), GHOST)?;
+    }
+
+    Ok(())
+}
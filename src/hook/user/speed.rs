@@ -0,0 +1,77 @@
+use crate::game;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::PlayerContext;
+use crate::hook::{input, passive, reflect};
+
+use std::sync::{Mutex, PoisonError};
+
+use log::info;
+use winapi::um::winuser::{VK_ADD, VK_MULTIPLY, VK_SUBTRACT};
+
+/// How much each hotkey press speeds up or slows down the game.
+const STEP: f32 = 2.0;
+
+const MIN: f32 = 0.125;
+const MAX: f32 = 8.0;
+
+/// How many times its normal speed the game runs at while the feature is on.
+/// Set from the game and console threads.
+static DILATION: Mutex<f32> = Mutex::new(1.0);
+
+/// Speed up, slow down or reset the game's clock with hotkeys. Only the host
+/// of a co-op game can change it.
+pub unsafe fn subscribe() {
+    feature::register("speed", VK_MULTIPLY, Speed);
+    input::bind("faster", VK_ADD, || unsafe { scale(STEP) });
+    input::bind("slower", VK_SUBTRACT, || unsafe { scale(1.0 / STEP) });
+}
+
+struct Speed;
+
+impl Feature for Speed {
+    fn name(&self) -> &'static str {
+        "The game speed"
+    }
+
+    unsafe fn on_disable(&mut self) {
+        if let Some(world_info) = game::world_info() {
+            let _ = (*world_info).set_property("TimeDilation", 1.0_f32);
+        }
+    }
+
+    /// Loading a map resets the clock, so it's reapplied every tick.
+    unsafe fn on_tick(&mut self, _context: PlayerContext) -> Result<(), reflect::Error> {
+        let dilation = dilation();
+
+        if let Some(world_info) = game::world_info() {
+            let current: f32 = (*world_info).get_property("TimeDilation")?;
+
+            if current != dilation {
+                (*world_info).set_property("TimeDilation", dilation)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn dilation() -> f32 {
+    *DILATION.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run the game at `dilation` times its normal speed, within limits that keep
+/// physics stable. Call on the game thread.
+pub unsafe fn set(dilation: f32) {
+    if passive::refuses("Changing the game speed") {
+        return;
+    }
+
+    let dilation = dilation.max(MIN).min(MAX);
+    *DILATION.lock().unwrap_or_else(PoisonError::into_inner) = dilation;
+    info!("The game speed is {}x.", dilation);
+    feature::set("speed", dilation != 1.0);
+}
+
+unsafe fn scale(factor: f32) {
+    set(dilation() * factor);
+}
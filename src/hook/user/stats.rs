@@ -0,0 +1,144 @@
+use crate::game::{self, Color, Object};
+use crate::hook::reflect;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, kill};
+use crate::hook::{profiler, recorder};
+
+use std::time::{Duration, Instant};
+
+use winapi::um::winuser::VK_F2;
+
+/// Where the panel's first line is, in pixels from the top left.
+const ORIGIN: (f32, f32) = (16.0, 16.0);
+
+const LINE_HEIGHT: f32 = 16.0;
+
+/// How much each frame moves the FPS estimate, so that it doesn't flicker.
+const SMOOTHING: f32 = 0.05;
+
+/// How often to count enemies, which calls into the game for each AI pawn.
+const COUNT_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Stats {
+    last_frame: Option<Instant>,
+    fps: f32,
+    last_count: Option<Instant>,
+    enemies: usize,
+}
+
+/// Draw a panel of stats in the top left of the screen every frame.
+pub unsafe fn subscribe() {
+    let stats = Stats {
+        last_frame: None,
+        fps: 0.0,
+        last_count: None,
+        enemies: 0,
+    };
+
+    feature::register("stats", VK_F2, stats);
+}
+
+impl Feature for Stats {
+    fn name(&self) -> &'static str {
+        "Stats"
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    unsafe fn on_enable(&mut self) -> Result<(), reflect::Error> {
+        // Start the estimate over, since the time spent hidden isn't a frame.
+        self.last_frame = None;
+        self.fps = 0.0;
+        Ok(())
+    }
+
+    unsafe fn on_render(&mut self, canvas: *mut Object) -> Result<(), reflect::Error> {
+        self.update();
+
+        draw(canvas, self)
+            .ok_or_else(|| reflect::Error::FunctionNotFound("Engine.Canvas.DrawText".to_owned()))
+    }
+}
+
+impl Stats {
+    /// Fold the time since the last frame into the FPS estimate. The first
+    /// frame after showing starts it over, so that it settles quickly.
+    fn update(&mut self) {
+        let now = Instant::now();
+
+        if let Some(last) = self.last_frame {
+            let delta = (now - last).as_secs_f32();
+
+            if delta > 0.0 && self.fps > 0.0 {
+                self.fps += (1.0 / delta - self.fps) * SMOOTHING;
+            } else if delta > 0.0 {
+                self.fps = 1.0 / delta;
+            }
+        }
+
+        self.last_frame = Some(now);
+    }
+
+    unsafe fn enemies(&mut self, pawn: *mut Object) -> usize {
+        let now = Instant::now();
+
+        if self.last_count.map_or(true, |t| now - t >= COUNT_INTERVAL) {
+            self.last_count = Some(now);
+            self.enemies = kill::hostiles(pawn).map_or(0, |h| h.len());
+        }
+
+        self.enemies
+    }
+}
+
+unsafe fn draw(canvas: *mut Object, stats: &mut Stats) -> Option<()> {
+    let context = user::context();
+    let pawn = context.and_then(|c| c.pawn).map(|p| p.cast::<Object>());
+
+    let mut lines = vec![format!("FPS: {:.0}", stats.fps)];
+
+    lines.push(format!(
+        "Map: {}",
+        game::map_name().unwrap_or_else(|| "none".to_owned())
+    ));
+
+    match pawn {
+        Some(pawn) => {
+            if let Ok(location) = user::location(pawn) {
+                lines.push(format!(
+                    "Position: {:.0} {:.0} {:.0}",
+                    location.x, location.y, location.z
+                ));
+            }
+
+            lines.push(format!("Enemies: {}", stats.enemies(pawn)));
+        }
+
+        None => lines.push("No pawn".to_owned()),
+    }
+
+    lines.push(format!(
+        "Recording: {}, profiling: {}",
+        on_off(recorder::is_recording()),
+        on_off(profiler::is_profiling())
+    ));
+
+    let (x, mut y) = ORIGIN;
+
+    for line in lines {
+        game::draw_text(canvas, &line, x, y, Color::WHITE, Some(Color::BLACK))?;
+        y += LINE_HEIGHT;
+    }
+
+    Some(())
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
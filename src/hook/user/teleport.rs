@@ -0,0 +1,217 @@
+use crate::game::{self, Object, Rotator, Vector};
+use crate::hook::user;
+use crate::hook::{input, passive};
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use log::{info, warn};
+use thiserror::Error;
+use winapi::um::winuser::{VK_NUMPAD4, VK_NUMPAD5};
+
+/// One file of slots per map, e.g., "teleports/Sanctuary_P.txt".
+const TELEPORTS: &str = "teleports";
+
+/// The slot that the hotkeys save to and teleport to.
+const HOTKEY_SLOT: u32 = 0;
+
+static SLOTS: Mutex<Option<Slots>> = Mutex::new(None);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("there's no pawn")]
+    NoPawn,
+
+    #[error("no map is loaded")]
+    NoMap,
+
+    #[error("slot {0} is empty")]
+    EmptySlot(u32),
+
+    #[error("no property named {0}")]
+    PropertyNotFound(&'static str),
+
+    #[error("unable to call {0}")]
+    Call(&'static str),
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    location: Vector,
+    rotation: Rotator,
+}
+
+/// The saved slots of the current map.
+struct Slots {
+    map: String,
+    slots: BTreeMap<u32, Slot>,
+}
+
+impl Slots {
+    fn path(map: &str) -> PathBuf {
+        PathBuf::from(TELEPORTS).join(format!("{}.txt", map))
+    }
+
+    /// Read the slots of `map`. A map without a file has no slots.
+    fn load(map: String) -> Self {
+        let text = fs::read_to_string(Self::path(&map)).unwrap_or_default();
+        let slots = text.lines().filter_map(parse_line).collect();
+        Self { map, slots }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let mut text = String::new();
+
+        for (number, slot) in &self.slots {
+            let Slot { location, rotation } = slot;
+
+            let _ = writeln!(
+                text,
+                "{} {} {} {} {} {} {}",
+                number,
+                location.x,
+                location.y,
+                location.z,
+                rotation.pitch,
+                rotation.yaw,
+                rotation.roll
+            );
+        }
+
+        fs::create_dir_all(TELEPORTS)?;
+        fs::write(Self::path(&self.map), text)
+    }
+}
+
+/// "number x y z pitch yaw roll"
+fn parse_line(line: &str) -> Option<(u32, Slot)> {
+    let mut words = line.split_whitespace();
+    let number = words.next()?.parse().ok()?;
+
+    let mut float = || words.next()?.parse::<f32>().ok();
+    let location = Vector::new(float()?, float()?, float()?);
+
+    let mut int = || words.next()?.parse::<i32>().ok();
+
+    let rotation = Rotator {
+        pitch: int()?,
+        yaw: int()?,
+        roll: int()?,
+    };
+
+    Some((number, Slot { location, rotation }))
+}
+
+/// Run `f` with the slots of the current map, loading them after a map
+/// change.
+unsafe fn with_slots<R>(f: impl FnOnce(&mut Slots) -> Result<R, Error>) -> Result<R, Error> {
+    let map = game::map_name().ok_or(Error::NoMap)?;
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if slots.as_ref().map_or(true, |s| s.map != map) {
+        *slots = Some(Slots::load(map));
+    }
+
+    match slots.as_mut() {
+        Some(slots) => f(slots),
+        None => Err(Error::NoMap),
+    }
+}
+
+/// Save and restore the player's position with hotkeys, or with the console
+/// for more slots.
+pub unsafe fn subscribe() {
+    input::bind("save position", VK_NUMPAD4, || unsafe { save(HOTKEY_SLOT) });
+    input::bind("teleport", VK_NUMPAD5, || unsafe { teleport(HOTKEY_SLOT) });
+}
+
+/// Save the pawn's location and the view's rotation to `slot` of the current
+/// map. Call on the game thread.
+pub unsafe fn save(slot: u32) {
+    match try_save(slot) {
+        Ok(()) => info!("Saved position {}.", slot),
+        Err(e) => warn!("Failed to save position {}: {}", slot, e),
+    }
+}
+
+/// Move the pawn to `slot` of the current map. Call on the game thread.
+pub unsafe fn teleport(slot: u32) {
+    if passive::refuses("Teleporting") {
+        return;
+    }
+
+    match try_teleport(slot) {
+        Ok(()) => info!("Teleported to position {}.", slot),
+        Err(e) => warn!("Failed to teleport to position {}: {}", slot, e),
+    }
+}
+
+/// Print the saved slots of the current map. Call on the game thread.
+pub unsafe fn list() {
+    let result = with_slots(|slots| {
+        for (number, slot) in &slots.slots {
+            info!("{}: {:?} {:?}", number, slot.location, slot.rotation);
+        }
+
+        info!("{} has {} saved positions.", slots.map, slots.slots.len());
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        warn!("Failed to list positions: {}", e);
+    }
+}
+
+unsafe fn try_save(slot: u32) -> Result<(), Error> {
+    let (pawn, controller) = player()?;
+
+    let rotation = (*controller)
+        .property("Rotation")
+        .ok_or(Error::PropertyNotFound("Rotation"))?;
+
+    let saved = Slot {
+        location: user::location(pawn).map_err(|_| Error::PropertyNotFound("Location"))?,
+        rotation: rotation.cast::<Rotator>().read_unaligned(),
+    };
+
+    with_slots(|slots| {
+        slots.slots.insert(slot, saved);
+        Ok(slots.save()?)
+    })
+}
+
+unsafe fn try_teleport(slot: u32) -> Result<(), Error> {
+    let saved = with_slots(|slots| {
+        slots
+            .slots
+            .get(&slot)
+            .copied()
+            .ok_or(Error::EmptySlot(slot))
+    })?;
+
+    let (pawn, controller) = player()?;
+
+    user::set_location(pawn, saved.location).map_err(|_| Error::Call("SetLocation"))?;
+
+    user::set_rotation(controller, saved.rotation).map_err(|_| Error::Call("SetRotation"))?;
+
+    // Don't keep falling or sliding at the new position.
+    if let Some(velocity) = (*pawn).property("Velocity") {
+        velocity.cast::<Vector>().write_unaligned(Vector::default());
+    }
+
+    Ok(())
+}
+
+unsafe fn player() -> Result<(*mut Object, *mut Object), Error> {
+    let context = user::context().ok_or(Error::NoPawn)?;
+    let pawn = context.pawn.ok_or(Error::NoPawn)?;
+    Ok((pawn.cast(), context.controller.cast()))
+}
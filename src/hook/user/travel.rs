@@ -0,0 +1,175 @@
+use crate::game::{Array, FString, Object};
+use crate::hook::reflect::{self, Value};
+use crate::hook::{gmalloc, passive, user};
+use crate::GLOBAL_OBJECTS;
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// Each fast travel station's definition names it and its map.
+const DEFINITION: &str = "Class WillowGame.FastTravelStationDefinition";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("there's no player controller")]
+    NoController,
+
+    #[error("no class named {0}")]
+    ClassNotFound(&'static str),
+
+    #[error("no station named \"{0}\"")]
+    StationNotFound(String),
+
+    #[error("{0}")]
+    Reflect(#[from] reflect::Error),
+
+    #[error("{0}")]
+    Allocate(#[from] gmalloc::Error),
+}
+
+struct Station {
+    definition: *mut Object,
+
+    /// The name the travel menu shows, e.g., "Sanctuary".
+    display_name: String,
+
+    /// The definition's object name, which the discovered list holds.
+    name: String,
+}
+
+/// Print every fast travel station the game has loaded, and whether the player
+/// discovered it. Call on the game thread.
+pub unsafe fn list() {
+    let result = stations().and_then(|stations| {
+        let discovered = discovered(controller()?)?;
+
+        for station in &stations {
+            let mark = if discovered.contains(&station.name) {
+                ' '
+            } else {
+                '*'
+            };
+
+            info!("{} {} ({})", mark, station.display_name, station.name);
+        }
+
+        info!("{} stations. * marks the undiscovered.", stations.len());
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        warn!("Failed to list fast travel stations: {}", e);
+    }
+}
+
+/// Fast travel to the station called `name`, ignoring case. Call on the game
+/// thread.
+pub unsafe fn travel(name: &str) {
+    if passive::refuses("Fast travel") {
+        return;
+    }
+
+    match try_travel(name) {
+        Ok(()) => info!("Traveling to {}.", name),
+        Err(e) => warn!("Failed to travel to {}: {}", name, e),
+    }
+}
+
+/// Mark every loaded station as discovered, so that the travel menu lists it.
+/// Call on the game thread.
+pub unsafe fn unlock_all() {
+    if passive::refuses("Unlocking fast travel stations") {
+        return;
+    }
+
+    match try_unlock_all() {
+        Ok(count) => info!("Unlocked {} fast travel stations.", count),
+        Err(e) => warn!("Failed to unlock fast travel stations: {}", e),
+    }
+}
+
+unsafe fn try_travel(name: &str) -> Result<(), Error> {
+    let station = stations()?
+        .into_iter()
+        .find(|s| s.display_name.eq_ignore_ascii_case(name) || s.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| Error::StationNotFound(name.to_owned()))?;
+
+    reflect::call_by_name(
+        "WillowGame.WillowPlayerController.ServerTeleportPlayerToStation",
+        controller()?,
+        &[Value::Object(station.definition)],
+    )?;
+
+    Ok(())
+}
+
+unsafe fn try_unlock_all() -> Result<usize, Error> {
+    let controller = controller()?;
+    let discovered = discovered(controller)?;
+    let list = activated_list(controller)?;
+    let mut count = 0;
+
+    for station in stations()? {
+        if !discovered.contains(&station.name) {
+            list.push(gmalloc::string(&station.name)?)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+unsafe fn controller() -> Result<*mut Object, Error> {
+    user::controller()
+        .map(|c| c.cast())
+        .ok_or(Error::NoController)
+}
+
+unsafe fn stations() -> Result<Vec<Station>, Error> {
+    let class = GLOBAL_OBJECTS
+        .get()
+        .find(DEFINITION)
+        .ok_or(Error::ClassNotFound(DEFINITION))?;
+
+    let mut stations = Vec::new();
+
+    for object in GLOBAL_OBJECTS.get().iter() {
+        if (*object).is_default_object() || !(*object).is(class.cast()) {
+            continue;
+        }
+
+        if let Some(name) = (*object).name() {
+            stations.push(Station {
+                definition: object,
+                display_name: (*object).get_property("StationDisplayName")?,
+                name: name.into_owned(),
+            });
+        }
+    }
+
+    stations.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    Ok(stations)
+}
+
+/// The controller's list of the stations the player discovered, by name.
+unsafe fn activated_list(controller: *mut Object) -> Result<&'static mut Array<FString>, Error> {
+    const LIST: &str = "ActivatedTeleportersList";
+
+    let list = (*controller)
+        .property(LIST)
+        .ok_or_else(|| reflect::Error::PropertyNotFound(LIST.to_owned()))?;
+
+    Ok(&mut *list.cast())
+}
+
+unsafe fn discovered(controller: *mut Object) -> Result<Vec<String>, Error> {
+    Ok(activated_list(controller)?
+        .iter()
+        .map(|name| {
+            name.to_string()
+                .to_string_lossy()
+                .trim_end_matches('\0')
+                .to_owned()
+        })
+        .collect())
+}
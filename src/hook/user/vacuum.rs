@@ -0,0 +1,129 @@
+use crate::game::{Object, Vector};
+use crate::hook::reflect;
+use crate::hook::tracker;
+use crate::hook::user::feature::{self, Feature};
+use crate::hook::user::{self, Interval, PlayerContext};
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use log::info;
+use winapi::um::winuser::VK_NUMPAD7;
+
+/// Every drop, from guns and shields to money and ammo.
+pub(super) const PICKUP: &str = "WillowGame.WillowPickup";
+
+/// How often to pull pickups in. Pulling every tick would fight their physics.
+const INTERVAL: Duration = Duration::from_millis(500);
+
+/// Pickups this close, in unreal units, are left where they are.
+const CLOSE_ENOUGH: f32 = 100.0;
+
+/// The filters, set from the console thread.
+static SETTINGS: Mutex<Settings> = Mutex::new(Settings {
+    radius: 3000.0,
+    rarity: 0,
+});
+
+#[derive(Clone, Copy)]
+struct Settings {
+    /// Only pickups within this many unreal units of the player are pulled.
+    radius: f32,
+
+    /// Only items of at least this rarity level are pulled, e.g., 0 for all.
+    rarity: i32,
+}
+
+fn settings() -> MutexGuard<'static, Settings> {
+    SETTINGS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Pull the drops around the player to their feet.
+pub unsafe fn subscribe() {
+    tracker::track(PICKUP);
+    let pull = Interval::new(INTERVAL);
+    feature::register("vacuum", VK_NUMPAD7, Vacuum { pull });
+}
+
+struct Vacuum {
+    pull: Interval,
+}
+
+impl Feature for Vacuum {
+    fn name(&self) -> &'static str {
+        "Loot vacuum"
+    }
+
+    unsafe fn on_tick(&mut self, context: PlayerContext) -> Result<(), reflect::Error> {
+        if self.pull.is_due() {
+            pull(context)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn set_radius(radius: f32) {
+    settings().radius = radius;
+    info!("The loot vacuum reaches {} units.", radius);
+}
+
+pub fn set_rarity(rarity: i32) {
+    settings().rarity = rarity;
+    info!("The loot vacuum pulls items of rarity {} and up.", rarity);
+}
+
+unsafe fn pull(context: PlayerContext) -> Result<(), reflect::Error> {
+    let Settings { radius, rarity } = *settings();
+    let pulled = pull_matching(context, radius, |pickup| Ok(rarity_of(pickup)? >= rarity))?;
+
+    if pulled > 0 {
+        info!("Pulled in {} pickups.", pulled);
+    }
+
+    Ok(())
+}
+
+/// Move the pickups within `radius` of the player for which `filter` is true
+/// to the player, who then touches them. Returns how many moved.
+pub(super) unsafe fn pull_matching(
+    context: PlayerContext,
+    radius: f32,
+    mut filter: impl FnMut(*mut Object) -> Result<bool, reflect::Error>,
+) -> Result<usize, reflect::Error> {
+    let pawn = match context.pawn {
+        Some(pawn) => pawn.cast::<Object>(),
+        None => return Ok(0),
+    };
+
+    let destination = user::location(pawn)?;
+    let mut pulled = 0;
+
+    for pickup in tracker::instances(PICKUP) {
+        let pickup = match pickup.get() {
+            Some(pickup) => pickup,
+            None => continue,
+        };
+
+        let distance = user::location(pickup)?.distance(destination);
+
+        if distance <= CLOSE_ENOUGH || distance > radius || !filter(pickup)? {
+            continue;
+        }
+
+        user::set_location(pickup, destination + Vector::new(0.0, 0.0, CLOSE_ENOUGH))?;
+        pulled += 1;
+    }
+
+    Ok(pulled)
+}
+
+/// The rarity level of the item in `pickup`, or 0 if it's empty.
+unsafe fn rarity_of(pickup: *mut Object) -> Result<i32, reflect::Error> {
+    let inventory: *mut Object = (*pickup).get_property("Inventory")?;
+
+    match inventory.as_ref() {
+        Some(inventory) => inventory.get_property("RarityLevel"),
+        None => Ok(0),
+    }
+}
@@ -0,0 +1,235 @@
+use crate::game::{self, NameIndex, Object, Rotator, Vector};
+use crate::hook::reflect::{self, Value};
+use crate::hook::{passive, scheduler, tracker, user};
+
+use log::{info, warn};
+use thiserror::Error;
+
+/// Every drivable vehicle, e.g., the ones Catch-A-Ride stations spawn.
+const VEHICLE: &str = "WillowGame.WillowVehicle";
+
+/// The Catch-A-Ride consoles, which spawn vehicles onto their pads.
+const TERMINAL: &str = "WillowGame.VehicleSpawnStationTerminal";
+const SPAWN: &str = "WillowGame.VehicleSpawnStationTerminal.SpawnVehicle";
+
+/// How long to give a spawned vehicle to show up in the tracker before moving
+/// it.
+const SPAWN_DELAY: f32 = 0.5;
+
+/// How far in front of the player to put the vehicle, in unreal units, so that
+/// it doesn't land on them.
+const DISTANCE: f32 = 500.0;
+
+/// How high above the player's feet to put the vehicle, so that it drops onto
+/// the ground rather than into it.
+const HEIGHT: f32 = 100.0;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("reflect error: {0}")]
+    Reflect(#[from] reflect::Error),
+
+    #[error("there's no pawn to bring a vehicle to")]
+    NoPawn,
+
+    #[error("the player is already driving")]
+    Driving,
+
+    #[error("there's no free vehicle or Catch-A-Ride on this map")]
+    NoStation,
+
+    #[error("the vehicle has no physics mesh")]
+    NoMesh,
+}
+
+/// Keep the registries of vehicles and Catch-A-Rides warm for `summon`.
+pub unsafe fn subscribe() {
+    tracker::track(VEHICLE);
+    tracker::track(TERMINAL);
+}
+
+/// Move the nearest free vehicle to in front of the player, or spawn one at
+/// the nearest Catch-A-Ride and then move it. Call on the game thread.
+pub unsafe fn summon() {
+    if passive::refuses("Summoning a vehicle") {
+        return;
+    }
+
+    match try_move() {
+        Ok(true) => info!("Summoned a vehicle."),
+        Ok(false) => match spawn() {
+            Ok(()) => {
+                info!("Spawned a vehicle at the nearest Catch-A-Ride.");
+                scheduler::after(SPAWN_DELAY, || unsafe { bring_spawned() });
+            }
+            Err(e) => warn!("Failed to summon a vehicle: {}", e),
+        },
+        Err(e) => warn!("Failed to summon a vehicle: {}", e),
+    }
+}
+
+unsafe fn bring_spawned() {
+    match try_move() {
+        Ok(true) => info!("Summoned a vehicle."),
+        Ok(false) => warn!("The spawned vehicle didn't show up, so it's at the Catch-A-Ride."),
+        Err(e) => warn!("Failed to summon the spawned vehicle: {}", e),
+    }
+}
+
+/// Move the nearest vehicle that nobody is driving. `false` if there's none.
+unsafe fn try_move() -> Result<bool, Error> {
+    let context = user::context().ok_or(Error::NoPawn)?;
+    let pawn: *mut Object = context.pawn.ok_or(Error::NoPawn)?.cast();
+
+    // While driving, the controller's pawn is the vehicle, and only vehicles
+    // have drivers.
+    if (*pawn).property("Driver").is_some() {
+        return Err(Error::Driving);
+    }
+
+    let here = user::location(pawn)?;
+    let mut nearest: Option<(f32, *mut Object)> = None;
+
+    for vehicle in tracker::instances(VEHICLE) {
+        if let Some(vehicle) = vehicle.get() {
+            if user::object_property(vehicle, "Driver").is_some() {
+                continue;
+            }
+
+            let distance = user::location(vehicle)?.distance(here);
+
+            if nearest.map_or(true, |(d, _)| distance < d) {
+                nearest = Some((distance, vehicle));
+            }
+        }
+    }
+
+    let vehicle = match nearest {
+        Some((_, vehicle)) => vehicle,
+        None => return Ok(false),
+    };
+
+    // Only turn it to face the same way, not to pitch into the ground.
+    let mut rotation = user::rotation(context.controller.cast())?;
+    rotation.pitch = 0;
+    rotation.roll = 0;
+
+    let forward = rotation.to_direction();
+    let destination = here + forward * DISTANCE + Vector::new(0.0, 0.0, HEIGHT);
+
+    user::set_location(vehicle, destination)?;
+    user::set_rotation(vehicle, rotation)?;
+
+    // Vehicles are rigid bodies, so physics puts them back where their body
+    // is unless the body moves too.
+    let mesh = user::object_property(vehicle, "Mesh").ok_or(Error::NoMesh)?;
+    set_rigid_body(mesh, destination, rotation)?;
+
+    Ok(true)
+}
+
+/// Spawn a vehicle at the Catch-A-Ride nearest the player, the way its
+/// console does.
+unsafe fn spawn() -> Result<(), Error> {
+    let context = user::context().ok_or(Error::NoPawn)?;
+    let pawn: *mut Object = context.pawn.ok_or(Error::NoPawn)?.cast();
+    let here = user::location(pawn)?;
+
+    let mut nearest: Option<(f32, *mut Object)> = None;
+
+    for terminal in tracker::instances(TERMINAL) {
+        if let Some(terminal) = terminal.get() {
+            let distance = user::location(terminal)?.distance(here);
+
+            if nearest.map_or(true, |(d, _)| distance < d) {
+                nearest = Some((distance, terminal));
+            }
+        }
+    }
+
+    let (_, terminal) = nearest.ok_or(Error::NoStation)?;
+
+    let function = reflect::find_function(SPAWN)
+        .ok_or_else(|| reflect::Error::FunctionNotFound(SPAWN.to_owned()))?;
+
+    // The other parameters pick the first vehicle type and pad.
+    let arguments = [("PC", Value::Object(context.controller.cast()))];
+    reflect::call_named(terminal, function, &arguments)?;
+
+    Ok(())
+}
+
+/// Move `mesh`'s rigid body to `location` and `rotation`, and stop it.
+unsafe fn set_rigid_body(
+    mesh: *mut Object,
+    location: Vector,
+    rotation: Rotator,
+) -> Result<(), reflect::Error> {
+    #[repr(C)]
+    struct SetRBPosition {
+        position: Vector,
+        bone: NameIndex,
+    }
+
+    #[repr(C)]
+    struct SetRBRotation {
+        rotation: Rotator,
+        bone: NameIndex,
+    }
+
+    #[repr(C)]
+    struct SetRBVelocity {
+        velocity: Vector,
+        add_to_current: u32,
+    }
+
+    let none = || NameIndex {
+        index: 0,
+        number: 0,
+    };
+
+    let mut position = SetRBPosition {
+        position: location,
+        bone: none(),
+    };
+
+    let mut rotation = SetRBRotation {
+        rotation,
+        bone: none(),
+    };
+
+    let mut stop = SetRBVelocity {
+        velocity: Vector::default(),
+        add_to_current: 0,
+    };
+
+    call(
+        mesh,
+        "Engine.PrimitiveComponent.SetRBPosition",
+        &mut position,
+    )?;
+    call(
+        mesh,
+        "Engine.PrimitiveComponent.SetRBRotation",
+        &mut rotation,
+    )?;
+    call(
+        mesh,
+        "Engine.PrimitiveComponent.SetRBLinearVelocity",
+        &mut stop,
+    )?;
+    call(
+        mesh,
+        "Engine.PrimitiveComponent.SetRBAngularVelocity",
+        &mut stop,
+    )
+}
+
+unsafe fn call<P>(
+    object: *mut Object,
+    function: &str,
+    parameters: &mut P,
+) -> Result<(), reflect::Error> {
+    game::call_checked(object, &format!("Function {}", function), parameters)
+        .ok_or_else(|| reflect::Error::FunctionNotFound(function.to_owned()))
+}
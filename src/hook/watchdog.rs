@@ -0,0 +1,88 @@
+use crate::global::Global;
+use crate::hook::{self, render, InFlight};
+
+use std::mem;
+use std::ptr;
+
+use log::{info, warn};
+use winapi::ctypes::c_int;
+use winapi::shared::d3d9types::D3DDEVICE_CREATION_PARAMETERS;
+use winapi::shared::minwindef::{LPARAM, LRESULT, WPARAM};
+use winapi::shared::windef::{HHOOK__, HWND__};
+use winapi::um::winuser::{
+    CallNextHookEx, GetWindowThreadProcessId, IsWindow, SetWindowsHookExW, UnhookWindowsHookEx,
+    CWPSTRUCT, HC_ACTION, WH_CALLWNDPROC, WM_DESTROY,
+};
+
+/// The game's main window, once a frame has been drawn.
+static WINDOW: Global<HWND__> = Global::null();
+
+/// Sees the messages sent to the game's window, whether or not the overlay
+/// subclassed it.
+static HOOK: Global<HHOOK__> = Global::null();
+
+/// Remember the game's window from the first frame, and watch its messages to
+/// notice when it's gone.
+pub fn install() {
+    render::on_frame(|device| unsafe {
+        if WINDOW.is_null() {
+            let mut parameters: D3DDEVICE_CREATION_PARAMETERS = mem::zeroed();
+            (*device).GetCreationParameters(&mut parameters);
+            WINDOW.set(parameters.hFocusWindow);
+            watch(parameters.hFocusWindow);
+        }
+    });
+}
+
+unsafe fn watch(window: *mut HWND__) {
+    let thread = GetWindowThreadProcessId(window, ptr::null_mut());
+    let hook = SetWindowsHookExW(
+        WH_CALLWNDPROC,
+        Some(my_call_wnd_proc),
+        ptr::null_mut(),
+        thread,
+    );
+
+    if hook.is_null() {
+        warn!("Failed to watch the game window, so closing it may not unhook.");
+    }
+
+    HOOK.set(hook);
+}
+
+/// Stop watching the game's window. Call before draining in-flight calls,
+/// since the hook procedure is one of them.
+pub unsafe fn uninstall() {
+    if !HOOK.is_null() {
+        UnhookWindowsHookEx(HOOK.ptr());
+        HOOK.set(ptr::null_mut());
+    }
+}
+
+unsafe extern "system" fn my_call_wnd_proc(code: c_int, w: WPARAM, l: LPARAM) -> LRESULT {
+    let _in_flight = InFlight::enter();
+
+    if code == HC_ACTION {
+        let message = &*(l as *const CWPSTRUCT);
+
+        if message.hwnd == WINDOW.ptr() {
+            on_message(message.message);
+        }
+    }
+
+    CallNextHookEx(ptr::null_mut(), code, w, l)
+}
+
+/// Asks to unhook once the window is being destroyed, since the game is
+/// exiting and our hooks would otherwise run while it tears down the engine.
+fn on_message(message: u32) {
+    if message == WM_DESTROY && !hook::unload_requested() {
+        info!("The game window is closing. Unhooking.");
+        hook::request_unload();
+    }
+}
+
+/// Whether the game's window was destroyed, in case we missed the message.
+pub unsafe fn game_exiting() -> bool {
+    !WINDOW.is_null() && IsWindow(WINDOW.ptr()) == 0
+}
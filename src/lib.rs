@@ -14,6 +14,7 @@ compile_error!("You cannot generate an SDK and hook the game at the same time. D
 use std::ffi::c_void;
 use std::io::{self, Read};
 use std::ptr;
+use std::time::{Duration, Instant};
 
 use log::{error, info, warn};
 use simplelog::{Config, LevelFilter, TermLogger, TerminalMode};
@@ -36,18 +37,32 @@ mod dump;
 mod game;
 use game::{Names, Objects};
 
+mod global;
+use global::Global;
+
 #[cfg(feature = "hook")]
 mod hook;
 
 mod module;
 use module::Module;
 
+mod profile;
+use profile::GameProfile;
+
 mod timeit;
 use timeit::TimeIt;
 
-pub static mut GLOBAL_NAMES: *const Names = ptr::null();
-pub static mut GLOBAL_OBJECTS: *const Objects = ptr::null();
-pub static mut PROCESS_EVENT: *mut c_void = ptr::null_mut();
+#[cfg(feature = "hook")]
+mod wildcard;
+
+pub static GLOBAL_NAMES: Global<Names> = Global::null();
+pub static GLOBAL_OBJECTS: Global<Objects> = Global::null();
+pub static PROCESS_EVENT: Global<c_void> = Global::null();
+
+/// How long to wait for the game to initialize the globals after injection.
+const ATTACH_TIMEOUT: Duration = Duration::from_secs(120);
+const ATTACH_POLL_INTERVAL_MS: u32 = 250;
+const ATTACH_LOG_INTERVAL: Duration = Duration::from_secs(5);
 
 fn idle() {
     println!("Idling. Press enter to continue.");
@@ -68,6 +83,12 @@ enum Error {
     #[error("{0}")]
     Module(#[from] module::Error),
 
+    #[error("the game did not initialize its globals within {0:?}")]
+    AttachTimeout(Duration),
+
+    #[error("the global names or objects are still empty")]
+    GlobalsEmpty,
+
     #[error("cannot find global names")]
     NamesNotFound,
 
@@ -76,77 +97,54 @@ enum Error {
 
     #[error("cannot find ProcessEvent")]
     ProcessEventNotFound,
+
+    #[error("cannot find ProcessEvent in UObject's vtable")]
+    ProcessEventIndexNotFound,
+
+    #[error("not running in a supported game")]
+    UnknownGame,
 }
 
-unsafe fn find_global_names(game: &Module) -> Result<*const Names, Error> {
-    const PATTERN: [Option<u8>; 12] = [
-        Some(0x66),
-        Some(0x0F),
-        Some(0xEF),
-        Some(0xC0),
-        Some(0x66),
-        Some(0x0F),
-        Some(0xD6),
-        Some(0x05),
-        None,
-        None,
-        None,
-        None,
-    ];
-
-    let global_names = game.find_pattern(&PATTERN).ok_or(Error::NamesNotFound)?;
-
-    let global_names = (global_names + 8) as *const *const Names;
+unsafe fn find_global_names(game: &Module, profile: &dyn GameProfile) -> Result<*mut Names, Error> {
+    let signature = profile.global_names();
+
+    let global_names = game
+        .find_pattern(signature.pattern)
+        .ok_or(Error::NamesNotFound)?;
+
+    let global_names = (global_names + signature.offset) as *const *mut Names;
 
     Ok(global_names.read_unaligned())
 }
 
-unsafe fn find_global_objects(game: &Module) -> Result<*const Objects, Error> {
-    const PATTERN: [Option<u8>; 9] = [
-        Some(0x8B),
-        Some(0x0D),
-        None,
-        None,
-        None,
-        None,
-        Some(0x8B),
-        Some(0x34),
-        Some(0xB9),
-    ];
+unsafe fn find_global_objects(
+    game: &Module,
+    profile: &dyn GameProfile,
+) -> Result<*mut Objects, Error> {
+    let signature = profile.global_objects();
 
-    let global_objects = game.find_pattern(&PATTERN).ok_or(Error::ObjectsNotFound)?;
+    let global_objects = game
+        .find_pattern(signature.pattern)
+        .ok_or(Error::ObjectsNotFound)?;
 
-    let global_objects = (global_objects + 2) as *const *const Objects;
+    let global_objects = (global_objects + signature.offset) as *const *mut Objects;
 
     Ok(global_objects.read_unaligned())
 }
 
-unsafe fn find_process_event(game: &Module) -> Result<*mut c_void, Error> {
-    const PATTERN: [Option<u8>; 15] = [
-        Some(0x50),
-        Some(0x51),
-        Some(0x52),
-        Some(0x8B),
-        Some(0xCE),
-        Some(0xE8),
-        None,
-        None,
-        None,
-        None,
-        Some(0x5E),
-        Some(0x5D),
-        Some(0xC2),
-        Some(0x0C),
-        Some(0x00),
-    ];
-
-    // 1. Find the first address A that matches the above pattern.
+unsafe fn find_process_event(
+    game: &Module,
+    profile: &dyn GameProfile,
+) -> Result<*mut c_void, Error> {
+    let signature = profile.process_event();
+
+    // 1. Find the first address A that matches the signature's pattern.
     let a = game
-        .find_pattern(&PATTERN)
+        .find_pattern(signature.pattern)
         .ok_or(Error::ProcessEventNotFound)?;
 
-    // 2. Offset A by six bytes to get the address of the CALL immediate. Call that address B.
-    let b = a + 6;
+    // 2. Offset A to get the address of the CALL immediate. Call that address B.
+    let b = a + signature.offset;
 
     // 3. Do an unaligned* usize pointer read operation on B to get the call immediate. Call that immediate I.
     let i = (b as *const usize).read_unaligned();
@@ -159,24 +157,68 @@ unsafe fn find_process_event(game: &Module) -> Result<*mut c_void, Error> {
 }
 
 unsafe fn find_globals() -> Result<(), Error> {
+    let profile = profile::detect().ok_or(Error::UnknownGame)?;
+    let game = Module::from(profile.module())?;
+
+    GLOBAL_NAMES.set(find_global_names(&game, profile)?);
+    GLOBAL_OBJECTS.set(find_global_objects(&game, profile)?);
+    PROCESS_EVENT.set(find_process_event(&game, profile)?);
+
+    if GLOBAL_NAMES.get().is_empty() || GLOBAL_OBJECTS.get().is_empty() {
+        return Err(Error::GlobalsEmpty);
+    }
+
+    let expected = profile.process_event_index();
+    let index = game::find_process_event_index(expected).ok_or(Error::ProcessEventIndexNotFound)?;
+
+    if index != expected {
+        warn!(
+            "ProcessEvent is at vtable index {}, not {}.",
+            index, expected
+        );
+    }
+
+    Ok(())
+}
+
+/// Find the globals, retrying until the game has initialized them. This lets
+/// a launcher inject us at process start, before the game has unpacked its
+/// code or loaded any names and objects.
+unsafe fn wait_for_globals() -> Result<(), Error> {
     let _time = TimeIt::new("find globals");
 
-    let game = Module::from("BorderlandsPreSequel.exe")?;
+    let start = Instant::now();
+    let mut last_log: Option<Instant> = None;
+
+    loop {
+        let error = match find_globals() {
+            Ok(()) => break,
+            Err(e) => e,
+        };
+
+        if start.elapsed() > ATTACH_TIMEOUT {
+            warn!("Still unable to find the globals: {}", error);
+            return Err(Error::AttachTimeout(ATTACH_TIMEOUT));
+        }
 
-    GLOBAL_NAMES = find_global_names(&game)?;
-    info!("GLOBAL_NAMES = {:?}", GLOBAL_NAMES);
+        if last_log.map_or(true, |t| t.elapsed() >= ATTACH_LOG_INTERVAL) {
+            last_log = Some(Instant::now());
+            info!("Waiting for the game to initialize: {}", error);
+        }
 
-    GLOBAL_OBJECTS = find_global_objects(&game)?;
-    info!("GLOBAL_OBJECTS = {:?}", GLOBAL_OBJECTS);
+        Sleep(ATTACH_POLL_INTERVAL_MS);
+    }
 
-    PROCESS_EVENT = find_process_event(&game)?;
-    info!("PROCESS_EVENT = {:?}", PROCESS_EVENT);
+    info!("Attached to {}.", profile::current().name());
+    info!("GLOBAL_NAMES = {:?}", GLOBAL_NAMES.ptr());
+    info!("GLOBAL_OBJECTS = {:?}", GLOBAL_OBJECTS.ptr());
+    info!("PROCESS_EVENT = {:?}", PROCESS_EVENT.ptr());
 
     Ok(())
 }
 
 unsafe fn run() -> Result<(), Error> {
-    find_globals()?;
+    wait_for_globals()?;
 
     #[cfg(feature = "dump")]
     {
@@ -187,8 +229,8 @@ unsafe fn run() -> Result<(), Error> {
 
     #[cfg(feature = "hook")]
     {
-        let _hook = hook::Hook::new()?;
-        idle();
+        let mut hook = hook::Hook::new()?;
+        hook::console::run(&mut hook);
     }
 
     Ok(())
@@ -205,10 +247,25 @@ unsafe extern "system" fn on_attach(dll: LPVOID) -> DWORD {
 
         if let Err(e) = run() {
             error!("{}", e);
+            idle();
+        } else if cfg!(feature = "dump") {
+            // Keep the dump's log up until it's read. The hook unloads from a
+            // hotkey, so it shouldn't wait.
+            idle();
+        }
+    }
+
+    #[cfg(feature = "hook")]
+    {
+        if !hook::can_unload() {
+            error!("A detour is still attached, so the DLL stays loaded.");
+            FreeConsole();
+            return 0;
         }
     }
 
-    idle();
+    // The hook waited for in-flight calls, but a call can still be between
+    // leaving the in-flight count and returning out of this module.
     println!("Sleeping 1 second before detaching.");
     Sleep(1000);
 
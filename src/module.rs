@@ -2,6 +2,11 @@ use std::mem::{self, MaybeUninit};
 
 use thiserror::Error;
 use winapi::shared::minwindef::HMODULE;
+#[cfg(feature = "hook")]
+use winapi::um::libloaderapi::{
+    GetModuleHandleExW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+    GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+};
 use winapi::um::{
     libloaderapi::GetModuleHandleW,
     processthreadsapi::GetCurrentProcess,
@@ -90,6 +95,41 @@ impl Module {
         Ok(module)
     }
 
+    /// Construct the module whose image contains `address`, e.g., the address of
+    /// one of our own functions to get this DLL.
+    #[cfg(feature = "hook")]
+    pub fn containing(address: usize) -> Result<Module, Error> {
+        let name = format!("module at {:#x}", address);
+
+        let (module, info) = unsafe {
+            const FLAGS: u32 = GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS
+                | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT;
+
+            let mut module = std::ptr::null_mut();
+            let found = GetModuleHandleExW(FLAGS, address as *const u16, &mut module);
+
+            if found == 0 {
+                return Err(Error::new(&name, ErrorKind::NullModule));
+            }
+
+            let info = Module::get_info(module)
+                .ok_or_else(|| Error::new(&name, ErrorKind::GetModuleInformation))?;
+
+            (module, info)
+        };
+
+        let base = info.lpBaseOfDll as usize;
+        let size = info.SizeOfImage as usize;
+
+        Ok(Module {
+            module,
+            name,
+            base,
+            size,
+            end: base + size,
+        })
+    }
+
     fn get_handle(name: &str) -> Result<HMODULE, Error> {
         let handle = unsafe {
             let wide_name = wide_format!("{}", name);
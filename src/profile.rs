@@ -0,0 +1,331 @@
+use crate::game::{Function, Object};
+use crate::module::Module;
+
+use std::ffi::c_void;
+use std::mem;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A byte pattern to scan the game's module for, and where the interesting
+/// operand sits relative to the match.
+pub struct Signature {
+    pub pattern: &'static [Option<u8>],
+    pub offset: usize,
+}
+
+/// What differs between the games we support.
+///
+/// The generated SDK is still specific to the game it was dumped from, so a
+/// hook build only runs its SDK-based features on that game.
+pub trait GameProfile: Sync {
+    fn name(&self) -> &'static str;
+
+    /// The executable's file name, used to detect the running game.
+    fn module(&self) -> &'static str;
+
+    /// The absolute address of the global names array sits at `offset`.
+    fn global_names(&self) -> Signature;
+
+    /// The absolute address of the global objects array sits at `offset`.
+    fn global_objects(&self) -> Signature;
+
+    /// A `CALL rel32` to `ProcessEvent` whose immediate sits at `offset`.
+    fn process_event(&self) -> Signature;
+
+    /// The address of `GMalloc`, the game's `FMalloc*`, sits at `offset`.
+    #[cfg(feature = "hook")]
+    fn global_malloc(&self) -> Signature;
+
+    /// The start of `FName::Init`, which finds or adds a name.
+    fn fname_init(&self) -> Signature;
+
+    /// The start of `UObject::StaticConstructObject`, which creates objects.
+    #[cfg(feature = "hook")]
+    fn construct_object(&self) -> Signature;
+
+    /// The address of `GEngine`, the game's `UEngine*`, sits at `offset`.
+    #[cfg(feature = "hook")]
+    fn global_engine(&self) -> Signature;
+
+    /// The address of `GWorld`, the current `UWorld*`, sits at `offset`.
+    #[cfg(feature = "hook")]
+    fn global_world(&self) -> Signature;
+
+    /// `ProcessEvent`'s index in `UObject`'s vtable. Checked against the
+    /// address that `process_event` finds before it's used.
+    fn process_event_index(&self) -> usize;
+
+    /// Call `ProcessEvent`, which is at `address`, with this game's calling
+    /// convention. The Pre-Sequel builds it `thiscall`, which is `fastcall`
+    /// with an unused `edx`.
+    unsafe fn call_process_event(
+        &self,
+        address: usize,
+        object: *mut Object,
+        function: *mut Function,
+        parameters: *mut c_void,
+    ) {
+        type ProcessEvent = unsafe extern "fastcall" fn(
+            this: *mut Object,
+            edx: usize,
+            function: *mut Function,
+            parameters: *mut c_void,
+            return_value: *mut usize,
+        );
+
+        let process_event = mem::transmute::<usize, ProcessEvent>(address);
+        let mut return_value = 0;
+        process_event(object, 0, function, parameters, &mut return_value);
+    }
+}
+
+const GLOBAL_NAMES: Signature = Signature {
+    pattern: &[
+        Some(0x66),
+        Some(0x0F),
+        Some(0xEF),
+        Some(0xC0),
+        Some(0x66),
+        Some(0x0F),
+        Some(0xD6),
+        Some(0x05),
+        None,
+        None,
+        None,
+        None,
+    ],
+    offset: 8,
+};
+
+const GLOBAL_OBJECTS: Signature = Signature {
+    pattern: &[
+        Some(0x8B),
+        Some(0x0D),
+        None,
+        None,
+        None,
+        None,
+        Some(0x8B),
+        Some(0x34),
+        Some(0xB9),
+    ],
+    offset: 2,
+};
+
+const PROCESS_EVENT: Signature = Signature {
+    pattern: &[
+        Some(0x50),
+        Some(0x51),
+        Some(0x52),
+        Some(0x8B),
+        Some(0xCE),
+        Some(0xE8),
+        None,
+        None,
+        None,
+        None,
+        Some(0x5E),
+        Some(0x5D),
+        Some(0xC2),
+        Some(0x0C),
+        Some(0x00),
+    ],
+    offset: 6,
+};
+
+// journal/20200627.txt records how the ProcessEvent signature was found. The
+// signatures below haven't been written up against a disassembly yet. When one
+// matches nothing, its caller gets `None` or an error, and `GEngine` and
+// `GWorld` fall back to searching the objects array by class.
+#[cfg(feature = "hook")]
+const GLOBAL_MALLOC: Signature = Signature {
+    pattern: &[
+        Some(0x89),
+        Some(0x35),
+        None,
+        None,
+        None,
+        None,
+        Some(0xFF),
+        Some(0xD7),
+        Some(0x8B),
+        Some(0x0D),
+        None,
+        None,
+        None,
+        None,
+    ],
+    offset: 2,
+};
+
+const FNAME_INIT: Signature = Signature {
+    pattern: &[
+        Some(0x55),
+        Some(0x8B),
+        Some(0xEC),
+        Some(0x6A),
+        Some(0xFF),
+        Some(0x68),
+        None,
+        None,
+        None,
+        None,
+        Some(0x64),
+        Some(0xA1),
+        Some(0x00),
+        Some(0x00),
+        Some(0x00),
+        Some(0x00),
+        Some(0x50),
+        Some(0x81),
+        Some(0xEC),
+        Some(0x98),
+        Some(0x0C),
+        Some(0x00),
+        Some(0x00),
+    ],
+    offset: 0,
+};
+
+#[cfg(feature = "hook")]
+const CONSTRUCT_OBJECT: Signature = Signature {
+    pattern: &[
+        Some(0x6A),
+        Some(0xFF),
+        Some(0x68),
+        None,
+        None,
+        None,
+        None,
+        Some(0x64),
+        Some(0xA1),
+        Some(0x00),
+        Some(0x00),
+        Some(0x00),
+        Some(0x00),
+        Some(0x50),
+        Some(0x83),
+        Some(0xEC),
+        Some(0x0C),
+        Some(0x53),
+        Some(0x55),
+        Some(0x56),
+        Some(0x57),
+        Some(0xA1),
+    ],
+    offset: 0,
+};
+
+#[cfg(feature = "hook")]
+const GLOBAL_ENGINE: Signature = Signature {
+    pattern: &[
+        Some(0xA1),
+        None,
+        None,
+        None,
+        None,
+        Some(0x8B),
+        Some(0x88),
+        None,
+        None,
+        None,
+        None,
+        Some(0x85),
+        Some(0xC9),
+        Some(0x74),
+    ],
+    offset: 1,
+};
+
+#[cfg(feature = "hook")]
+const GLOBAL_WORLD: Signature = Signature {
+    pattern: &[
+        Some(0x8B),
+        Some(0x0D),
+        None,
+        None,
+        None,
+        None,
+        Some(0x8B),
+        Some(0x91),
+        None,
+        None,
+        None,
+        None,
+        Some(0x85),
+        Some(0xD2),
+    ],
+    offset: 2,
+};
+
+pub struct PreSequel;
+
+impl GameProfile for PreSequel {
+    fn name(&self) -> &'static str {
+        "Borderlands: The Pre-Sequel"
+    }
+
+    fn module(&self) -> &'static str {
+        "BorderlandsPreSequel.exe"
+    }
+
+    fn global_names(&self) -> Signature {
+        GLOBAL_NAMES
+    }
+
+    fn global_objects(&self) -> Signature {
+        GLOBAL_OBJECTS
+    }
+
+    fn process_event(&self) -> Signature {
+        PROCESS_EVENT
+    }
+
+    #[cfg(feature = "hook")]
+    fn global_malloc(&self) -> Signature {
+        GLOBAL_MALLOC
+    }
+
+    fn fname_init(&self) -> Signature {
+        FNAME_INIT
+    }
+
+    #[cfg(feature = "hook")]
+    fn construct_object(&self) -> Signature {
+        CONSTRUCT_OBJECT
+    }
+
+    #[cfg(feature = "hook")]
+    fn global_engine(&self) -> Signature {
+        GLOBAL_ENGINE
+    }
+
+    #[cfg(feature = "hook")]
+    fn global_world(&self) -> Signature {
+        GLOBAL_WORLD
+    }
+
+    fn process_event_index(&self) -> usize {
+        58
+    }
+}
+
+static PROFILES: [&dyn GameProfile; 1] = [&PreSequel];
+
+/// Index into `PROFILES` of the running game.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Select the profile of whichever supported game loaded us.
+pub fn detect() -> Option<&'static dyn GameProfile> {
+    let index = PROFILES
+        .iter()
+        .position(|profile| Module::from(profile.module()).is_ok())?;
+
+    CURRENT.store(index, Ordering::SeqCst);
+    Some(PROFILES[index])
+}
+
+/// The profile selected by `detect`, or The Pre-Sequel's before detection.
+pub fn current() -> &'static dyn GameProfile {
+    PROFILES[CURRENT.load(Ordering::SeqCst)]
+}
@@ -0,0 +1,45 @@
+/// Match `text` against `pattern`, where `*` matches any run of characters and
+/// `?` matches any single character. Comparison ignores ASCII case.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let text = text.as_bytes();
+
+    let mut p = 0;
+    let mut t = 0;
+
+    // Where to resume if the current attempt at matching after a `*` fails.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+
+            Some(&c) if c == b'?' || c.eq_ignore_ascii_case(&text[t]) => {
+                p += 1;
+                t += 1;
+            }
+
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and try again.
+                Some((star, star_t)) => {
+                    backtrack = Some((star, star_t + 1));
+                    p = star + 1;
+                    t = star_t + 1;
+                }
+
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Whether `pattern` uses any wildcards. Patterns without wildcards are
+/// usually meant as substrings.
+pub fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(|c| c == '*' || c == '?')
+}